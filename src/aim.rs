@@ -0,0 +1,408 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collision::EnemyKdTree,
+    configs::{AUTO_AIM_RANGE, CROSSHAIR_STICK_DISTANCE, LAYER5},
+    enemy::{Boss, Enemy, ExplosionAbility, ShootingAbility},
+    game_state::GameState,
+    input::CombatAction,
+    player::Player,
+    replay::ReplayPlayback,
+    resources::UiFont,
+    ui::systems::in_game_ui::spawn_floating_text,
+    utils::InGameEntity,
+};
+
+pub struct AimPlugin;
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimMode {
+    #[default]
+    Auto,
+    Mouse,
+    Stick,
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetPriority {
+    #[default]
+    Nearest,
+    LowestHealth,
+    HighestThreat,
+    BossFirst,
+    Sticky,
+}
+
+#[derive(Component, Default)]
+pub struct AimTarget(pub Option<Entity>);
+
+#[derive(Resource, Default)]
+pub struct CursorWorldPosition(pub Option<Vec2>);
+
+#[derive(Component)]
+pub struct Crosshair;
+
+#[derive(Component)]
+pub struct TargetMarker;
+
+impl AimMode {
+    pub fn is_manual(self) -> bool {
+        self != AimMode::Auto
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            AimMode::Auto => AimMode::Mouse,
+            AimMode::Mouse => AimMode::Stick,
+            AimMode::Stick => AimMode::Auto,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AimMode::Auto => "Auto",
+            AimMode::Mouse => "Mouse",
+            AimMode::Stick => "Stick",
+        }
+    }
+}
+
+impl TargetPriority {
+    pub fn next(self) -> Self {
+        match self {
+            TargetPriority::Nearest => TargetPriority::LowestHealth,
+            TargetPriority::LowestHealth => TargetPriority::HighestThreat,
+            TargetPriority::HighestThreat => TargetPriority::BossFirst,
+            TargetPriority::BossFirst => TargetPriority::Sticky,
+            TargetPriority::Sticky => TargetPriority::Nearest,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TargetPriority::Nearest => "Nearest",
+            TargetPriority::LowestHealth => "Lowest Health",
+            TargetPriority::HighestThreat => "Highest Threat",
+            TargetPriority::BossFirst => "Boss First",
+            TargetPriority::Sticky => "Sticky",
+        }
+    }
+}
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AimMode>()
+            .init_resource::<TargetPriority>()
+            .init_resource::<CursorWorldPosition>()
+            .add_systems(
+                PreUpdate,
+                aim_at_cursor
+                    .after(InputManagerSystem::Update)
+                    .run_if(in_state(GameState::Combat))
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(
+                OnEnter(GameState::Combat),
+                (spawn_crosshair, spawn_target_marker),
+            )
+            .add_systems(
+                Update,
+                (
+                    cycle_aim_mode,
+                    cycle_target_priority,
+                    update_crosshair,
+                    update_target_marker,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Combat)),
+            );
+    }
+}
+
+fn aim_at_cursor(
+    aim_mode: Res<AimMode>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, (With<Player>, Without<ActionState<CombatAction>>)>,
+    mut cursor_position: ResMut<CursorWorldPosition>,
+    mut action_state: ResMut<ActionState<CombatAction>>,
+) {
+    cursor_position.0 = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .zip(camera_query.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| {
+            camera.viewport_to_world_2d(camera_transform, cursor).ok()
+        });
+
+    if *aim_mode != AimMode::Mouse {
+        return;
+    }
+    if let (Some(cursor), Ok(player_transform)) = (cursor_position.0, player_query.get_single()) {
+        let direction = (cursor - player_transform.translation.truncate()).normalize_or_zero();
+        action_state.set_axis_pair(&CombatAction::Aim, direction);
+    }
+}
+
+fn cycle_aim_mode(
+    mut commands: Commands,
+    mut aim_mode: ResMut<AimMode>,
+    action_state: Res<ActionState<CombatAction>>,
+    player_query: Query<&Transform, (With<Player>, Without<ActionState<CombatAction>>)>,
+    font: Res<UiFont>,
+) {
+    if !action_state.just_pressed(&CombatAction::CycleAimMode) {
+        return;
+    }
+
+    *aim_mode = aim_mode.next();
+    if let Ok(transform) = player_query.get_single() {
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            format!("Aim: {}", aim_mode.label()),
+            Some(Color::WHITE),
+        );
+    }
+}
+
+fn cycle_target_priority(
+    mut commands: Commands,
+    mut priority: ResMut<TargetPriority>,
+    mut target_query: Query<&mut AimTarget>,
+    action_state: Res<ActionState<CombatAction>>,
+    player_query: Query<&Transform, (With<Player>, Without<ActionState<CombatAction>>)>,
+    font: Res<UiFont>,
+) {
+    if !action_state.just_pressed(&CombatAction::CycleTargetPriority) {
+        return;
+    }
+
+    *priority = priority.next();
+    for mut target in target_query.iter_mut() {
+        target.0 = None;
+    }
+    if let Ok(transform) = player_query.get_single() {
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            format!("Target: {}", priority.label()),
+            Some(Color::WHITE),
+        );
+    }
+}
+
+pub fn select_aim_target(
+    mut player_query: Query<(&Transform, &mut AimTarget), With<Player>>,
+    enemy_query: Query<
+        (
+            &Transform,
+            &Enemy,
+            Has<ShootingAbility>,
+            Has<ExplosionAbility>,
+            Has<Boss>,
+        ),
+        Without<Player>,
+    >,
+    enemy_kd_tree: Res<EnemyKdTree>,
+    priority: Res<TargetPriority>,
+) {
+    for (player_transform, mut target) in player_query.iter_mut() {
+        let player_pos = player_transform.translation.truncate();
+        target.0 = select_target(
+            player_pos,
+            target.0,
+            &enemy_query,
+            &enemy_kd_tree,
+            *priority,
+        );
+    }
+}
+
+fn select_target(
+    player_pos: Vec2,
+    current: Option<Entity>,
+    enemy_query: &Query<
+        (
+            &Transform,
+            &Enemy,
+            Has<ShootingAbility>,
+            Has<ExplosionAbility>,
+            Has<Boss>,
+        ),
+        Without<Player>,
+    >,
+    enemy_kd_tree: &EnemyKdTree,
+    priority: TargetPriority,
+) -> Option<Entity> {
+    let distance_to = |entity: Entity| {
+        enemy_query
+            .get(entity)
+            .ok()
+            .map(|(transform, ..)| transform.translation.truncate().distance(player_pos))
+            .filter(|distance| *distance <= AUTO_AIM_RANGE)
+    };
+
+    if priority == TargetPriority::Sticky && current.and_then(distance_to).is_some() {
+        return current;
+    }
+
+    enemy_kd_tree
+        .0
+        .within_radius(&[player_pos.x, player_pos.y], AUTO_AIM_RANGE)
+        .into_iter()
+        .filter_map(|collidable| {
+            let (_, enemy, is_shooter, is_bomber, is_boss) =
+                enemy_query.get(collidable.entity).ok()?;
+            let distance = distance_to(collidable.entity)?;
+            let rank = match priority {
+                TargetPriority::Nearest | TargetPriority::Sticky => 0,
+                TargetPriority::LowestHealth => enemy.health,
+                TargetPriority::HighestThreat => u32::from(!(is_shooter || is_bomber)),
+                TargetPriority::BossFirst => u32::from(!is_boss),
+            };
+            Some((rank, distance, collidable.entity))
+        })
+        .min_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)))
+        .map(|(_, _, entity)| entity)
+}
+
+fn spawn_crosshair(mut commands: Commands, crosshair_query: Query<Entity, With<Crosshair>>) {
+    if !crosshair_query.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Crosshair"),
+            Transform::from_xyz(0.0, 0.0, LAYER5),
+            Visibility::Hidden,
+            Crosshair,
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            for (offset, size) in [
+                (Vec2::new(0.0, 12.0), Vec2::new(3.0, 12.0)),
+                (Vec2::new(0.0, -12.0), Vec2::new(3.0, 12.0)),
+                (Vec2::new(12.0, 0.0), Vec2::new(12.0, 3.0)),
+                (Vec2::new(-12.0, 0.0), Vec2::new(12.0, 3.0)),
+                (Vec2::ZERO, Vec2::splat(3.0)),
+            ] {
+                parent.spawn((
+                    Sprite {
+                        color: Color::srgba(1.0, 1.0, 1.0, 0.9),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_translation(offset.extend(0.0)),
+                ));
+            }
+        });
+}
+
+fn update_crosshair(
+    aim_mode: Res<AimMode>,
+    cursor_position: Res<CursorWorldPosition>,
+    action_state: Res<ActionState<CombatAction>>,
+    player_query: Query<
+        &Transform,
+        (
+            With<Player>,
+            Without<ActionState<CombatAction>>,
+            Without<Crosshair>,
+        ),
+    >,
+    mut crosshair_query: Query<(&mut Transform, &mut Visibility), With<Crosshair>>,
+) {
+    let Ok((mut transform, mut visibility)) = crosshair_query.get_single_mut() else {
+        return;
+    };
+
+    let target = match *aim_mode {
+        AimMode::Auto => None,
+        AimMode::Mouse => cursor_position.0,
+        AimMode::Stick => {
+            let aim = action_state.clamped_axis_pair(&CombatAction::Aim);
+            player_query
+                .get_single()
+                .ok()
+                .filter(|_| aim != Vec2::ZERO)
+                .map(|player_transform| {
+                    player_transform.translation.truncate() + aim * CROSSHAIR_STICK_DISTANCE
+                })
+        }
+    };
+
+    match target {
+        Some(target) => {
+            transform.translation = target.extend(LAYER5);
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+fn spawn_target_marker(mut commands: Commands, marker_query: Query<Entity, With<TargetMarker>>) {
+    if !marker_query.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Target Marker"),
+            Transform::from_xyz(0.0, 0.0, LAYER5),
+            Visibility::Hidden,
+            TargetMarker,
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            for corner in [
+                Vec2::new(1.0, 1.0),
+                Vec2::new(-1.0, 1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(-1.0, -1.0),
+            ] {
+                for size in [Vec2::new(12.0, 3.0), Vec2::new(3.0, 12.0)] {
+                    let offset = corner * 30.0 - corner * (size - Vec2::splat(3.0)) / 2.0;
+                    parent.spawn((
+                        Sprite {
+                            color: Color::srgba(1.0, 0.2, 0.2, 0.9),
+                            custom_size: Some(size),
+                            ..default()
+                        },
+                        Transform::from_translation(offset.extend(0.0)),
+                    ));
+                }
+            }
+        });
+}
+
+fn update_target_marker(
+    aim_mode: Res<AimMode>,
+    player_query: Query<&AimTarget, (With<Player>, Without<ActionState<CombatAction>>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<TargetMarker>)>,
+    mut marker_query: Query<(&mut Transform, &mut Visibility), With<TargetMarker>>,
+) {
+    let Ok((mut transform, mut visibility)) = marker_query.get_single_mut() else {
+        return;
+    };
+
+    match player_query
+        .get_single()
+        .ok()
+        .and_then(|target| target.0)
+        .filter(|_| !aim_mode.is_manual())
+        .and_then(|entity| enemy_query.get(entity).ok())
+    {
+        Some(target_transform) => {
+            transform.translation = target_transform.translation.truncate().extend(LAYER5);
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
@@ -1,11 +1,13 @@
 use bevy::prelude::*;
 
 use crate::{
+    class::ClassSprite,
     collision::EnemyKdTree,
+    coop::nearest_player,
     enemy::Enemy,
     game_state::GameState,
     gun::ActiveGun,
-    player::{Player, PlayerState},
+    player::{Downed, Player, PlayerState},
 };
 
 pub struct AnimationPlugin;
@@ -39,14 +41,17 @@ fn animation_timer_tick(
 }
 
 fn animate_player(
-    mut player_query: Query<(&mut Sprite, &PlayerState, &AnimationTimer), With<Player>>,
+    mut player_query: Query<
+        (&mut Sprite, &PlayerState, &AnimationTimer, &ClassSprite),
+        With<Player>,
+    >,
 ) {
-    if let Ok((mut sprite, state, timer)) = player_query.get_single_mut() {
+    for (mut sprite, state, timer, class_sprite) in player_query.iter_mut() {
         if timer.just_finished() {
             if let Some(texture_atlas) = &mut sprite.texture_atlas {
                 let base_sprite_index = match state {
-                    PlayerState::Idle => 0,
-                    PlayerState::Run => 4,
+                    PlayerState::Idle => class_sprite.0,
+                    PlayerState::Run => class_sprite.0 + 4,
                 };
                 texture_atlas.index = base_sprite_index + (texture_atlas.index + 1) % 4;
             }
@@ -66,16 +71,13 @@ fn animate_enemy(mut enemy_query: Query<(&mut Sprite, &AnimationTimer), With<Ene
 }
 
 fn flip_enemy_sprite_x(
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
     mut enemy_query: Query<(&mut Sprite, &Transform), With<Enemy>>,
 ) {
-    if player_query.is_empty() || enemy_query.is_empty() {
-        return;
-    }
-
-    let player_pos = player_query.single().translation;
     for (mut sprite, transform) in enemy_query.iter_mut() {
-        sprite.flip_x = transform.translation.x >= player_pos.x;
+        if let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) {
+            sprite.flip_x = transform.translation.x >= player_pos.x;
+        }
     }
 }
 
@@ -84,8 +86,8 @@ fn flip_gun_sprite_y(
     enemy_kd_tree: Res<EnemyKdTree>,
     mut gun_query: Query<(&mut Sprite, &Transform), With<ActiveGun>>,
 ) {
-    if player_query.get_single().is_ok() {
-        if let Ok((mut sprite, transform)) = gun_query.get_single_mut() {
+    if !player_query.is_empty() {
+        for (mut sprite, transform) in gun_query.iter_mut() {
             let gun_pos = transform.translation.truncate();
             let nearest_enemy = enemy_kd_tree
                 .0
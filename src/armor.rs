@@ -1,18 +1,19 @@
 use crate::{
     game_state::GameState,
-    input::Action,
+    input::CombatAction,
     loot::Description,
     player::{Player, PlayerInventory},
     utils::InGameEntity,
 };
 use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 #[require(ArmorStats, Sprite, InGameEntity, Description)]
 pub struct Armor;
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct ArmorStats {
     pub defense: u32,
     pub durability: u32,
@@ -29,20 +30,27 @@ impl Plugin for ArmorPlugin {
 }
 
 fn switch_armor(
-    mut player_query: Query<&mut PlayerInventory, With<Player>>,
-    active_state: Res<ActionState<Action>>,
+    mut player_query: Query<
+        (&mut PlayerInventory, Option<&ActionState<CombatAction>>),
+        With<Player>,
+    >,
+    active_state: Res<ActionState<CombatAction>>,
     mut commands: Commands,
     armor_query: Query<Entity, With<Armor>>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let mut inventory = player_query.single_mut();
+    for (mut inventory, player_state) in player_query.iter_mut() {
+        if !player_state
+            .unwrap_or(&active_state)
+            .just_pressed(&CombatAction::SwitchArmor)
+        {
+            continue;
+        }
 
-    if active_state.just_pressed(&Action::SwitchArmor) {
         inventory.active_armor_index = (inventory.active_armor_index + 1) % inventory.armors.len();
-        for (index, entity) in armor_query.iter().enumerate() {
+        for (index, entity) in inventory.armors.iter().enumerate() {
+            let Ok(entity) = armor_query.get(*entity) else {
+                continue;
+            };
             if index == inventory.active_armor_index {
                 commands.entity(entity).insert(ActiveArmor);
             } else {
@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use fishmans_adventure::{
+    class::PlayerClass,
+    configs::{DEFAULT_CLASS, WH, WW},
+    damage::DamageAppliedEvent,
+    enemy::{Enemy, EnemyBullet, Explosion, Trail},
+    game_state::GameState,
+    headless::{current_state, headless_app, step_combat},
+    input::{CombatAction, MenuAction},
+    perk::{handle_perk_choice, ChoosePerkEvent, PerkChoices},
+    player::{Gold, Health, Player, PlayerInventory},
+    resources::{GameMode, Level, Wave},
+    status::{StatusEffects, StatusKind},
+};
+use leafwing_input_manager::prelude::*;
+
+const DEFAULT_RUNS: u64 = 20;
+const DEFAULT_OUTPUT_PATH: &str = "balance_sim.csv";
+const MAX_SIMULATED_SECS: f32 = 1800.0;
+
+const BOT_DANGER_RADIUS: f32 = 450.0;
+const BOT_THREAT_RADIUS: f32 = 200.0;
+const BOT_KITE_DISTANCE: f32 = 300.0;
+const BOT_WALL_MARGIN: f32 = 250.0;
+const BOT_HEALTH_POTION_RATIO: f32 = 0.4;
+const BOT_SPEED_POTION_CROWD: usize = 8;
+
+struct SimConfig {
+    runs: u64,
+    first_seed: u64,
+    game_mode: GameMode,
+    player_class: String,
+    output_path: String,
+}
+
+#[derive(Default)]
+struct WaveStats {
+    wave: u32,
+    started_at: f32,
+    duration: f32,
+    cleared: bool,
+    damage_taken: BTreeMap<String, u32>,
+    gold_earned: u32,
+    level: u32,
+}
+
+#[derive(Resource, Default)]
+struct RunStats {
+    waves: Vec<WaveStats>,
+    last_gold: Option<u32>,
+}
+
+struct RunReport {
+    seed: u64,
+    outcome: &'static str,
+    waves: Vec<WaveStats>,
+}
+
+fn main() {
+    let config = parse_args();
+    let mut reports = Vec::new();
+
+    for run in 0..config.runs {
+        let seed = config.first_seed + run;
+        let report = simulate_run(config.game_mode, &config.player_class, seed);
+        eprintln!(
+            "run {}/{} seed {}: {} at wave {}",
+            run + 1,
+            config.runs,
+            seed,
+            report.outcome,
+            report.waves.last().map_or(0, |wave| wave.wave)
+        );
+        reports.push(report);
+    }
+
+    let csv = to_csv(&reports, config.game_mode, &config.player_class);
+    match std::fs::write(&config.output_path, csv) {
+        Ok(()) => eprintln!("Wrote {} runs to {}", reports.len(), config.output_path),
+        Err(err) => eprintln!("Failed to write {}: {}", config.output_path, err),
+    }
+}
+
+fn parse_args() -> SimConfig {
+    let mut config = SimConfig {
+        runs: DEFAULT_RUNS,
+        first_seed: 0,
+        game_mode: GameMode::Forever,
+        player_class: DEFAULT_CLASS.to_owned(),
+        output_path: DEFAULT_OUTPUT_PATH.to_owned(),
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--runs" => config.runs = value.parse().unwrap_or(DEFAULT_RUNS),
+            "--seed" => config.first_seed = value.parse().unwrap_or(0),
+            "--mode" => {
+                config.game_mode = match value.as_str() {
+                    "normal" => GameMode::Normal,
+                    _ => GameMode::Forever,
+                }
+            }
+            "--class" => config.player_class = value,
+            "--out" => config.output_path = value,
+            _ => eprintln!("Ignoring unknown argument {}", arg),
+        }
+    }
+    config
+}
+
+fn simulate_run(game_mode: GameMode, player_class: &str, seed: u64) -> RunReport {
+    let mut app = headless_app(game_mode, seed);
+    app.insert_resource(PlayerClass(player_class.to_owned()));
+    app.world_mut().remove_resource::<InputMap<CombatAction>>();
+    app.world_mut().remove_resource::<InputMap<MenuAction>>();
+    app.init_resource::<RunStats>()
+        .add_systems(
+            PreUpdate,
+            (steer_bot, use_potions).after(InputManagerSystem::Update),
+        )
+        .add_systems(FixedPreUpdate, steer_bot.after(InputManagerSystem::Update))
+        .add_systems(
+            Update,
+            choose_perk
+                .before(handle_perk_choice)
+                .run_if(in_state(GameState::LevelUp)),
+        )
+        .add_systems(
+            Update,
+            (
+                track_waves.run_if(resource_changed::<Wave>),
+                track_damage,
+                track_gold,
+            )
+                .chain()
+                .run_if(in_state(GameState::Combat)),
+        );
+
+    loop {
+        if step_combat(&mut app, 1) == 0 {
+            if current_state(&app) != GameState::LevelUp {
+                break;
+            }
+            app.update();
+        }
+        if app.world().resource::<Time<Virtual>>().elapsed_secs() > MAX_SIMULATED_SECS {
+            break;
+        }
+    }
+
+    let outcome = match app.world().resource::<State<GameState>>().get() {
+        GameState::End => "death",
+        GameState::Win => "win",
+        _ => "timeout",
+    };
+    let elapsed = app.world().resource::<Time<Virtual>>().elapsed_secs();
+    let level = app.world().resource::<Level>().level();
+    let mut stats = app.world_mut().resource_mut::<RunStats>();
+    if outcome == "win" {
+        stats.waves.pop();
+    }
+    if let Some(last_wave) = stats.waves.last_mut() {
+        last_wave.duration = elapsed - last_wave.started_at;
+        last_wave.cleared = outcome == "win";
+        last_wave.level = level;
+    }
+
+    RunReport {
+        seed,
+        outcome,
+        waves: std::mem::take(&mut stats.waves),
+    }
+}
+
+fn steer_bot(
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    threat_query: Query<
+        &Transform,
+        (
+            Or<(With<EnemyBullet>, With<Explosion>, With<Trail>)>,
+            Without<Player>,
+        ),
+    >,
+    mut action_state: ResMut<ActionState<CombatAction>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    let mut flee = Vec2::ZERO;
+    let mut nearest_enemy: Option<(f32, Vec2)> = None;
+    for enemy_transform in enemy_query.iter() {
+        let enemy_pos = enemy_transform.translation.truncate();
+        let distance = player_pos.distance(enemy_pos).max(1.0);
+        if distance < BOT_DANGER_RADIUS {
+            flee += (player_pos - enemy_pos) / (distance * distance);
+        }
+        if nearest_enemy.map_or(true, |(nearest, _)| distance < nearest) {
+            nearest_enemy = Some((distance, enemy_pos));
+        }
+    }
+    for threat_transform in threat_query.iter() {
+        let threat_pos = threat_transform.translation.truncate();
+        let distance = player_pos.distance(threat_pos).max(1.0);
+        if distance < BOT_THREAT_RADIUS {
+            flee += 2.0 * (player_pos - threat_pos) / (distance * distance);
+        }
+    }
+
+    let mut movement = if flee != Vec2::ZERO {
+        let away = flee.normalize();
+        away + away.perp() * 0.5
+    } else {
+        match nearest_enemy {
+            Some((distance, enemy_pos)) if distance > BOT_KITE_DISTANCE => {
+                (enemy_pos - player_pos).normalize() * 0.5
+            }
+            _ => Vec2::ZERO,
+        }
+    };
+
+    if player_pos.x < -WW + BOT_WALL_MARGIN {
+        movement.x += 1.0;
+    }
+    if player_pos.x > WW - BOT_WALL_MARGIN {
+        movement.x -= 1.0;
+    }
+    if player_pos.y < -WH + BOT_WALL_MARGIN {
+        movement.y += 1.0;
+    }
+    if player_pos.y > WH - BOT_WALL_MARGIN {
+        movement.y -= 1.0;
+    }
+
+    action_state.set_axis_pair(&CombatAction::Move, movement.normalize_or_zero());
+}
+
+fn use_potions(
+    player_query: Query<(&Transform, &Health, &PlayerInventory, &StatusEffects), With<Player>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    mut action_state: ResMut<ActionState<CombatAction>>,
+) {
+    let Ok((player_transform, health, inventory, effects)) = player_query.get_single() else {
+        return;
+    };
+
+    let low_health = (health.0 as f32) < health.1 as f32 * BOT_HEALTH_POTION_RATIO;
+    tap_action(
+        &mut action_state,
+        CombatAction::UsePotion1,
+        low_health && !inventory.health_potions.is_empty(),
+    );
+
+    let crowd = enemy_query
+        .iter()
+        .filter(|enemy_transform| {
+            enemy_transform
+                .translation
+                .distance(player_transform.translation)
+                < BOT_DANGER_RADIUS
+        })
+        .count();
+    tap_action(
+        &mut action_state,
+        CombatAction::UsePotion2,
+        crowd >= BOT_SPEED_POTION_CROWD
+            && !effects.has(StatusKind::Haste)
+            && !inventory.speed_potions.is_empty(),
+    );
+}
+
+fn choose_perk(choices: Res<PerkChoices>, mut ev_choose: EventWriter<ChoosePerkEvent>) {
+    if !choices.perks.is_empty() {
+        ev_choose.send(ChoosePerkEvent(0));
+    }
+}
+
+fn tap_action(action_state: &mut ActionState<CombatAction>, action: CombatAction, wanted: bool) {
+    if wanted && action_state.released(&action) {
+        action_state.press(&action);
+    } else {
+        action_state.release(&action);
+    }
+}
+
+fn track_waves(
+    wave: Res<Wave>,
+    level: Res<Level>,
+    time: Res<Time<Virtual>>,
+    mut stats: ResMut<RunStats>,
+) {
+    if wave.number == 0 {
+        return;
+    }
+
+    let now = time.elapsed_secs();
+    if let Some(previous_wave) = stats.waves.last_mut() {
+        previous_wave.duration = now - previous_wave.started_at;
+        previous_wave.cleared = true;
+        previous_wave.level = level.level();
+    }
+    stats.waves.push(WaveStats {
+        wave: wave.number,
+        started_at: now,
+        ..default()
+    });
+}
+
+fn track_damage(
+    mut events: EventReader<DamageAppliedEvent>,
+    player_query: Query<(), With<Player>>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in events.read() {
+        if !player_query.contains(event.target) {
+            continue;
+        }
+        if let Some(current_wave) = stats.waves.last_mut() {
+            *current_wave
+                .damage_taken
+                .entry(event.source.name.as_str().to_owned())
+                .or_default() += event.amount;
+        }
+    }
+}
+
+fn track_gold(gold_query: Query<&Gold, With<Player>>, mut stats: ResMut<RunStats>) {
+    let Ok(gold) = gold_query.get_single() else {
+        return;
+    };
+    let earned = stats
+        .last_gold
+        .map_or(0, |last_gold| gold.0.saturating_sub(last_gold));
+    stats.last_gold = Some(gold.0);
+    if let Some(current_wave) = stats.waves.last_mut() {
+        current_wave.gold_earned += earned;
+    }
+}
+
+fn to_csv(reports: &[RunReport], game_mode: GameMode, player_class: &str) -> String {
+    let sources: BTreeSet<&str> = reports
+        .iter()
+        .flat_map(|report| report.waves.iter())
+        .flat_map(|wave| wave.damage_taken.keys().map(String::as_str))
+        .collect();
+    let mode = match game_mode {
+        GameMode::Normal => "normal",
+        GameMode::Forever => "forever",
+    };
+
+    let mut csv = String::from(
+        "seed,mode,class,outcome,survival_wave,wave,wave_secs,cleared,gold_earned,level_reached",
+    );
+    for source in &sources {
+        csv.push_str(&format!(
+            ",damage_{}",
+            source.to_lowercase().replace(' ', "_")
+        ));
+    }
+    csv.push('\n');
+
+    for report in reports {
+        let survival_wave = report.waves.last().map_or(0, |wave| wave.wave);
+        for wave in &report.waves {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.2},{},{},{}",
+                report.seed,
+                mode,
+                player_class,
+                report.outcome,
+                survival_wave,
+                wave.wave,
+                wave.duration,
+                wave.cleared,
+                wave.gold_earned,
+                wave.level
+            ));
+            for source in &sources {
+                let damage = wave.damage_taken.get(*source).copied().unwrap_or(0);
+                csv.push_str(&format!(",{}", damage));
+            }
+            csv.push('\n');
+        }
+    }
+    csv
+}
@@ -0,0 +1,452 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    configs::{COOP_JOIN_BUTTONS, NAVIGATION_STICK_THRESHOLD},
+    input::{CombatAction, InputContext, InputDevice, MenuAction},
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Control {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Combat(CombatAction),
+    Menu(MenuAction),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ControlBinding {
+    pub control: Control,
+    pub inputs: Vec<InputBinding>,
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Bindings {
+    pub entries: Vec<ControlBinding>,
+}
+
+impl InputBinding {
+    pub fn device(self) -> InputDevice {
+        match self {
+            InputBinding::Key(_) | InputBinding::Mouse(_) => InputDevice::Keyboard,
+            InputBinding::Gamepad(_) => InputDevice::Gamepad,
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            InputBinding::Key(key) => key_label(key),
+            InputBinding::Mouse(button) => match button {
+                MouseButton::Left => "Left Mouse".to_string(),
+                MouseButton::Right => "Right Mouse".to_string(),
+                MouseButton::Middle => "Middle Mouse".to_string(),
+                other => format!("{:?} Mouse", other),
+            },
+            InputBinding::Gamepad(button) => gamepad_label(button).to_string(),
+        }
+    }
+}
+
+impl Control {
+    pub const ALL: [Control; 21] = [
+        Control::MoveUp,
+        Control::MoveDown,
+        Control::MoveLeft,
+        Control::MoveRight,
+        Control::Combat(CombatAction::Dash),
+        Control::Combat(CombatAction::Fire),
+        Control::Combat(CombatAction::SwitchGun),
+        Control::Combat(CombatAction::SwitchArmor),
+        Control::Combat(CombatAction::UsePotion1),
+        Control::Combat(CombatAction::UsePotion2),
+        Control::Combat(CombatAction::CycleAimMode),
+        Control::Combat(CombatAction::CycleTargetPriority),
+        Control::Combat(CombatAction::ToggleShop),
+        Control::Combat(CombatAction::ToggleLootBoard),
+        Control::Combat(CombatAction::TogglePause),
+        Control::Menu(MenuAction::SellLoot),
+        Control::Menu(MenuAction::Confirm),
+        Control::Menu(MenuAction::NavigateUp),
+        Control::Menu(MenuAction::NavigateDown),
+        Control::Menu(MenuAction::NavigateLeft),
+        Control::Menu(MenuAction::NavigateRight),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Control::MoveUp => "Move Up",
+            Control::MoveDown => "Move Down",
+            Control::MoveLeft => "Move Left",
+            Control::MoveRight => "Move Right",
+            Control::Combat(action) => match action {
+                CombatAction::Move => "Move",
+                CombatAction::Aim => "Aim",
+                CombatAction::Fire => "Fire (Manual Aim)",
+                CombatAction::Dash => "Dash",
+                CombatAction::SwitchGun => "Switch Gun",
+                CombatAction::SwitchArmor => "Switch Armor",
+                CombatAction::UsePotion1 => "Use Health Potion",
+                CombatAction::UsePotion2 => "Use Speed Potion",
+                CombatAction::CycleAimMode => "Cycle Aim Mode",
+                CombatAction::CycleTargetPriority => "Cycle Target Priority",
+                CombatAction::TogglePause => "Pause / Back",
+                CombatAction::ToggleShop => "Toggle Shop",
+                CombatAction::ToggleLootBoard => "Toggle Loot Board",
+            },
+            Control::Menu(action) => match action {
+                MenuAction::NavigateUp => "Navigate Up",
+                MenuAction::NavigateDown => "Navigate Down",
+                MenuAction::NavigateLeft => "Navigate Left",
+                MenuAction::NavigateRight => "Navigate Right",
+                MenuAction::Confirm => "Confirm",
+                MenuAction::Back => "Back",
+                MenuAction::SellLoot => "Sell Loot",
+                MenuAction::ToggleShop => "Close Shop",
+                MenuAction::ToggleLootBoard => "Close Loot Board",
+            },
+        }
+    }
+
+    pub fn contexts(self) -> &'static [InputContext] {
+        match self {
+            Control::Combat(
+                CombatAction::TogglePause
+                | CombatAction::ToggleShop
+                | CombatAction::ToggleLootBoard,
+            ) => &[InputContext::Combat, InputContext::Menu],
+            Control::Menu(_) => &[InputContext::Menu],
+            _ => &[InputContext::Combat],
+        }
+    }
+
+    pub fn keys_only(self) -> bool {
+        matches!(
+            self,
+            Control::MoveUp | Control::MoveDown | Control::MoveLeft | Control::MoveRight
+        )
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use GamepadButton as Pad;
+        use InputBinding::{Gamepad, Key, Mouse};
+
+        let bind = |control: Control, inputs: &[InputBinding]| ControlBinding {
+            control,
+            inputs: inputs.to_vec(),
+        };
+        let combat = Control::Combat;
+        let menu = Control::Menu;
+
+        Self {
+            entries: vec![
+                bind(Control::MoveUp, &[Key(KeyCode::KeyW)]),
+                bind(Control::MoveDown, &[Key(KeyCode::KeyS)]),
+                bind(Control::MoveLeft, &[Key(KeyCode::KeyA)]),
+                bind(Control::MoveRight, &[Key(KeyCode::KeyD)]),
+                bind(
+                    combat(CombatAction::Dash),
+                    &[Key(KeyCode::Space), Gamepad(Pad::LeftTrigger2)],
+                ),
+                bind(
+                    combat(CombatAction::Fire),
+                    &[Mouse(MouseButton::Left), Gamepad(Pad::RightTrigger2)],
+                ),
+                bind(
+                    combat(CombatAction::SwitchGun),
+                    &[Key(KeyCode::KeyQ), Gamepad(Pad::RightTrigger)],
+                ),
+                bind(
+                    combat(CombatAction::SwitchArmor),
+                    &[Key(KeyCode::KeyZ), Gamepad(Pad::LeftTrigger)],
+                ),
+                bind(
+                    combat(CombatAction::UsePotion1),
+                    &[Key(KeyCode::Digit1), Gamepad(Pad::West)],
+                ),
+                bind(
+                    combat(CombatAction::UsePotion2),
+                    &[Key(KeyCode::Digit2), Gamepad(Pad::East)],
+                ),
+                bind(
+                    combat(CombatAction::CycleAimMode),
+                    &[Key(KeyCode::KeyT), Gamepad(Pad::LeftThumb)],
+                ),
+                bind(
+                    combat(CombatAction::CycleTargetPriority),
+                    &[Key(KeyCode::KeyR), Gamepad(Pad::RightThumb)],
+                ),
+                bind(
+                    combat(CombatAction::ToggleShop),
+                    &[Key(KeyCode::KeyO), Gamepad(Pad::North)],
+                ),
+                bind(
+                    combat(CombatAction::ToggleLootBoard),
+                    &[Key(KeyCode::Tab), Gamepad(Pad::Select)],
+                ),
+                bind(
+                    menu(MenuAction::SellLoot),
+                    &[Key(KeyCode::Delete), Gamepad(Pad::West)],
+                ),
+                bind(
+                    combat(CombatAction::TogglePause),
+                    &[
+                        Key(KeyCode::KeyP),
+                        Key(KeyCode::Escape),
+                        Gamepad(Pad::Start),
+                    ],
+                ),
+                bind(
+                    menu(MenuAction::Confirm),
+                    &[Key(KeyCode::Enter), Gamepad(Pad::South)],
+                ),
+                bind(
+                    menu(MenuAction::NavigateUp),
+                    &[
+                        Key(KeyCode::ArrowUp),
+                        Key(KeyCode::KeyW),
+                        Gamepad(Pad::DPadUp),
+                    ],
+                ),
+                bind(
+                    menu(MenuAction::NavigateDown),
+                    &[
+                        Key(KeyCode::ArrowDown),
+                        Key(KeyCode::KeyS),
+                        Gamepad(Pad::DPadDown),
+                    ],
+                ),
+                bind(
+                    menu(MenuAction::NavigateLeft),
+                    &[
+                        Key(KeyCode::ArrowLeft),
+                        Key(KeyCode::KeyA),
+                        Gamepad(Pad::DPadLeft),
+                    ],
+                ),
+                bind(
+                    menu(MenuAction::NavigateRight),
+                    &[
+                        Key(KeyCode::ArrowRight),
+                        Key(KeyCode::KeyD),
+                        Gamepad(Pad::DPadRight),
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn inputs(&self, control: Control) -> &[InputBinding] {
+        self.entries
+            .iter()
+            .find(|entry| entry.control == control)
+            .map_or(&[], |entry| entry.inputs.as_slice())
+    }
+
+    pub fn label(&self, control: Control, device: InputDevice) -> String {
+        let labels: Vec<String> = self
+            .inputs(control)
+            .iter()
+            .filter(|input| input.device() == device)
+            .map(|input| input.label())
+            .collect();
+        if labels.is_empty() {
+            "-".to_string()
+        } else {
+            labels.join("/")
+        }
+    }
+
+    pub fn rebind(&mut self, control: Control, input: InputBinding) {
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.control == control)
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(ControlBinding {
+                    control,
+                    inputs: Vec::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+        let inputs = &mut self.entries[index].inputs;
+        inputs.retain(|existing| existing.device() != input.device());
+        inputs.push(input);
+    }
+
+    pub fn conflicts(&self, control: Control) -> Vec<Control> {
+        let inputs = self.inputs(control);
+        self.entries
+            .iter()
+            .filter(|entry| entry.control != control)
+            .filter(|entry| {
+                entry
+                    .control
+                    .contexts()
+                    .iter()
+                    .any(|context| control.contexts().contains(context))
+            })
+            .filter(|entry| entry.inputs.iter().any(|input| inputs.contains(input)))
+            .map(|entry| entry.control)
+            .collect()
+    }
+
+    pub fn coop_join_button(&self) -> Option<GamepadButton> {
+        COOP_JOIN_BUTTONS.into_iter().find(|&button| {
+            !self.entries.iter().any(|entry| {
+                entry.control.contexts().contains(&InputContext::Menu)
+                    && entry.inputs.contains(&InputBinding::Gamepad(button))
+            })
+        })
+    }
+
+    pub fn combat_input_map(&self) -> InputMap<CombatAction> {
+        let mut input_map = InputMap::default();
+
+        for entry in &self.entries {
+            if let Control::Combat(action) = entry.control {
+                insert_inputs(&mut input_map, action, &entry.inputs);
+            }
+        }
+
+        let key = |control| {
+            self.inputs(control).iter().find_map(|input| match input {
+                InputBinding::Key(key) => Some(*key),
+                _ => None,
+            })
+        };
+        if let (Some(up), Some(down), Some(left), Some(right)) = (
+            key(Control::MoveUp),
+            key(Control::MoveDown),
+            key(Control::MoveLeft),
+            key(Control::MoveRight),
+        ) {
+            input_map.insert_dual_axis(CombatAction::Move, VirtualDPad::new(up, down, left, right));
+        }
+
+        input_map
+            .insert_dual_axis(CombatAction::Move, GamepadStick::LEFT)
+            .insert_dual_axis(CombatAction::Aim, GamepadStick::RIGHT);
+        input_map
+    }
+
+    pub fn gamepad_combat_input_map(&self, gamepad: Entity) -> InputMap<CombatAction> {
+        let mut input_map = InputMap::default().with_gamepad(gamepad);
+
+        for entry in &self.entries {
+            if let Control::Combat(action) = entry.control {
+                for input in &entry.inputs {
+                    if let InputBinding::Gamepad(button) = *input {
+                        input_map.insert(action, button);
+                    }
+                }
+            }
+        }
+
+        input_map
+            .insert_dual_axis(CombatAction::Move, GamepadStick::LEFT)
+            .insert_dual_axis(CombatAction::Aim, GamepadStick::RIGHT);
+        input_map
+    }
+
+    pub fn menu_input_map(&self) -> InputMap<MenuAction> {
+        let mut input_map = InputMap::default();
+
+        for entry in &self.entries {
+            let action = match entry.control {
+                Control::Menu(action) => action,
+                Control::Combat(CombatAction::TogglePause) => MenuAction::Back,
+                Control::Combat(CombatAction::ToggleShop) => MenuAction::ToggleShop,
+                Control::Combat(CombatAction::ToggleLootBoard) => MenuAction::ToggleLootBoard,
+                _ => continue,
+            };
+            insert_inputs(&mut input_map, action, &entry.inputs);
+        }
+
+        input_map
+            .insert(
+                MenuAction::NavigateUp,
+                GamepadControlDirection::LEFT_UP.threshold(NAVIGATION_STICK_THRESHOLD),
+            )
+            .insert(
+                MenuAction::NavigateDown,
+                GamepadControlDirection::LEFT_DOWN.threshold(NAVIGATION_STICK_THRESHOLD),
+            )
+            .insert(
+                MenuAction::NavigateLeft,
+                GamepadControlDirection::LEFT_LEFT.threshold(NAVIGATION_STICK_THRESHOLD),
+            )
+            .insert(
+                MenuAction::NavigateRight,
+                GamepadControlDirection::LEFT_RIGHT.threshold(NAVIGATION_STICK_THRESHOLD),
+            );
+        input_map
+    }
+}
+
+fn insert_inputs<A: Actionlike>(input_map: &mut InputMap<A>, action: A, inputs: &[InputBinding]) {
+    for input in inputs {
+        match *input {
+            InputBinding::Key(key) => input_map.insert(action.clone(), key),
+            InputBinding::Mouse(button) => input_map.insert(action.clone(), button),
+            InputBinding::Gamepad(button) => input_map.insert(action.clone(), button),
+        };
+    }
+}
+
+fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::Escape => "ESC".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::ArrowUp => "Up".to_string(),
+        KeyCode::ArrowDown => "Down".to_string(),
+        KeyCode::ArrowLeft => "Left".to_string(),
+        KeyCode::ArrowRight => "Right".to_string(),
+        other => {
+            let name = format!("{:?}", other);
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .unwrap_or(&name)
+                .to_string()
+        }
+    }
+}
+
+fn gamepad_label(button: GamepadButton) -> &'static str {
+    match button {
+        GamepadButton::South => "A",
+        GamepadButton::East => "B",
+        GamepadButton::West => "X",
+        GamepadButton::North => "Y",
+        GamepadButton::LeftTrigger => "LB",
+        GamepadButton::RightTrigger => "RB",
+        GamepadButton::LeftTrigger2 => "LT",
+        GamepadButton::RightTrigger2 => "RT",
+        GamepadButton::Select => "Back",
+        GamepadButton::Start => "Start",
+        GamepadButton::Mode => "Guide",
+        GamepadButton::LeftThumb => "L3",
+        GamepadButton::RightThumb => "R3",
+        GamepadButton::DPadUp => "D-Pad Up",
+        GamepadButton::DPadDown => "D-Pad Down",
+        GamepadButton::DPadLeft => "D-Pad Left",
+        GamepadButton::DPadRight => "D-Pad Right",
+        _ => "Pad",
+    }
+}
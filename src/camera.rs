@@ -1,8 +1,9 @@
 use bevy::{math::vec3, prelude::*};
 
 use crate::{
-    configs::{WH, WW},
+    configs::{CAMERA_FRAME_MARGIN, CAMERA_MAX_SCALE, CAMERA_SCALE, WH, WW},
     game_state::GameState,
+    net::NetPlayerProxy,
     player::Player,
 };
 
@@ -25,7 +26,7 @@ fn setup_camera(mut commands: Commands) {
         OrthographicProjection {
             near: -1000.0,
             far: 1000.0,
-            scale: 1.3,
+            scale: CAMERA_SCALE,
             ..OrthographicProjection::default_2d()
         },
         Msaa::Off,
@@ -34,18 +35,36 @@ fn setup_camera(mut commands: Commands) {
 }
 
 fn camera_follow_player(
-    player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
+    player_query: Query<&Transform, Or<(With<Player>, With<NetPlayerProxy>)>>,
+    mut camera_query: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<Camera>, Without<Player>, Without<NetPlayerProxy>),
+    >,
 ) {
-    if camera_query.is_empty() || player_query.is_empty() {
+    let Ok((mut camera_transform, mut projection)) = camera_query.get_single_mut() else {
         return;
-    }
-
-    let mut camera_transform = camera_query.single_mut();
-    let player_transform = player_query.single().translation;
-    let (x, y) = (player_transform.x, player_transform.y);
+    };
+    let mut positions = player_query
+        .iter()
+        .map(|transform| transform.translation.xy());
+    let Some(first) = positions.next() else {
+        return;
+    };
+    let (min, max) = positions.fold((first, first), |(min, max), position| {
+        (min.min(position), max.max(position))
+    });
+    let center = (min + max) / 2.0;
+    let extent = max - min + Vec2::splat(CAMERA_FRAME_MARGIN * 2.0);
+    let scale = (extent.x / WW)
+        .max(extent.y / WH)
+        .clamp(CAMERA_SCALE, CAMERA_MAX_SCALE);
 
-    camera_transform.translation = camera_transform.translation.lerp(vec3(x, y, 0.0), 0.1);
+    if (scale - projection.scale).abs() > f32::EPSILON {
+        projection.scale += (scale - projection.scale) * 0.1;
+    }
+    camera_transform.translation = camera_transform
+        .translation
+        .lerp(vec3(center.x, center.y, 0.0), 0.1);
     camera_transform.translation.x = camera_transform
         .translation
         .x
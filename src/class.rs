@@ -0,0 +1,452 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::{
+    audio::AudioEvent,
+    collision::EnemyKdTree,
+    configs::*,
+    damage::resolve_damage,
+    game_state::{GameState, GameplaySet},
+    gun::{fire_bullets, BulletStats, GunType},
+    input::{MenuAction, NavigationRepeat},
+    player::{handle_player_movement, mark_loot_for_pickup, Health, Player, PlayerState},
+    resources::GlobalTextureAtlas,
+    rng::GameRng,
+    save::{SavedArmor, SavedGun, SavedPlayer, SavedPotion},
+    stats::{StatKind, StatModifier, Stats},
+    utils::{get_nearest_enemy_position, InGameEntity, RonAssetLoader},
+};
+
+pub struct ClassPlugin;
+
+#[derive(Component, Deserialize, Clone, Copy, Debug)]
+pub enum ClassPassive {
+    Regeneration {
+        amount: u32,
+        interval: f32,
+    },
+    SteadyAim {
+        damage_boost: f32,
+        delay: f32,
+    },
+    Familiar {
+        damage: u32,
+        interval: f32,
+        range: f32,
+    },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ClassDefinition {
+    pub name: String,
+    pub description: String,
+    pub sprite_index: usize,
+    pub color: (u8, u8, u8),
+    pub passive: ClassPassive,
+    pub player: SavedPlayer,
+    pub guns: Vec<SavedGun>,
+    pub health_potions: Vec<SavedPotion>,
+    pub speed_potions: Vec<SavedPotion>,
+    pub armors: Vec<SavedArmor>,
+}
+
+#[derive(Asset, TypePath, Deserialize, Clone)]
+pub struct ClassDefinitions {
+    pub classes: Vec<ClassDefinition>,
+}
+
+#[derive(Resource)]
+pub struct ClassRegistry {
+    classes: Vec<ClassDefinition>,
+}
+
+#[derive(Resource)]
+pub struct ClassDefinitionsHandle(pub Handle<ClassDefinitions>);
+
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct PlayerClass(pub String);
+
+#[derive(Resource, Default)]
+pub struct SelectedClass(pub usize);
+
+#[derive(Event)]
+pub struct ChooseClassEvent(pub usize);
+
+#[derive(Component)]
+pub struct ClassSprite(pub usize);
+
+#[derive(Component)]
+pub struct PassiveTimer(pub Timer);
+
+#[derive(Component)]
+pub struct Familiar {
+    pub owner: Entity,
+    pub angle: f32,
+}
+
+impl ClassPassive {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClassPassive::Regeneration { .. } => "Regeneration",
+            ClassPassive::SteadyAim { .. } => "Steady Aim",
+            ClassPassive::Familiar { .. } => "Familiar",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            ClassPassive::Regeneration { amount, interval } => {
+                format!("Heal {} every {}s", amount, interval)
+            }
+            ClassPassive::SteadyAim {
+                damage_boost,
+                delay,
+            } => format!(
+                "+{} damage after standing still for {}s",
+                damage_boost, delay
+            ),
+            ClassPassive::Familiar {
+                damage, interval, ..
+            } => format!(
+                "A familiar shoots nearby enemies for {} damage every {}s",
+                damage, interval
+            ),
+        }
+    }
+
+    pub fn timer(&self) -> Timer {
+        match *self {
+            ClassPassive::Regeneration { interval, .. }
+            | ClassPassive::Familiar { interval, .. } => {
+                Timer::from_seconds(interval, TimerMode::Repeating)
+            }
+            ClassPassive::SteadyAim { delay, .. } => Timer::from_seconds(delay, TimerMode::Once),
+        }
+    }
+}
+
+impl ClassDefinition {
+    pub fn color(&self) -> Color {
+        let (r, g, b) = self.color;
+        Color::srgb_u8(r, g, b)
+    }
+}
+
+impl Default for ClassRegistry {
+    fn default() -> Self {
+        let definitions = ron::from_str::<ClassDefinitions>(include_str!("../assets/classes.ron"))
+            .expect("built-in class definitions should parse");
+        Self {
+            classes: definitions.classes,
+        }
+    }
+}
+
+impl ClassRegistry {
+    pub fn classes(&self) -> &[ClassDefinition] {
+        &self.classes
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ClassDefinition> {
+        self.classes
+            .iter()
+            .find(|definition| definition.name == name)
+    }
+
+    pub fn get_or_default(&self, name: &str) -> &ClassDefinition {
+        self.get(name).unwrap_or_else(|| {
+            warn!("Unknown class {}, falling back to {}", name, DEFAULT_CLASS);
+            self.get(DEFAULT_CLASS).unwrap_or(&self.classes[0])
+        })
+    }
+
+    pub fn replace(&mut self, definitions: &ClassDefinitions) {
+        if definitions.classes.is_empty() {
+            warn!("Ignoring class definitions without any classes");
+            return;
+        }
+        self.classes = definitions.classes.clone();
+    }
+}
+
+impl Default for PlayerClass {
+    fn default() -> Self {
+        Self(DEFAULT_CLASS.to_string())
+    }
+}
+
+impl Plugin for ClassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChooseClassEvent>()
+            .init_resource::<ClassRegistry>()
+            .init_resource::<PlayerClass>()
+            .init_resource::<SelectedClass>()
+            .add_systems(OnEnter(GameState::ClassSelect), select_current_class)
+            .add_systems(
+                Update,
+                (navigate_class_choices, handle_class_choice)
+                    .chain()
+                    .run_if(in_state(GameState::ClassSelect)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    handle_regeneration.after(resolve_damage),
+                    handle_steady_aim.after(handle_player_movement),
+                    (spawn_familiar, move_familiar, handle_familiar_firing)
+                        .chain()
+                        .after(mark_loot_for_pickup),
+                )
+                    .chain()
+                    .in_set(GameplaySet::Player)
+                    .run_if(in_state(GameState::Combat)),
+            );
+
+        if app.is_plugin_added::<AssetPlugin>() {
+            app.init_asset::<ClassDefinitions>()
+                .init_asset_loader::<RonAssetLoader<ClassDefinitions>>()
+                .add_systems(
+                    Update,
+                    sync_class_registry.run_if(resource_exists::<ClassDefinitionsHandle>),
+                );
+
+            #[cfg(not(target_arch = "wasm32"))]
+            app.add_systems(OnEnter(GameState::Loading), load_class_definitions);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_class_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ClassDefinitionsHandle(
+        asset_server.load(CLASS_DEFINITIONS_PATH),
+    ));
+}
+
+fn sync_class_registry(
+    mut events: EventReader<AssetEvent<ClassDefinitions>>,
+    handle: Res<ClassDefinitionsHandle>,
+    definitions: Res<Assets<ClassDefinitions>>,
+    mut registry: ResMut<ClassRegistry>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != handle.0.id() {
+            continue;
+        }
+        if let Some(definitions) = definitions.get(*id) {
+            registry.replace(definitions);
+            info!("Loaded {} class definitions", definitions.classes.len());
+        }
+    }
+}
+
+fn select_current_class(
+    registry: Res<ClassRegistry>,
+    player_class: Res<PlayerClass>,
+    mut selected: ResMut<SelectedClass>,
+) {
+    selected.0 = registry
+        .classes
+        .iter()
+        .position(|definition| definition.name == player_class.0)
+        .unwrap_or(0);
+}
+
+fn navigate_class_choices(
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
+    registry: Res<ClassRegistry>,
+    mut selected: ResMut<SelectedClass>,
+    mut ev_choose: EventWriter<ChooseClassEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    let count = registry.classes.len();
+
+    if navigation.triggered(MenuAction::NavigateUp) {
+        selected.0 = (selected.0 + count - 1) % count;
+        ew.send(AudioEvent::UI);
+    }
+
+    if navigation.triggered(MenuAction::NavigateDown) {
+        selected.0 = (selected.0 + 1) % count;
+        ew.send(AudioEvent::UI);
+    }
+
+    if action_state.just_pressed(&MenuAction::Confirm) {
+        ew.send(AudioEvent::PopUp);
+        ev_choose.send(ChooseClassEvent(selected.0));
+    }
+
+    if action_state.just_pressed(&MenuAction::Back) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+pub fn handle_class_choice(
+    mut events: EventReader<ChooseClassEvent>,
+    registry: Res<ClassRegistry>,
+    mut player_class: ResMut<PlayerClass>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        let Some(definition) = registry.classes.get(event.0) else {
+            continue;
+        };
+        info!("Playing as {}", definition.name);
+        player_class.0 = definition.name.clone();
+        next_state.set(GameState::Initializing);
+    }
+}
+
+fn handle_regeneration(
+    time: Res<Time>,
+    mut player_query: Query<(&ClassPassive, &mut PassiveTimer, &mut Health, &Stats), With<Player>>,
+) {
+    for (passive, mut timer, mut health, stats) in player_query.iter_mut() {
+        let ClassPassive::Regeneration { amount, .. } = *passive else {
+            continue;
+        };
+        if health.0 == 0 {
+            continue;
+        }
+        if timer.0.tick(time.delta()).just_finished() {
+            health.0 = (health.0 + amount).min(stats.value_u32(StatKind::MaxHealth));
+        }
+    }
+}
+
+fn handle_steady_aim(
+    time: Res<Time>,
+    mut player_query: Query<
+        (&ClassPassive, &mut PassiveTimer, &PlayerState, &mut Stats),
+        With<Player>,
+    >,
+) {
+    for (passive, mut timer, state, mut stats) in player_query.iter_mut() {
+        let ClassPassive::SteadyAim { damage_boost, .. } = *passive else {
+            continue;
+        };
+        let source = passive.name();
+        match state {
+            PlayerState::Idle => {
+                if timer.0.tick(time.delta()).finished() && !stats.has_modifier_from(source) {
+                    stats.add_modifier(StatModifier::add(
+                        StatKind::DamageBoost,
+                        damage_boost,
+                        source,
+                    ));
+                }
+            }
+            PlayerState::Run => {
+                timer.0.reset();
+                stats.remove_modifiers_from(source);
+            }
+        }
+    }
+}
+
+fn spawn_familiar(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    player_query: Query<(Entity, &ClassPassive, &Transform), With<Player>>,
+    familiar_query: Query<&Familiar>,
+) {
+    for (player_entity, passive, transform) in player_query.iter() {
+        if !matches!(passive, ClassPassive::Familiar { .. })
+            || familiar_query
+                .iter()
+                .any(|familiar| familiar.owner == player_entity)
+        {
+            continue;
+        }
+
+        commands.spawn((
+            Name::new("Familiar"),
+            Sprite {
+                image: handle.image.clone().unwrap(),
+                texture_atlas: Some(TextureAtlas {
+                    layout: handle.layout_16x16.clone().unwrap(),
+                    index: FAMILIAR_SPRITE_INDEX,
+                }),
+                ..default()
+            },
+            Transform::from_translation(transform.translation.truncate().extend(LAYER3))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * 0.6)),
+            Familiar {
+                owner: player_entity,
+                angle: 0.0,
+            },
+            InGameEntity,
+        ));
+    }
+}
+
+fn move_familiar(
+    time: Res<Time>,
+    player_query: Query<&Transform, (With<Player>, Without<Familiar>)>,
+    mut familiar_query: Query<(&mut Transform, &mut Familiar)>,
+) {
+    for (mut transform, mut familiar) in familiar_query.iter_mut() {
+        let Ok(player_transform) = player_query.get(familiar.owner) else {
+            continue;
+        };
+        familiar.angle =
+            (familiar.angle + FAMILIAR_ORBIT_SPEED * time.delta_secs()) % std::f32::consts::TAU;
+        let offset = Vec2::from_angle(familiar.angle) * FAMILIAR_ORBIT_RADIUS;
+        let position = player_transform.translation.truncate() + offset;
+        transform.translation = position.extend(LAYER3);
+    }
+}
+
+fn handle_familiar_firing(
+    mut commands: Commands,
+    time: Res<Time>,
+    handle: Res<GlobalTextureAtlas>,
+    enemy_kd_tree: Res<EnemyKdTree>,
+    mut rng: ResMut<GameRng>,
+    mut player_query: Query<(Entity, &ClassPassive, &mut PassiveTimer), With<Player>>,
+    familiar_query: Query<(&Transform, &Familiar)>,
+) {
+    for (player_entity, passive, mut timer) in player_query.iter_mut() {
+        let ClassPassive::Familiar { damage, range, .. } = *passive else {
+            continue;
+        };
+        if !timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        for (transform, _) in familiar_query
+            .iter()
+            .filter(|(_, familiar)| familiar.owner == player_entity)
+        {
+            let position = transform.translation.truncate();
+            let Some(target) = get_nearest_enemy_position(position, &enemy_kd_tree, range) else {
+                continue;
+            };
+            let direction = (target - position).normalize_or_zero();
+            fire_bullets(
+                &mut commands,
+                player_entity,
+                position,
+                direction.extend(0.0),
+                1,
+                0.0,
+                &BulletStats {
+                    speed: FAMILIAR_BULLET_SPEED,
+                    damage,
+                    lifespan: range / FAMILIAR_BULLET_SPEED as f32,
+                },
+                &handle,
+                84..=87,
+                GunType::FocusedAim,
+                &mut rng.combat,
+            );
+        }
+    }
+}
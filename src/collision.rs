@@ -1,19 +1,21 @@
 use bevy::utils::Duration;
 
 use crate::{
-    audio::AudioEvent,
     configs::KD_TREE_REFRESH_RATE,
+    damage::{DamageEvent, DamageKind, DamageSource},
     gun::BulletStats,
-    player::{DamageBoost, InvincibilityEffect},
+    perk::Perks,
+    player::InvincibilityEffect,
+    status::ApplyStatusEvent,
 };
 use bevy::{prelude::*, time::common_conditions::on_timer};
 use kd_tree::{KdPoint, KdTree};
 
 use crate::{
-    enemy::{spawn_explosion, Enemy, ExplosionAbility, HitFlash, Trail},
-    game_state::GameState,
-    gun::Bullet,
-    player::{Player, PlayerDamagedEvent},
+    enemy::{spawn_explosion, Burrowed, Enemy, ExplosionAbility, Trail},
+    game_state::{GameState, GameplaySet},
+    gun::{Bullet, BulletOwner},
+    player::{Downed, Player},
 };
 
 pub struct CollisionPlugin;
@@ -30,7 +32,7 @@ pub struct EnemyKdTree(pub KdTree<Collidable>);
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(EnemyKdTree::default()).add_systems(
-            Update,
+            FixedUpdate,
             (
                 handle_enemy_bullet_collision,
                 handle_enemy_player_collision,
@@ -38,6 +40,8 @@ impl Plugin for CollisionPlugin {
                 update_enemy_kd_tree
                     .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE))),
             )
+                .chain()
+                .in_set(GameplaySet::Collision)
                 .run_if(in_state(GameState::Combat)),
         );
     }
@@ -45,72 +49,100 @@ impl Plugin for CollisionPlugin {
 
 pub fn handle_enemy_player_collision(
     mut commands: Commands,
-    player_query: Query<&Transform, (With<Player>, Without<InvincibilityEffect>)>,
-    mut enemy_query: Query<(Entity, &Transform, &mut Enemy, Option<&ExplosionAbility>)>,
+    player_query: Query<
+        (Entity, &Transform),
+        (With<Player>, Without<InvincibilityEffect>, Without<Downed>),
+    >,
+    enemy_query: Query<(Entity, &Transform, &Enemy, &Name, Option<&ExplosionAbility>)>,
     tree: Res<EnemyKdTree>,
-    mut ev: EventWriter<PlayerDamagedEvent>,
+    mut ev: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
+    let mut exploded = Vec::new();
 
-    let player_transform = player_query.single();
-    let player_pos = player_transform.translation;
+    for (player_entity, player_transform) in player_query.iter() {
+        let player_pos = player_transform.translation;
 
-    let enemies = tree.0.within_radius(&[player_pos.x, player_pos.y], 50.0);
-    if enemies.is_empty() {
-        return;
-    }
-    if let Ok((entity, transform, enemy_component, explosion_ability)) =
-        enemy_query.get_mut(enemies.first().unwrap().entity)
-    {
-        if let Some(explosion) = explosion_ability {
-            spawn_explosion(
-                &mut commands,
-                transform.translation,
-                explosion.explosion_radius,
-                explosion.explosion_damage,
-            );
-            ev.send(PlayerDamagedEvent {
-                damage: explosion.explosion_damage,
-            });
-            commands.entity(entity).despawn();
-            return;
+        let enemies = tree.0.within_radius(&[player_pos.x, player_pos.y], 50.0);
+        let Some(nearby) = enemies.first() else {
+            continue;
+        };
+        if exploded.contains(&nearby.entity) {
+            continue;
         }
-        if enemy_component.damage > 0 {
-            ev.send(PlayerDamagedEvent {
-                damage: enemy_component.damage,
-            });
-            return;
+        if let Ok((entity, transform, enemy_component, name, explosion_ability)) =
+            enemy_query.get(nearby.entity)
+        {
+            if let Some(explosion) = explosion_ability {
+                spawn_explosion(
+                    &mut commands,
+                    transform.translation,
+                    explosion.explosion_radius,
+                    explosion.explosion_damage,
+                    name.clone(),
+                );
+                ev.send(DamageEvent {
+                    source: DamageSource::new(entity, name),
+                    target: player_entity,
+                    amount: explosion.explosion_damage,
+                    kind: DamageKind::Explosion,
+                });
+                if let Some(status) = explosion.status {
+                    ev_status.send(ApplyStatusEvent {
+                        target: player_entity,
+                        effect: status.effect(DamageSource::new(entity, name)),
+                    });
+                }
+                commands.entity(entity).despawn();
+                exploded.push(entity);
+                continue;
+            }
+            if enemy_component.damage > 0 {
+                ev.send(DamageEvent {
+                    source: DamageSource::new(entity, name),
+                    target: player_entity,
+                    amount: enemy_component.damage,
+                    kind: DamageKind::Contact,
+                });
+            }
         }
     }
 }
 
 fn handle_player_trail_collision(
-    mut player_query: Query<&Transform, (With<Player>, Without<InvincibilityEffect>)>,
+    player_query: Query<
+        (Entity, &Transform),
+        (With<Player>, Without<InvincibilityEffect>, Without<Downed>),
+    >,
     trail_query: Query<(&Transform, &Trail)>,
-    mut ev: EventWriter<PlayerDamagedEvent>,
+    mut ev: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let translation = player_query.single_mut();
-    let player_pos = translation.translation.xy();
-    for (trail_transform, trail) in trail_query.iter() {
-        let trail_pos = trail_transform.translation.xy();
-        if player_pos.distance(trail_pos) <= trail.radius {
-            ev.send(PlayerDamagedEvent {
-                damage: trail.damage,
+    for (player_entity, player_transform) in player_query.iter() {
+        let player_pos = player_transform.translation.xy();
+        let touched_trail = trail_query.iter().find(|(trail_transform, trail)| {
+            player_pos.distance(trail_transform.translation.xy()) <= trail.radius
+        });
+        if let Some((_, trail)) = touched_trail {
+            ev.send(DamageEvent {
+                source: DamageSource::named(&trail.source),
+                target: player_entity,
+                amount: trail.damage,
+                kind: DamageKind::Trail,
             });
-            return;
+            if let Some(status) = trail.status {
+                ev_status.send(ApplyStatusEvent {
+                    target: player_entity,
+                    effect: status.effect(DamageSource::named(&trail.source)),
+                });
+            }
         }
     }
 }
 
 fn update_enemy_kd_tree(
     mut tree: ResMut<EnemyKdTree>,
-    enemy_query: Query<(&Transform, Entity), With<Enemy>>,
+    enemy_query: Query<(&Transform, Entity), (With<Enemy>, Without<Burrowed>)>,
 ) {
     let mut items = Vec::new();
     for (t, e) in enemy_query.iter() {
@@ -125,31 +157,36 @@ fn update_enemy_kd_tree(
 
 fn handle_enemy_bullet_collision(
     mut commands: Commands,
-    bullet_query: Query<(&Transform, Entity, &BulletStats), With<Bullet>>,
+    bullet_query: Query<(&Transform, Entity, &BulletStats, &BulletOwner), With<Bullet>>,
     tree: Res<EnemyKdTree>,
-    mut enemy_query: Query<(&Transform, &mut Enemy, Entity), With<Enemy>>,
-    player_query: Query<&DamageBoost, With<Player>>,
-    mut ew: EventWriter<AudioEvent>,
+    enemy_query: Query<&Enemy>,
+    player_query: Query<(Entity, &Name, &Perks), With<Player>>,
+    mut ev: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
 ) {
-    if bullet_query.is_empty() || enemy_query.is_empty() || player_query.is_empty() {
+    if bullet_query.is_empty() || enemy_query.is_empty() {
         return;
     }
-    let player_damage_boost = player_query.get_single().unwrap().0;
-    for (bullet_transform, bullet_entity, stats) in bullet_query.iter() {
+    for (bullet_transform, bullet_entity, stats, owner) in bullet_query.iter() {
+        let Ok((player_entity, player_name, perks)) = player_query.get(owner.0) else {
+            continue;
+        };
         let pos = bullet_transform.translation;
         let enemies_in_radius = tree.0.within_radius(&[pos.x, pos.y], 30.0);
 
         if let Some(enemy) = enemies_in_radius.first() {
-            if let Ok((_, mut enemy_component, enemy_entity)) = enemy_query.get_mut(enemy.entity) {
-                if enemy_component.health > 0 {
-                    enemy_component.health = enemy_component
-                        .health
-                        .saturating_sub(stats.damage + player_damage_boost);
-
-                    if enemy_component.health > 0 {
-                        ew.send(AudioEvent::Hit);
-                        commands.entity(enemy_entity).insert(HitFlash::default());
-                    }
+            if enemy_query.contains(enemy.entity) {
+                ev.send(DamageEvent {
+                    source: DamageSource::new(player_entity, player_name),
+                    target: enemy.entity,
+                    amount: stats.damage,
+                    kind: DamageKind::Bullet,
+                });
+                for status in &perks.bullet_statuses {
+                    ev_status.send(ApplyStatusEvent {
+                        target: enemy.entity,
+                        effect: status.effect(DamageSource::new(player_entity, player_name)),
+                    });
                 }
 
                 commands.entity(bullet_entity).try_despawn();
@@ -25,17 +25,104 @@ pub const TILE_H_32: u32 = 32;
 pub const SPRITE_SHEET_W: u32 = 256 / TILE_W;
 pub const SPRITE_SHEET_H: u32 = 256 / TILE_H;
 
+// Simulation
+pub const FIXED_TIMESTEP_HZ: f64 = 60.0;
+pub const HEADLESS_MAX_INIT_UPDATES: u32 = 1000;
+
 // World
 pub const NUM_WORLD_DECORATIONS: usize = 100;
 
 // Player
-pub const PLAYER_SPEED: u32 = 10;
+pub const PLAYER_SPEED: u32 = 600;
 pub const PLAYER_HEALTH: u32 = 30;
 pub const PLAYER_INVINCIBLE_TIME: f32 = 0.5;
 pub const MAX_DEFENSE: u32 = 30;
+pub const SPEED_POTION_SCALE: u32 = 60;
+pub const PLAYER_DASH_SPEED: f32 = 2400.0;
+pub const PLAYER_DASH_DURATION: f32 = 0.15;
+pub const PLAYER_DASH_INVINCIBLE_TIME: f32 = 0.3;
+pub const PLAYER_DASH_COOLDOWN: f32 = 1.5;
+pub const PLAYER_PICKUP_RADIUS: f32 = 400.0;
+pub const INVENTORY_SLOTS: usize = 4;
+pub const MAX_POTION_SLOTS: usize = 6;
 
 // Enemy
 pub const REPEL_MARGIN: f32 = 100.0;
+pub const ENEMY_DEFINITIONS_PATH: &str = "enemies.ron";
+pub const NORMAL_WAVE_SCRIPT_PATH: &str = "waves/normal.ron";
+pub const FOREVER_WAVE_SCRIPT_PATH: &str = "waves/forever.ron";
+
+// Boss
+pub const BOSS_BARRAGE_MIN_BULLETS: usize = 8;
+pub const BOSS_BARRAGE_TELEGRAPH_TIME: f32 = 0.8;
+pub const BOSS_BARRAGE_MARKER_SIZE: f32 = 420.0;
+pub const BOSS_CHARGE_MARKER_SIZE: f32 = 180.0;
+pub const BOSS_CHARGE_MARKER_LINGER: f32 = 0.6;
+pub const BOSS_ERUPTION_TELEGRAPH_TIME: f32 = 1.0;
+pub const BOSS_HAZARD_SPREAD: f32 = 600.0;
+pub const BOSS_PHASE_FLASH_TIME: f32 = 0.4;
+pub const BOSS_ENRAGE_COLOR: (u8, u8, u8) = (255, 110, 110);
+pub const BOSS_BAR_WIDTH: f32 = 600.0;
+pub const BOSS_BAR_HEIGHT: f32 = 18.0;
+
+// Elite
+pub const ELITE_MAX_AFFIXES: usize = 3;
+pub const ELITE_EXTRA_AFFIX_CHANCE: f32 = 0.3;
+pub const ELITE_HEALTH_MULTIPLIER: f32 = 1.6;
+pub const ELITE_XP_MULTIPLIER: f32 = 2.0;
+pub const ELITE_LOOT_BONUS_PER_AFFIX: f32 = 0.75;
+pub const ELITE_AFFIX_DURATION: f32 = 3600.0;
+pub const ELITE_SHIELD_FRACTION: f32 = 0.5;
+pub const ELITE_HASTE_MULTIPLIER: f32 = 1.4;
+pub const ELITE_REGEN_FRACTION: f32 = 0.03;
+pub const ELITE_VAMPIRIC_HEAL_FRACTION: f32 = 0.2;
+pub const ELITE_EXPLOSION_RADIUS: f32 = 150.0;
+pub const ELITE_EXPLOSION_DAMAGE: u32 = 15;
+pub const ELITE_REFLECT_CHANCE: f32 = 0.3;
+pub const ELITE_REFLECT_BULLET_SPEED: u32 = 600;
+pub const ELITE_REFLECT_BULLET_DAMAGE: u32 = 5;
+pub const ELITE_SUMMON_INTERVAL: f32 = 6.0;
+
+// Perks
+pub const PERK_DEFINITIONS_PATH: &str = "perks.ron";
+pub const PERK_CHOICES: usize = 3;
+
+// Classes
+pub const CLASS_DEFINITIONS_PATH: &str = "classes.ron";
+pub const DEFAULT_CLASS: &str = "Diver";
+pub const FAMILIAR_SPRITE_INDEX: usize = 24;
+pub const FAMILIAR_ORBIT_RADIUS: f32 = 90.0;
+pub const FAMILIAR_ORBIT_SPEED: f32 = 2.5;
+pub const FAMILIAR_BULLET_SPEED: u32 = 900;
+
+// Input
+pub const NAVIGATION_REPEAT_DELAY: f32 = 0.4;
+pub const NAVIGATION_REPEAT_INTERVAL: f32 = 0.12;
+pub const NAVIGATION_STICK_THRESHOLD: f32 = 0.5;
+pub const GAMEPAD_DEVICE_DEADZONE: f32 = 0.3;
+pub const INPUT_BUFFER_WINDOW: f32 = 0.15;
+pub const INSPECTOR_TOGGLE_KEY: bevy::prelude::KeyCode = bevy::prelude::KeyCode::F1;
+
+// Co-op
+pub const COOP_JOIN_BUTTONS: [bevy::prelude::GamepadButton; 3] = [
+    bevy::prelude::GamepadButton::RightThumb,
+    bevy::prelude::GamepadButton::LeftThumb,
+    bevy::prelude::GamepadButton::Mode,
+];
+pub const COOP_SPAWN_OFFSET: f32 = 80.0;
+pub const COOP_REVIVE_RADIUS: f32 = 90.0;
+pub const COOP_REVIVE_TIME: f32 = 3.0;
+pub const COOP_REVIVE_HEALTH_FRACTION: f32 = 0.5;
+
+// Camera
+pub const CAMERA_SCALE: f32 = 1.3;
+pub const CAMERA_MAX_SCALE: f32 = 2.2;
+pub const CAMERA_FRAME_MARGIN: f32 = 250.0;
+
+// Aim
+pub const AUTO_AIM_RANGE: f32 = 700.0;
+pub const CROSSHAIR_STICK_DISTANCE: f32 = 300.0;
+pub const HOMING_RADIUS: f32 = 300.0;
 
 // Kd-tree
 pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
@@ -43,11 +130,38 @@ pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
 // Default Gun
 pub const FIRING_INTERVAL: f32 = 0.4;
 pub const BULLET_TIME_SECS: f32 = 0.5;
-pub const BULLET_SPEED: u32 = 10;
+pub const BULLET_SPEED: u32 = 600;
 pub const BULLET_DAMAGE: u32 = 25;
 pub const BULLET_SPREAD: f32 = 0.7;
 pub const NUM_BULLETS_PER_SHOT: usize = 4;
 
+// Save
+pub const SAVE_FILE_PATH: &str = "fishmans_adventure.save.ron";
+pub const SAVE_VERSION: u32 = 3;
+
+// Replay
+pub const REPLAY_FILE_PATH: &str = "fishmans_adventure.replay.ron";
+pub const REPLAY_VERSION: u32 = 3;
+
+// Settings
+pub const SETTINGS_FILE_PATH: &str = "fishmans_adventure.settings.ron";
+pub const SETTINGS_VERSION: u32 = 2;
+
+// Rng
+pub const SEED_ENV_VAR: &str = "FISHMANS_ADVENTURE_SEED";
+
+// Net
+pub const NET_HOST_ENV_VAR: &str = "FISHMANS_ADVENTURE_HOST";
+pub const NET_CONNECT_ENV_VAR: &str = "FISHMANS_ADVENTURE_CONNECT";
+pub const NET_DEFAULT_PORT: u16 = 7777;
+pub const NET_SNAPSHOT_INTERVAL: f32 = 0.05;
+pub const NET_HELLO_INTERVAL: f32 = 1.0;
+pub const NET_INTERPOLATION_DELAY: f64 = 0.1;
+pub const NET_CLOCK_SNAP_THRESHOLD: f64 = 0.25;
+pub const NET_TIMEOUT: f64 = 5.0;
+pub const NET_MAX_SNAPSHOT_BYTES: usize = 60_000;
+pub const NET_MAX_PACKET_SIZE: usize = 65_000;
+
 // Colors
 pub const BG_COLOR: (u8, u8, u8) = (72, 59, 58);
 pub const UI_BG_COLOR: (u8, u8, u8) = (197, 204, 184);
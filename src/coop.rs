@@ -0,0 +1,227 @@
+use bevy::{prelude::*, time::Stopwatch};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    bindings::Bindings,
+    configs::{
+        COOP_REVIVE_HEALTH_FRACTION, COOP_REVIVE_RADIUS, COOP_REVIVE_TIME, PLAYER_INVINCIBLE_TIME,
+    },
+    damage::resolve_damage,
+    game_state::{GameState, GameplaySet},
+    input::{apply_bindings, CombatAction},
+    player::{Downed, Health, InvincibilityEffect, OriginalColor, Player, PlayerSlot},
+    resources::UiFont,
+    ui::systems::in_game_ui::spawn_floating_text,
+};
+
+#[derive(Resource, Default)]
+pub struct CoopSession {
+    pub gamepad: Option<Entity>,
+    pub remote: bool,
+}
+
+impl CoopSession {
+    pub fn is_active(&self) -> bool {
+        self.gamepad.is_some() || self.remote
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MenuOwner(pub Option<Entity>);
+
+pub struct CoopPlugin;
+
+impl Plugin for CoopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoopSession>()
+            .init_resource::<MenuOwner>()
+            .add_systems(OnEnter(GameState::MainMenu), reset_coop_session)
+            .add_systems(
+                Update,
+                handle_coop_join.run_if(in_state(GameState::ClassSelect)),
+            )
+            .add_systems(Update, assign_player_gamepads.after(apply_bindings))
+            .add_systems(
+                FixedUpdate,
+                (handle_player_downed, revive_downed_players)
+                    .chain()
+                    .after(resolve_damage)
+                    .in_set(GameplaySet::Damage)
+                    .run_if(in_state(GameState::Combat)),
+            );
+    }
+}
+
+pub fn nearest_player<'a>(
+    position: Vec3,
+    players: impl IntoIterator<Item = (Entity, &'a Transform)>,
+) -> Option<(Entity, Vec3)> {
+    players
+        .into_iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+}
+
+pub fn player_just_pressed<'a>(
+    action: CombatAction,
+    action_state: &ActionState<CombatAction>,
+    players: impl IntoIterator<Item = (Entity, Option<&'a ActionState<CombatAction>>)>,
+) -> Option<Entity> {
+    players.into_iter().find_map(|(entity, player_state)| {
+        player_state
+            .unwrap_or(action_state)
+            .just_pressed(&action)
+            .then_some(entity)
+    })
+}
+
+fn reset_coop_session(mut session: ResMut<CoopSession>) {
+    *session = CoopSession::default();
+}
+
+fn handle_coop_join(
+    gamepads: Query<(Entity, &Gamepad)>,
+    bindings: Res<Bindings>,
+    mut session: ResMut<CoopSession>,
+) {
+    if session.remote {
+        return;
+    }
+    let Some(join_button) = bindings.coop_join_button() else {
+        return;
+    };
+    for (entity, gamepad) in gamepads.iter() {
+        if !gamepad.just_pressed(join_button) {
+            continue;
+        }
+        if session.gamepad == Some(entity) {
+            session.gamepad = None;
+            info!("Player 2 left");
+        } else if session.gamepad.is_none() {
+            session.gamepad = Some(entity);
+            info!("Player 2 joined");
+        }
+    }
+}
+
+fn assign_player_gamepads(
+    session: Res<CoopSession>,
+    bindings: Res<Bindings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    input_map: Option<ResMut<InputMap<CombatAction>>>,
+    mut player_maps: Query<&mut InputMap<CombatAction>, With<Player>>,
+) {
+    let gamepad = session.gamepad.map(|coop_gamepad| {
+        gamepads
+            .iter()
+            .find(|&entity| entity != coop_gamepad)
+            .unwrap_or(Entity::PLACEHOLDER)
+    });
+    if let Some(mut input_map) = input_map.filter(|input_map| input_map.gamepad() != gamepad) {
+        match gamepad {
+            Some(gamepad) => input_map.set_gamepad(gamepad),
+            None => input_map.clear_gamepad(),
+        };
+    }
+
+    if let (Some(coop_gamepad), true) = (session.gamepad, bindings.is_changed()) {
+        for mut player_map in player_maps.iter_mut() {
+            *player_map = bindings.gamepad_combat_input_map(coop_gamepad);
+        }
+    }
+}
+
+fn handle_player_downed(
+    mut commands: Commands,
+    mut player_query: Query<
+        (Entity, &Health, &Transform, &mut Sprite),
+        (With<Player>, Without<Downed>),
+    >,
+    health_query: Query<&Health, With<Player>>,
+    font: Res<UiFont>,
+) {
+    if health_query.iter().all(|health| health.0 == 0) {
+        return;
+    }
+
+    for (entity, health, transform, mut sprite) in player_query.iter_mut() {
+        if health.0 > 0 {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .insert(Downed {
+                revive: Timer::from_seconds(COOP_REVIVE_TIME, TimerMode::Once),
+            })
+            .remove::<InvincibilityEffect>();
+        sprite.color = Color::srgba(0.4, 0.4, 0.4, 0.5);
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            "Downed!".to_string(),
+            Some(Color::srgb(0.6, 0.6, 0.6)),
+        );
+    }
+}
+
+fn revive_downed_players(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut downed_query: Query<(
+        Entity,
+        &Transform,
+        &mut Downed,
+        &mut Health,
+        &mut Sprite,
+        &OriginalColor,
+        &PlayerSlot,
+    )>,
+    rescuer_query: Query<(&Transform, &Health), (With<Player>, Without<Downed>)>,
+    font: Res<UiFont>,
+) {
+    for (entity, transform, mut downed, mut health, mut sprite, original_color, slot) in
+        downed_query.iter_mut()
+    {
+        let rescued = rescuer_query.iter().any(|(rescuer, rescuer_health)| {
+            rescuer_health.0 > 0
+                && rescuer
+                    .translation
+                    .xy()
+                    .distance(transform.translation.xy())
+                    <= COOP_REVIVE_RADIUS
+        });
+        if !rescued {
+            downed.revive.reset();
+            sprite.color = Color::srgba(0.4, 0.4, 0.4, 0.5);
+            continue;
+        }
+
+        downed.revive.tick(time.delta());
+        sprite.color = Color::srgba(0.4, 0.4, 0.4, 0.5 + downed.revive.fraction() * 0.5);
+        if !downed.revive.finished() {
+            continue;
+        }
+
+        health.0 = ((health.1 as f32 * COOP_REVIVE_HEALTH_FRACTION) as u32).max(1);
+        sprite.color = original_color.0;
+        commands
+            .entity(entity)
+            .remove::<Downed>()
+            .insert(InvincibilityEffect(
+                Stopwatch::new(),
+                PLAYER_INVINCIBLE_TIME,
+            ));
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            format!("{} Revived!", slot.label()),
+            Some(Color::srgb_u8(0, 128, 0)),
+        );
+        info!("{} revived", slot.label());
+    }
+}
@@ -0,0 +1,220 @@
+use bevy::{prelude::*, time::Stopwatch};
+
+use crate::{
+    armor::{ActiveArmor, Armor, ArmorStats},
+    audio::AudioEvent,
+    configs::PLAYER_INVINCIBLE_TIME,
+    enemy::{Enemy, HitFlash},
+    game_state::{GameState, GameplaySet},
+    player::{DamageBoost, Defense, Health, InvincibilityEffect, Player, PlayerInventory},
+    resources::UiFont,
+    status::{StatusEffects, StatusKind},
+    ui::systems::in_game_ui::spawn_floating_text,
+    utils::calculate_defense_percentage,
+};
+
+pub struct DamagePlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageKind {
+    Contact,
+    Bullet,
+    Explosion,
+    Trail,
+    Status,
+}
+
+#[derive(Clone, Debug)]
+pub struct DamageSource {
+    pub entity: Option<Entity>,
+    pub name: Name,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub source: DamageSource,
+    pub target: Entity,
+    pub amount: u32,
+    pub kind: DamageKind,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DamageAppliedEvent {
+    pub source: DamageSource,
+    pub target: Entity,
+    pub amount: u32,
+    pub kind: DamageKind,
+    pub fatal: bool,
+}
+
+impl DamageSource {
+    pub fn new(entity: Entity, name: &Name) -> Self {
+        Self {
+            entity: Some(entity),
+            name: name.clone(),
+        }
+    }
+
+    pub fn named(name: &Name) -> Self {
+        Self {
+            entity: None,
+            name: name.clone(),
+        }
+    }
+}
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DamageAppliedEvent>()
+            .add_systems(
+                FixedUpdate,
+                resolve_damage
+                    .run_if(on_event::<DamageEvent>)
+                    .in_set(GameplaySet::Damage)
+                    .run_if(in_state(GameState::Combat)),
+            );
+    }
+}
+
+pub fn resolve_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    mut applied_events: EventWriter<DamageAppliedEvent>,
+    mut player_query: Query<
+        (
+            &mut Health,
+            &Defense,
+            &mut PlayerInventory,
+            &Transform,
+            Has<InvincibilityEffect>,
+        ),
+        With<Player>,
+    >,
+    mut enemy_query: Query<&mut Enemy>,
+    mut status_query: Query<&mut StatusEffects>,
+    mut armor_query: Query<&mut ArmorStats, With<Armor>>,
+    boost_query: Query<&DamageBoost>,
+    font: Res<UiFont>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    let mut hit_players = Vec::new();
+
+    for event in events.read() {
+        if let Ok((mut health, defense, mut inventory, transform, is_invincible)) =
+            player_query.get_mut(event.target)
+        {
+            let is_status = event.kind == DamageKind::Status;
+            if health.0 == 0
+                || (!is_status && (is_invincible || hit_players.contains(&event.target)))
+            {
+                continue;
+            }
+
+            let mut total_defense = defense.0;
+            let active_armor = inventory
+                .armors
+                .get(inventory.active_armor_index)
+                .copied()
+                .filter(|armor_entity| armor_query.contains(*armor_entity));
+            if let Some(armor_entity) = active_armor {
+                total_defense += armor_query.get(armor_entity).unwrap().defense;
+            }
+            let mut amount =
+                (event.amount as f32 * calculate_defense_percentage(total_defense)) as u32;
+            amount = absorb_with_shield(&mut status_query, event.target, amount);
+
+            if let Some(armor_entity) = active_armor {
+                let mut armor_stats = armor_query.get_mut(armor_entity).unwrap();
+                armor_stats.durability = armor_stats.durability.saturating_sub(amount);
+
+                if armor_stats.durability == 0 {
+                    commands.entity(armor_entity).despawn();
+                    let armor_to_remove = inventory.active_armor_index;
+                    inventory.armors.remove(armor_to_remove);
+                    if inventory.active_armor_index >= inventory.armors.len() {
+                        inventory.active_armor_index = 0;
+                    }
+                    if let Some(new_active_armor_entity) =
+                        inventory.armors.get(inventory.active_armor_index)
+                    {
+                        commands
+                            .entity(*new_active_armor_entity)
+                            .insert(ActiveArmor);
+                    }
+                }
+            }
+
+            if amount == 0 {
+                continue;
+            }
+
+            health.0 = health.0.saturating_sub(amount);
+            if !is_status {
+                ew.send(AudioEvent::Hit);
+                commands.entity(event.target).insert(InvincibilityEffect(
+                    Stopwatch::new(),
+                    PLAYER_INVINCIBLE_TIME,
+                ));
+                hit_players.push(event.target);
+            }
+            spawn_floating_text(
+                &mut commands,
+                &font.0,
+                transform.translation,
+                format!("-{}", amount),
+                None,
+            );
+
+            applied_events.send(DamageAppliedEvent {
+                source: event.source.clone(),
+                target: event.target,
+                amount,
+                kind: event.kind,
+                fatal: health.0 == 0,
+            });
+        } else if let Ok(mut enemy) = enemy_query.get_mut(event.target) {
+            if enemy.health == 0 {
+                continue;
+            }
+
+            let damage_boost = event
+                .source
+                .entity
+                .filter(|_| event.kind != DamageKind::Status)
+                .and_then(|source| boost_query.get(source).ok())
+                .map_or(0, |boost| boost.0);
+            let mut amount = event.amount + damage_boost;
+            amount = absorb_with_shield(&mut status_query, event.target, amount);
+            let amount = amount.min(enemy.health);
+            if amount == 0 {
+                continue;
+            }
+            enemy.health -= amount;
+
+            if enemy.health > 0 && event.kind != DamageKind::Status {
+                ew.send(AudioEvent::Hit);
+                commands.entity(event.target).insert(HitFlash::default());
+            }
+
+            applied_events.send(DamageAppliedEvent {
+                source: event.source.clone(),
+                target: event.target,
+                amount,
+                kind: event.kind,
+                fatal: enemy.health == 0,
+            });
+        }
+    }
+}
+
+fn absorb_with_shield(
+    status_query: &mut Query<&mut StatusEffects>,
+    target: Entity,
+    amount: u32,
+) -> u32 {
+    match status_query.get_mut(target) {
+        Ok(mut effects) if effects.has(StatusKind::Shield) => effects.absorb(amount),
+        _ => amount,
+    }
+}
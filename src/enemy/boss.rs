@@ -0,0 +1,337 @@
+use super::{
+    builder::{insert_ability, remove_abilities},
+    components::*,
+    definitions::AbilitySpec,
+    systems::spawn_explosion,
+};
+use crate::{
+    audio::AudioEvent,
+    configs::*,
+    coop::nearest_player,
+    player::{Downed, Player},
+    resources::UiFont,
+    stats::{StatKind, StatModifier, Stats},
+    ui::systems::in_game_ui::spawn_floating_text,
+    utils::InGameEntity,
+};
+use bevy::prelude::*;
+
+pub fn announce_bosses(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    boss_query: Query<(Ref<BossState>, &Transform, &Name)>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    if boss_query.is_empty() || !boss_query.iter().all(|(boss, _, _)| boss.is_added()) {
+        return;
+    }
+    let count = boss_query.iter().count();
+    for (_, transform, name) in boss_query.iter().take(1) {
+        let text = if count > 1 {
+            format!("{} x{} approach!", name, count)
+        } else {
+            format!("{} approaches!", name)
+        };
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            text,
+            Some(Color::srgb(1.0, 0.8, 0.2)),
+        );
+    }
+    ew.send(AudioEvent::LevelUp);
+}
+
+pub fn update_boss_phases(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    mut boss_query: Query<(
+        Entity,
+        &Enemy,
+        &mut Stats,
+        &mut BossState,
+        &Transform,
+        &Name,
+    )>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    for (entity, enemy, mut stats, mut boss, transform, name) in boss_query.iter_mut() {
+        if enemy.health == 0 {
+            continue;
+        }
+        let health_fraction = enemy.health as f32 / stats.value(StatKind::MaxHealth).max(1.0);
+        let mut next_phase = boss.phase;
+        while next_phase < boss.phases.len() && health_fraction <= boss.phases[next_phase].threshold
+        {
+            next_phase += 1;
+        }
+        if next_phase == boss.phase {
+            continue;
+        }
+
+        boss.phase = next_phase;
+        boss.charge_telegraphed = false;
+        boss.barrage_telegraphed = false;
+        let phase = boss.phases[next_phase - 1].clone();
+        let fire_rate = match boss.enrage {
+            Some(enrage) if boss.enraged => enrage.fire_rate,
+            _ => 1.0,
+        };
+
+        swap_abilities(
+            &mut commands,
+            entity,
+            &mut stats,
+            &phase.abilities,
+            fire_rate,
+        );
+        stats.remove_modifiers_from("Boss Phase");
+        stats.add_modifier(StatModifier::multiply(
+            StatKind::Speed,
+            phase.speed,
+            "Boss Phase",
+        ));
+        commands.entity(entity).insert(HitFlash(Timer::from_seconds(
+            BOSS_PHASE_FLASH_TIME,
+            TimerMode::Once,
+        )));
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            format!("{}: {}", name, phase.name),
+            Some(Color::srgb(1.0, 0.6, 0.1)),
+        );
+        ew.send(AudioEvent::PopUp);
+    }
+}
+
+pub fn update_boss_enrage(
+    mut commands: Commands,
+    time: Res<Time>,
+    font: Res<UiFont>,
+    mut boss_query: Query<(Entity, &mut Stats, &mut BossState, &Transform, &Name)>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    for (entity, mut stats, mut boss, transform, name) in boss_query.iter_mut() {
+        let Some(enrage) = boss.enrage else {
+            continue;
+        };
+        if boss.enraged || !boss.enrage_timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        boss.enraged = true;
+        boss.charge_telegraphed = false;
+        boss.barrage_telegraphed = false;
+        boss.eruption_telegraphed = false;
+        swap_abilities(
+            &mut commands,
+            entity,
+            &mut stats,
+            boss.current_abilities(),
+            enrage.fire_rate,
+        );
+        stats.add_modifier(StatModifier::multiply(
+            StatKind::Speed,
+            enrage.speed,
+            "Enrage",
+        ));
+        stats.add_modifier(StatModifier::multiply(
+            StatKind::Damage,
+            enrage.damage,
+            "Enrage",
+        ));
+
+        commands.entity(entity).insert((
+            OriginalEnemyColor(Color::srgb_u8(
+                BOSS_ENRAGE_COLOR.0,
+                BOSS_ENRAGE_COLOR.1,
+                BOSS_ENRAGE_COLOR.2,
+            )),
+            HitFlash(Timer::from_seconds(BOSS_PHASE_FLASH_TIME, TimerMode::Once)),
+        ));
+        spawn_floating_text(
+            &mut commands,
+            &font.0,
+            transform.translation,
+            format!("{} is enraged!", name),
+            None,
+        );
+        ew.send(AudioEvent::PopUp);
+    }
+}
+
+fn swap_abilities(
+    commands: &mut Commands,
+    entity: Entity,
+    stats: &mut Stats,
+    abilities: &[AbilitySpec],
+    fire_rate: f32,
+) {
+    remove_abilities(commands, entity);
+    stats.remove_modifiers_from("Burrow");
+    for ability in abilities {
+        insert_ability(commands, entity, ability.clone().hastened(fire_rate));
+    }
+}
+
+pub fn telegraph_boss_attacks(
+    mut commands: Commands,
+    mut boss_query: Query<(
+        Entity,
+        &Transform,
+        &mut BossState,
+        Option<&ChargeAbility>,
+        Option<&ShootingAbility>,
+        Option<&BurrowAbility>,
+        Has<Burrowed>,
+    )>,
+) {
+    for (entity, transform, mut boss, charge, shooting, burrow, burrowed) in boss_query.iter_mut() {
+        let preparing_charge =
+            charge.is_some_and(|charge| matches!(charge.state, ChargeState::Preparing));
+        if preparing_charge && !boss.charge_telegraphed {
+            spawn_telegraph(
+                &mut commands,
+                entity,
+                TelegraphKind::Charge,
+                transform.translation,
+                BOSS_CHARGE_MARKER_LINGER,
+                BOSS_CHARGE_MARKER_SIZE,
+            );
+        }
+        boss.charge_telegraphed = preparing_charge;
+
+        let barrage_remaining = shooting
+            .filter(|shooting| {
+                shooting.in_range
+                    && shooting.bullets_per_shot >= BOSS_BARRAGE_MIN_BULLETS
+                    && shooting.shoot_timer.remaining_secs() <= BOSS_BARRAGE_TELEGRAPH_TIME
+            })
+            .map(|shooting| shooting.shoot_timer.remaining_secs());
+        if let (Some(remaining), false) = (barrage_remaining, boss.barrage_telegraphed) {
+            spawn_telegraph(
+                &mut commands,
+                entity,
+                TelegraphKind::Barrage,
+                transform.translation,
+                remaining.max(0.05),
+                0.0,
+            );
+        }
+        boss.barrage_telegraphed = barrage_remaining.is_some();
+
+        let eruption = burrow
+            .filter(|burrow| {
+                burrowed && burrow.timer.remaining_secs() <= BOSS_ERUPTION_TELEGRAPH_TIME
+            })
+            .map(|burrow| (burrow.timer.remaining_secs(), burrow.eruption_radius));
+        if let (Some((remaining, radius)), false) = (eruption, boss.eruption_telegraphed) {
+            spawn_telegraph(
+                &mut commands,
+                entity,
+                TelegraphKind::Eruption,
+                transform.translation,
+                remaining.max(0.05),
+                radius * 2.0,
+            );
+        }
+        boss.eruption_telegraphed = eruption.is_some();
+    }
+}
+
+pub fn spawn_telegraph(
+    commands: &mut Commands,
+    owner: Entity,
+    kind: TelegraphKind,
+    position: Vec3,
+    duration: f32,
+    size: f32,
+) {
+    commands.spawn((
+        Name::new("Boss Telegraph"),
+        Sprite {
+            color: Color::srgba(1.0, 0.1, 0.1, 0.0),
+            custom_size: Some(Vec2::splat(size)),
+            ..default()
+        },
+        Transform::from_translation(position.truncate().extend(LAYER1)),
+        BossTelegraph {
+            owner,
+            kind,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        },
+        InGameEntity,
+    ));
+}
+
+pub fn update_boss_telegraphs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut telegraph_query: Query<
+        (Entity, &mut BossTelegraph, &mut Transform, &mut Sprite),
+        (Without<Enemy>, Without<Player>),
+    >,
+    owner_query: Query<(&Transform, &Name, Option<&ChargeAbility>), With<Enemy>>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
+) {
+    for (entity, mut telegraph, mut transform, mut sprite) in telegraph_query.iter_mut() {
+        let Ok((owner_transform, owner_name, charge)) = owner_query.get(telegraph.owner) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let alpha = match telegraph.kind {
+            TelegraphKind::Charge => {
+                let preparing =
+                    charge.is_some_and(|charge| matches!(charge.state, ChargeState::Preparing));
+                if preparing {
+                    if let Some((_, player_pos)) =
+                        nearest_player(owner_transform.translation, &player_query)
+                    {
+                        transform.translation = player_pos.truncate().extend(LAYER1);
+                    }
+                    0.25 + 0.2 * (time.elapsed_secs() * 12.0).sin()
+                } else {
+                    if let Some(target) = charge.and_then(|charge| charge.target_position) {
+                        transform.translation = target.extend(LAYER1);
+                    }
+                    telegraph.timer.tick(time.delta());
+                    0.45 * telegraph.timer.fraction_remaining()
+                }
+            }
+            TelegraphKind::Barrage => {
+                telegraph.timer.tick(time.delta());
+                let progress = telegraph.timer.fraction();
+                transform.translation = owner_transform.translation.truncate().extend(LAYER1);
+                sprite.custom_size = Some(Vec2::splat(BOSS_BARRAGE_MARKER_SIZE * progress));
+                0.1 + 0.3 * progress
+            }
+            TelegraphKind::Eruption => {
+                telegraph.timer.tick(time.delta());
+                transform.translation = owner_transform.translation.truncate().extend(LAYER1);
+                0.15 + 0.3 * telegraph.timer.fraction()
+            }
+            TelegraphKind::Hazard { radius, damage } => {
+                telegraph.timer.tick(time.delta());
+                if telegraph.timer.finished() {
+                    spawn_explosion(
+                        &mut commands,
+                        transform.translation.truncate().extend(LAYER2),
+                        radius,
+                        damage,
+                        owner_name.clone(),
+                    );
+                }
+                0.1 + 0.35 * telegraph.timer.fraction()
+            }
+        };
+        sprite.color = Color::srgba(1.0, 0.1, 0.1, alpha);
+
+        if telegraph.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -1,29 +1,51 @@
-use super::components::*;
-use crate::{loot::LootPool, resources::GlobalTextureAtlas};
+use super::{
+    components::*,
+    definitions::{AbilitySpec, BossPhase, EnemyDefinition, EnrageSpec},
+};
+use crate::{
+    configs::*,
+    damage::DamageSource,
+    loot::{LootPool, LootTier},
+    resources::GlobalTextureAtlas,
+    stats::{StatKind, StatModifier, Stats},
+    status::{StatusEffect, StatusEffects, StatusKind},
+};
 use bevy::prelude::*;
 
 pub struct EnemyBuilder {
+    pub name: String,
     pub health: u32,
     pub speed: u32,
     pub damage: u32,
     pub xp: u32,
     pub sprite_index: usize,
     pub sprite_size: (u32, u32),
-    pub abilities: Vec<Box<dyn FnOnce(&mut Commands, Entity)>>,
+    pub tint: Color,
+    pub abilities: Vec<AbilitySpec>,
     pub loot_pool: Option<LootPool>,
+    pub boss: bool,
+    pub phases: Vec<BossPhase>,
+    pub enrage: Option<EnrageSpec>,
+    pub affixes: Vec<EliteAffix>,
 }
 
 impl Default for EnemyBuilder {
     fn default() -> Self {
         Self {
+            name: "Enemy".to_owned(),
             health: 100,
-            speed: 6,
+            speed: 360,
             damage: 6,
             xp: 4,
             sprite_index: 16,
             sprite_size: (16, 16),
+            tint: Color::WHITE,
             abilities: Vec::new(),
             loot_pool: None,
+            boss: false,
+            phases: Vec::new(),
+            enrage: None,
+            affixes: Vec::new(),
         }
     }
 }
@@ -33,6 +55,33 @@ impl EnemyBuilder {
         Self::default()
     }
 
+    pub fn from_definition(definition: &EnemyDefinition) -> Self {
+        Self {
+            name: definition.name.clone(),
+            health: definition.stats.health,
+            speed: definition.stats.speed,
+            damage: definition.stats.damage,
+            xp: definition.stats.xp,
+            sprite_index: definition.sprite.index,
+            sprite_size: definition.sprite.size,
+            tint: definition
+                .sprite
+                .tint
+                .map_or(Color::WHITE, |(r, g, b)| Color::srgb_u8(r, g, b)),
+            abilities: definition.abilities.clone(),
+            loot_pool: definition.loot.map(|tier| tier.loot_pool()),
+            boss: definition.boss,
+            phases: definition.phases.clone(),
+            enrage: definition.enrage,
+            affixes: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
     pub fn with_stats(mut self, health: u32, speed: u32, damage: u32, xp: u32) -> Self {
         self.health = health;
         self.speed = speed;
@@ -52,97 +101,66 @@ impl EnemyBuilder {
         self
     }
 
-    pub fn with_trail(mut self, damage: u32, interval: f32, radius: f32, duration: f32) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(TrailAbility {
-                timer: Timer::from_seconds(interval, TimerMode::Repeating),
-                damage,
-                trail_radius: radius,
-                trail_duration: duration,
-                last_position: None,
-            });
-        }));
-        self
-    }
-
-    pub fn with_explosion(mut self, radius: f32, damage: u32) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(ExplosionAbility {
-                explosion_radius: radius,
-                explosion_damage: damage,
-            });
-        }));
-        self
-    }
-
-    pub fn with_shooting(
-        mut self,
-        bullets: usize,
-        shoot_interval: f32,
-        range: f32,
-        bullet_speed: u32,
-        bullet_damage: u32,
-    ) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(ShootingAbility {
-                shoot_timer: Timer::from_seconds(shoot_interval, TimerMode::Repeating),
-                bullets_per_shot: bullets,
-                range,
-                in_range: false,
-                bullet_speed,
-                bullet_damage,
-            });
-        }));
-        self
-    }
-
-    pub fn with_charge(
-        mut self,
-        distance: u32,
-        speed: u32,
-        prepare_time: f32,
-        cooldown: f32,
-    ) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(ChargeAbility {
-                state: ChargeState::Approaching,
-                charge_timer: Timer::from_seconds(prepare_time, TimerMode::Once),
-                charge_distance: distance,
-                charge_speed: speed,
-                target_position: None,
-                cooldown_duration: cooldown,
-            });
-        }));
+    pub fn as_boss(mut self) -> Self {
+        self.boss = true;
         self
     }
 
-    pub fn with_splitting(mut self, splits: u8) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(SplitAbility {
-                splits_remaining: splits,
-                num_splits: splits,
-            });
-        }));
+    pub fn with_affixes(mut self, affixes: Vec<EliteAffix>) -> Self {
+        self.affixes = affixes;
         self
     }
 
-    pub fn with_summoning(mut self, min_minions: u32, max_minions: u32, interval: f32) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(SummoningAbility {
-                timer: Timer::from_seconds(interval, TimerMode::Repeating),
-                min_minions,
-                max_minions,
-            });
-        }));
+    pub fn with_ability(mut self, ability: AbilitySpec) -> Self {
+        self.abilities.push(ability);
         self
     }
 
     pub fn spawn(
-        self,
+        mut self,
         commands: &mut Commands,
         position: Vec3,
         handle: &GlobalTextureAtlas,
     ) -> Entity {
+        if !self.affixes.is_empty() {
+            self.make_elite();
+        }
+        let mut stats = Stats::new([
+            (StatKind::MaxHealth, self.health as f32),
+            (StatKind::Speed, self.speed as f32),
+            (StatKind::Damage, self.damage as f32),
+        ]);
+        let mut status_effects = StatusEffects::default();
+        let source = DamageSource::named(&Name::new(self.name.clone()));
+        for affix in &self.affixes {
+            match affix {
+                EliteAffix::Shielded => {
+                    status_effects.apply(StatusEffect::new(
+                        StatusKind::Shield,
+                        self.health as f32 * ELITE_SHIELD_FRACTION,
+                        ELITE_AFFIX_DURATION,
+                        source.clone(),
+                    ));
+                }
+                EliteAffix::Hasted => {
+                    stats.add_modifier(StatModifier::multiply(
+                        StatKind::Speed,
+                        ELITE_HASTE_MULTIPLIER,
+                        "Hasted",
+                    ));
+                }
+                EliteAffix::Regenerating => {
+                    status_effects.apply(StatusEffect::new(
+                        StatusKind::Regen,
+                        (self.health as f32 * ELITE_REGEN_FRACTION).max(1.0),
+                        ELITE_AFFIX_DURATION,
+                        source.clone(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
         let layout = match self.sprite_size {
             (16, 16) => handle.layout_16x16.clone().unwrap(),
             (32, 32) => handle.layout_32x32.clone().unwrap(),
@@ -150,13 +168,14 @@ impl EnemyBuilder {
         };
         let entity = commands
             .spawn((
-                Name::new("Enemy"),
+                Name::new(self.name),
                 Sprite {
                     image: handle.image.clone().unwrap(),
                     texture_atlas: Some(TextureAtlas {
                         layout,
                         index: self.sprite_index,
                     }),
+                    color: self.tint,
                     ..default()
                 },
                 Transform::from_translation(position).with_scale(Vec3::splat(3.0)),
@@ -166,37 +185,226 @@ impl EnemyBuilder {
                     damage: self.damage,
                     xp: self.xp,
                 },
+                stats,
+                status_effects,
                 EnemyState::default(),
                 Collider { radius: 15 },
-                OriginalEnemyColor(Color::WHITE),
+                OriginalEnemyColor(self.tint),
             ))
             .id();
 
         if let Some(loot_pool) = self.loot_pool {
             commands.entity(entity).insert(loot_pool);
         }
+        if !self.affixes.is_empty() {
+            commands.entity(entity).insert(Elite {
+                affixes: self.affixes,
+            });
+        }
+        if self.boss {
+            commands.entity(entity).insert((
+                Boss,
+                BossState::new(self.abilities.clone(), self.phases, self.enrage),
+            ));
+        }
 
-        for ability_fn in self.abilities {
-            ability_fn(commands, entity);
+        for ability in self.abilities {
+            insert_ability(commands, entity, ability);
         }
 
         entity
     }
 
-    pub fn with_ranged_behavior(mut self, preferred_distance: f32, tolerance: f32) -> Self {
-        self.abilities.push(Box::new(move |commands, entity| {
-            commands.entity(entity).insert(RangedBehavior {
-                preferred_distance,
-                tolerance,
+    fn make_elite(&mut self) {
+        self.health = (self.health as f32 * ELITE_HEALTH_MULTIPLIER) as u32;
+        self.xp = (self.xp as f32 * ELITE_XP_MULTIPLIER) as u32;
+        self.tint = self.affixes[0].color();
+
+        let loot_factor = 1.0 + ELITE_LOOT_BONUS_PER_AFFIX * self.affixes.len() as f32;
+        let loot_pool = self
+            .loot_pool
+            .take()
+            .unwrap_or_else(|| LootTier::Medium.loot_pool());
+        self.loot_pool = Some(loot_pool.boosted(loot_factor));
+
+        let summons = self
+            .abilities
+            .iter()
+            .any(|ability| matches!(ability, AbilitySpec::Summoning { .. }));
+        if self.affixes.contains(&EliteAffix::Summoner) && !summons {
+            self.abilities.push(AbilitySpec::Summoning {
+                min_minions: 1,
+                max_minions: 2,
+                interval: ELITE_SUMMON_INTERVAL,
+                minions: vec![
+                    ("Basic Enemy".to_owned(), 0.7),
+                    ("Charging Enemy".to_owned(), 0.3),
+                ],
             });
-        }));
-        self
+        }
     }
+}
 
-    pub fn with_gurgle_marker(mut self) -> Self {
-        self.abilities.push(Box::new(|commands, entity| {
-            commands.entity(entity).insert(GurgleEnemy);
-        }));
-        self
+pub fn remove_abilities(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).remove::<(
+        TrailAbility,
+        ExplosionAbility,
+        ShootingAbility,
+        ChargeAbility,
+        SplitAbility,
+        SummoningAbility,
+        RangedBehavior,
+        GurgleEnemy,
+        SpiralAbility,
+        BurrowAbility,
+        HazardAbility,
+    )>();
+    commands
+        .entity(entity)
+        .remove::<Burrowed>()
+        .insert(Visibility::Inherited);
+}
+
+pub fn insert_ability(commands: &mut Commands, entity: Entity, ability: AbilitySpec) {
+    let mut entity_commands = commands.entity(entity);
+    match ability {
+        AbilitySpec::Trail {
+            damage,
+            interval,
+            radius,
+            duration,
+            status,
+        } => {
+            entity_commands.insert(TrailAbility {
+                timer: Timer::from_seconds(interval, TimerMode::Repeating),
+                damage,
+                trail_radius: radius,
+                trail_duration: duration,
+                last_position: None,
+                status,
+            });
+        }
+        AbilitySpec::Explosion {
+            radius,
+            damage,
+            status,
+        } => {
+            entity_commands.insert(ExplosionAbility {
+                explosion_radius: radius,
+                explosion_damage: damage,
+                status,
+            });
+        }
+        AbilitySpec::Shooting {
+            bullets,
+            interval,
+            range,
+            bullet_speed,
+            bullet_damage,
+            status,
+        } => {
+            entity_commands.insert(ShootingAbility {
+                shoot_timer: Timer::from_seconds(interval, TimerMode::Repeating),
+                bullets_per_shot: bullets,
+                range,
+                in_range: false,
+                bullet_speed,
+                bullet_damage,
+                status,
+            });
+        }
+        AbilitySpec::Charge {
+            distance,
+            speed,
+            prepare_time,
+            cooldown,
+        } => {
+            entity_commands.insert(ChargeAbility {
+                state: ChargeState::Approaching,
+                charge_timer: Timer::from_seconds(prepare_time, TimerMode::Once),
+                charge_distance: distance,
+                charge_speed: speed,
+                target_position: None,
+                cooldown_duration: cooldown,
+            });
+        }
+        AbilitySpec::Splitting { splits } => {
+            entity_commands.insert(SplitAbility {
+                splits_remaining: splits,
+                num_splits: splits,
+            });
+        }
+        AbilitySpec::Summoning {
+            min_minions,
+            max_minions,
+            interval,
+            minions,
+        } => {
+            entity_commands.insert(SummoningAbility {
+                timer: Timer::from_seconds(interval, TimerMode::Repeating),
+                min_minions,
+                max_minions,
+                minions,
+            });
+        }
+        AbilitySpec::Ranged {
+            preferred_distance,
+            tolerance,
+        } => {
+            entity_commands.insert(RangedBehavior {
+                preferred_distance,
+                tolerance,
+            });
+        }
+        AbilitySpec::Gurgle => {
+            entity_commands.insert(GurgleEnemy);
+        }
+        AbilitySpec::Spiral {
+            arms,
+            interval,
+            turn,
+            bullet_speed,
+            bullet_damage,
+        } => {
+            entity_commands.insert(SpiralAbility {
+                timer: Timer::from_seconds(interval, TimerMode::Repeating),
+                arms,
+                angle: 0.0,
+                turn,
+                bullet_speed,
+                bullet_damage,
+            });
+        }
+        AbilitySpec::Burrow {
+            interval,
+            duration,
+            speed,
+            radius,
+            damage,
+        } => {
+            entity_commands.insert(BurrowAbility {
+                timer: Timer::from_seconds(interval, TimerMode::Once),
+                interval,
+                duration,
+                speed,
+                eruption_radius: radius,
+                eruption_damage: damage,
+            });
+        }
+        AbilitySpec::Hazard {
+            interval,
+            count,
+            radius,
+            delay,
+            damage,
+        } => {
+            entity_commands.insert(HazardAbility {
+                timer: Timer::from_seconds(interval, TimerMode::Repeating),
+                count,
+                radius,
+                delay,
+                damage,
+            });
+        }
     }
 }
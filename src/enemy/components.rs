@@ -3,13 +3,16 @@ use bevy::prelude::*;
 use crate::{
     animation::AnimationTimer,
     configs::SPRITE_SCALE_FACTOR,
+    enemy::definitions::{AbilitySpec, BossPhase, EnrageSpec},
     gun::{BulletStats, HasLifespan},
+    interpolation::Interpolated,
     loot::LootPool,
+    status::StatusSpec,
     utils::InGameEntity,
 };
 
 #[derive(Component)]
-#[require(Sprite, Transform, AnimationTimer(||AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating))), InGameEntity, LootPool, Collider)]
+#[require(Sprite, Transform, AnimationTimer(||AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating))), InGameEntity, LootPool, Collider, Interpolated)]
 pub struct Enemy {
     pub health: u32,
     pub speed: u32,
@@ -29,12 +32,15 @@ pub struct SplitAbility {
 }
 
 #[derive(Component)]
-#[require(InGameEntity,HasLifespan(||HasLifespan::new(std::time::Duration::from_secs(2))),BulletStats(||            BulletStats {
+#[require(InGameEntity,Interpolated,HasLifespan(||HasLifespan::new(std::time::Duration::from_secs(2))),BulletStats(||            BulletStats {
                 speed: 200,
                 damage: 10,
                 lifespan: 2.0,
             }))]
-pub struct EnemyBullet;
+pub struct EnemyBullet {
+    pub source: Name,
+    pub status: Option<StatusSpec>,
+}
 
 #[derive(Component)]
 pub struct TrailAbility {
@@ -43,6 +49,7 @@ pub struct TrailAbility {
     pub trail_radius: f32,
     pub trail_duration: f32,
     pub last_position: Option<Vec3>,
+    pub status: Option<StatusSpec>,
 }
 
 #[derive(Component)]
@@ -57,6 +64,7 @@ pub struct TrailSegment {
 pub struct ExplosionAbility {
     pub explosion_radius: f32,
     pub explosion_damage: u32,
+    pub status: Option<StatusSpec>,
 }
 
 #[derive(Component)]
@@ -67,6 +75,7 @@ pub struct ShootingAbility {
     pub in_range: bool,
     pub bullet_speed: u32,
     pub bullet_damage: u32,
+    pub status: Option<StatusSpec>,
 }
 
 #[derive(Component, Debug)]
@@ -87,9 +96,172 @@ pub struct ChargeAbility {
     pub cooldown_duration: f32,
 }
 
+#[derive(Component)]
+pub struct SpiralAbility {
+    pub timer: Timer,
+    pub arms: usize,
+    pub angle: f32,
+    pub turn: f32,
+    pub bullet_speed: u32,
+    pub bullet_damage: u32,
+}
+
+#[derive(Component)]
+pub struct BurrowAbility {
+    pub timer: Timer,
+    pub interval: f32,
+    pub duration: f32,
+    pub speed: f32,
+    pub eruption_radius: f32,
+    pub eruption_damage: u32,
+}
+
+#[derive(Component)]
+pub struct Burrowed;
+
+#[derive(Component)]
+pub struct HazardAbility {
+    pub timer: Timer,
+    pub count: u32,
+    pub radius: f32,
+    pub delay: f32,
+    pub damage: u32,
+}
+
 #[derive(Component)]
 pub struct OriginalEnemyColor(pub Color);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EliteAffix {
+    Shielded,
+    Hasted,
+    Vampiric,
+    Explosive,
+    Summoner,
+    Reflective,
+    Regenerating,
+}
+
+impl EliteAffix {
+    pub const ALL: [EliteAffix; 7] = [
+        EliteAffix::Shielded,
+        EliteAffix::Hasted,
+        EliteAffix::Vampiric,
+        EliteAffix::Explosive,
+        EliteAffix::Summoner,
+        EliteAffix::Reflective,
+        EliteAffix::Regenerating,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EliteAffix::Shielded => "Shielded",
+            EliteAffix::Hasted => "Hasted",
+            EliteAffix::Vampiric => "Vampiric",
+            EliteAffix::Explosive => "Explosive",
+            EliteAffix::Summoner => "Summoner",
+            EliteAffix::Reflective => "Reflective",
+            EliteAffix::Regenerating => "Regenerating",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            EliteAffix::Shielded => Color::srgb(0.55, 0.8, 1.0),
+            EliteAffix::Hasted => Color::srgb(1.0, 0.95, 0.45),
+            EliteAffix::Vampiric => Color::srgb(0.8, 0.25, 0.35),
+            EliteAffix::Explosive => Color::srgb(1.0, 0.6, 0.25),
+            EliteAffix::Summoner => Color::srgb(0.7, 0.5, 1.0),
+            EliteAffix::Reflective => Color::srgb(0.85, 0.85, 0.95),
+            EliteAffix::Regenerating => Color::srgb(0.5, 1.0, 0.55),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+}
+
+impl Elite {
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+}
+
+#[derive(Component)]
+pub struct EliteNamePlate;
+
+#[derive(Component)]
+pub struct Boss;
+
+#[derive(Component)]
+pub struct BossState {
+    pub abilities: Vec<AbilitySpec>,
+    pub phases: Vec<BossPhase>,
+    pub phase: usize,
+    pub enrage: Option<EnrageSpec>,
+    pub enrage_timer: Timer,
+    pub enraged: bool,
+    pub charge_telegraphed: bool,
+    pub barrage_telegraphed: bool,
+    pub eruption_telegraphed: bool,
+}
+
+impl BossState {
+    pub fn new(
+        abilities: Vec<AbilitySpec>,
+        mut phases: Vec<BossPhase>,
+        enrage: Option<EnrageSpec>,
+    ) -> Self {
+        phases.sort_by(|a, b| b.threshold.total_cmp(&a.threshold));
+        Self {
+            abilities,
+            phases,
+            phase: 0,
+            enrage,
+            enrage_timer: Timer::from_seconds(
+                enrage.map_or(0.0, |enrage| enrage.after),
+                TimerMode::Once,
+            ),
+            enraged: false,
+            charge_telegraphed: false,
+            barrage_telegraphed: false,
+            eruption_telegraphed: false,
+        }
+    }
+
+    pub fn current_abilities(&self) -> &[AbilitySpec] {
+        self.phase
+            .checked_sub(1)
+            .and_then(|index| self.phases.get(index))
+            .map_or(&self.abilities, |phase| &phase.abilities)
+    }
+
+    pub fn phase_name(&self) -> Option<&str> {
+        self.phase
+            .checked_sub(1)
+            .and_then(|index| self.phases.get(index))
+            .map(|phase| phase.name.as_str())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TelegraphKind {
+    Charge,
+    Barrage,
+    Eruption,
+    Hazard { radius: f32, damage: u32 },
+}
+
+#[derive(Component)]
+#[require(InGameEntity)]
+pub struct BossTelegraph {
+    pub owner: Entity,
+    pub kind: TelegraphKind,
+    pub timer: Timer,
+}
+
 #[derive(Component)]
 pub struct SpawnIndicator {
     pub timer: Timer,
@@ -125,6 +297,8 @@ impl Default for DeathEffect {
 pub struct Trail {
     pub damage: u32,
     pub radius: f32,
+    pub source: Name,
+    pub status: Option<StatusSpec>,
 }
 
 #[derive(Component)]
@@ -133,6 +307,7 @@ pub struct Explosion {
     pub radius: f32,
     pub damage: u32,
     pub timer: Timer,
+    pub source: Name,
 }
 
 #[derive(Component)]
@@ -172,6 +347,7 @@ pub struct SummoningAbility {
     pub timer: Timer,
     pub min_minions: u32,
     pub max_minions: u32,
+    pub minions: Vec<(String, f32)>,
 }
 
 #[derive(Event)]
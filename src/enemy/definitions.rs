@@ -0,0 +1,237 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use super::builder::EnemyBuilder;
+use crate::{loot::LootTier, status::StatusSpec};
+
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct EnemyDefinitions {
+    pub enemies: Vec<EnemyDefinition>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyDefinition {
+    pub name: String,
+    pub stats: EnemyStats,
+    pub sprite: EnemySprite,
+    #[serde(default)]
+    pub loot: Option<LootTier>,
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default)]
+    pub abilities: Vec<AbilitySpec>,
+    #[serde(default)]
+    pub phases: Vec<BossPhase>,
+    #[serde(default)]
+    pub enrage: Option<EnrageSpec>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct EnemyStats {
+    pub health: u32,
+    pub speed: u32,
+    pub damage: u32,
+    pub xp: u32,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct EnemySprite {
+    pub index: usize,
+    pub size: (u32, u32),
+    #[serde(default)]
+    pub tint: Option<(u8, u8, u8)>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossPhase {
+    pub name: String,
+    pub threshold: f32,
+    #[serde(default = "default_multiplier")]
+    pub speed: f32,
+    pub abilities: Vec<AbilitySpec>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct EnrageSpec {
+    pub after: f32,
+    pub speed: f32,
+    pub damage: f32,
+    pub fire_rate: f32,
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum AbilitySpec {
+    Trail {
+        damage: u32,
+        interval: f32,
+        radius: f32,
+        duration: f32,
+        #[serde(default)]
+        status: Option<StatusSpec>,
+    },
+    Explosion {
+        radius: f32,
+        damage: u32,
+        #[serde(default)]
+        status: Option<StatusSpec>,
+    },
+    Shooting {
+        bullets: usize,
+        interval: f32,
+        range: f32,
+        bullet_speed: u32,
+        bullet_damage: u32,
+        #[serde(default)]
+        status: Option<StatusSpec>,
+    },
+    Charge {
+        distance: u32,
+        speed: u32,
+        prepare_time: f32,
+        cooldown: f32,
+    },
+    Splitting {
+        splits: u8,
+    },
+    Summoning {
+        min_minions: u32,
+        max_minions: u32,
+        interval: f32,
+        minions: Vec<(String, f32)>,
+    },
+    Ranged {
+        preferred_distance: f32,
+        tolerance: f32,
+    },
+    Gurgle,
+    Spiral {
+        arms: usize,
+        interval: f32,
+        turn: f32,
+        bullet_speed: u32,
+        bullet_damage: u32,
+    },
+    Burrow {
+        interval: f32,
+        duration: f32,
+        speed: f32,
+        radius: f32,
+        damage: u32,
+    },
+    Hazard {
+        interval: f32,
+        count: u32,
+        radius: f32,
+        delay: f32,
+        damage: u32,
+    },
+}
+
+impl AbilitySpec {
+    pub fn hastened(mut self, factor: f32) -> Self {
+        match &mut self {
+            AbilitySpec::Trail { interval, .. }
+            | AbilitySpec::Shooting { interval, .. }
+            | AbilitySpec::Summoning { interval, .. }
+            | AbilitySpec::Spiral { interval, .. }
+            | AbilitySpec::Burrow { interval, .. }
+            | AbilitySpec::Hazard { interval, .. } => *interval /= factor,
+            AbilitySpec::Charge { cooldown, .. } => *cooldown /= factor,
+            _ => {}
+        }
+        self
+    }
+}
+
+#[derive(Resource)]
+pub struct EnemyRegistry {
+    definitions: HashMap<String, EnemyDefinition>,
+}
+
+#[derive(Resource)]
+pub struct EnemyDefinitionsHandle(pub Handle<EnemyDefinitions>);
+
+impl Default for EnemyRegistry {
+    fn default() -> Self {
+        let definitions =
+            ron::from_str::<EnemyDefinitions>(include_str!("../../assets/enemies.ron"))
+                .expect("built-in enemy definitions should parse");
+        let mut registry = Self {
+            definitions: HashMap::default(),
+        };
+        registry.replace(&definitions);
+        registry
+    }
+}
+
+impl EnemyRegistry {
+    pub fn get(&self, name: &str) -> Option<&EnemyDefinition> {
+        self.definitions.get(name)
+    }
+
+    pub fn builder(&self, name: &str) -> Option<EnemyBuilder> {
+        match self.get(name) {
+            Some(definition) => Some(EnemyBuilder::from_definition(definition)),
+            None => {
+                warn!("Unknown enemy definition {}", name);
+                None
+            }
+        }
+    }
+
+    pub fn replace(&mut self, definitions: &EnemyDefinitions) {
+        self.definitions = definitions
+            .enemies
+            .iter()
+            .map(|definition| (definition.name.clone(), definition.clone()))
+            .collect();
+
+        for definition in &definitions.enemies {
+            let phase_abilities = definition
+                .phases
+                .iter()
+                .flat_map(|phase| phase.abilities.iter());
+            for ability in definition.abilities.iter().chain(phase_abilities) {
+                if let AbilitySpec::Summoning { minions, .. } = ability {
+                    for (minion, _) in minions {
+                        if !self.definitions.contains_key(minion) {
+                            warn!("{} summons unknown enemy {}", definition.name, minion);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_enemy_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyDefinitionsHandle(
+        asset_server.load(crate::configs::ENEMY_DEFINITIONS_PATH),
+    ));
+}
+
+pub fn sync_enemy_registry(
+    mut events: EventReader<AssetEvent<EnemyDefinitions>>,
+    handle: Res<EnemyDefinitionsHandle>,
+    definitions: Res<Assets<EnemyDefinitions>>,
+    mut registry: ResMut<EnemyRegistry>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != handle.0.id() {
+            continue;
+        }
+        if let Some(definitions) = definitions.get(*id) {
+            registry.replace(definitions);
+            info!("Loaded {} enemy definitions", definitions.enemies.len());
+        }
+    }
+}
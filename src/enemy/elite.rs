@@ -0,0 +1,137 @@
+use super::{
+    components::*,
+    systems::{spawn_enemy_bullet, spawn_explosion},
+};
+use crate::{
+    configs::*,
+    damage::{DamageAppliedEvent, DamageKind},
+    player::Player,
+    resources::{GlobalTextureAtlas, UiFont},
+    rng::GameRng,
+    stats::{StatKind, Stats},
+    ui::systems::in_game_ui::spawn_floating_text,
+};
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+pub fn roll_elite_affixes(chance: f32, rng: &mut impl Rng) -> Vec<EliteAffix> {
+    if rng.gen::<f32>() >= chance {
+        return Vec::new();
+    }
+
+    let mut candidates = EliteAffix::ALL.to_vec();
+    candidates.shuffle(rng);
+    let mut count = 1;
+    while count < ELITE_MAX_AFFIXES && rng.gen::<f32>() < ELITE_EXTRA_AFFIX_CHANCE {
+        count += 1;
+    }
+    candidates.truncate(count);
+    candidates
+}
+
+pub fn attach_elite_name_plates(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    elite_query: Query<(Entity, &Elite), Added<Elite>>,
+) {
+    for (entity, elite) in elite_query.iter() {
+        let label = elite
+            .affixes
+            .iter()
+            .map(|affix| affix.name())
+            .collect::<Vec<_>>()
+            .join(" ");
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Elite Name Plate"),
+                Text2d::new(label),
+                TextFont {
+                    font: font.0.clone(),
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(elite.affixes[0].color()),
+                Transform::from_xyz(0.0, 14.0, LAYER3)
+                    .with_scale(Vec3::splat(1.0 / SPRITE_SCALE_FACTOR)),
+                EliteNamePlate,
+            ));
+        });
+    }
+}
+
+pub fn handle_elite_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageAppliedEvent>,
+    mut elite_query: Query<(&mut Enemy, &Stats, &Elite, &Transform, &Name)>,
+    player_query: Query<&Transform, With<Player>>,
+    handle: Res<GlobalTextureAtlas>,
+    font: Res<UiFont>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in events.read() {
+        if let Ok((_, _, elite, transform, name)) = elite_query.get(event.target) {
+            if event.fatal && elite.has(EliteAffix::Explosive) {
+                spawn_explosion(
+                    &mut commands,
+                    transform.translation,
+                    ELITE_EXPLOSION_RADIUS,
+                    ELITE_EXPLOSION_DAMAGE,
+                    name.clone(),
+                );
+            }
+
+            let shooter = event
+                .source
+                .entity
+                .and_then(|source| player_query.get(source).ok());
+            if let Some(shooter_transform) = shooter.filter(|_| {
+                !event.fatal
+                    && event.kind == DamageKind::Bullet
+                    && elite.has(EliteAffix::Reflective)
+                    && rng.combat.gen::<f32>() < ELITE_REFLECT_CHANCE
+            }) {
+                let direction = (shooter_transform.translation - transform.translation)
+                    .truncate()
+                    .normalize_or_zero()
+                    .extend(0.0);
+                spawn_enemy_bullet(
+                    &mut commands,
+                    transform.translation,
+                    direction,
+                    &handle,
+                    false,
+                    ELITE_REFLECT_BULLET_SPEED,
+                    ELITE_REFLECT_BULLET_DAMAGE,
+                    None,
+                    name,
+                );
+            }
+        }
+
+        let Some(source) = event.source.entity else {
+            continue;
+        };
+        if !player_query.contains(event.target) {
+            continue;
+        }
+        let Ok((mut enemy, stats, elite, transform, _)) = elite_query.get_mut(source) else {
+            continue;
+        };
+        if enemy.health == 0 || !elite.has(EliteAffix::Vampiric) {
+            continue;
+        }
+        let max_health = stats.value_u32(StatKind::MaxHealth);
+        let heal = ((max_health as f32 * ELITE_VAMPIRIC_HEAL_FRACTION) as u32)
+            .min(max_health.saturating_sub(enemy.health));
+        if heal > 0 {
+            enemy.health += heal;
+            spawn_floating_text(
+                &mut commands,
+                &font.0,
+                transform.translation,
+                format!("+{}", heal),
+                Some(EliteAffix::Vampiric.color()),
+            );
+        }
+    }
+}
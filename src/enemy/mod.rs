@@ -1,10 +1,16 @@
+pub mod boss;
 pub mod builder;
 pub mod components;
+pub mod definitions;
+pub mod elite;
 pub mod plugin;
-pub mod presets;
 pub mod systems;
+pub mod waves;
 
+pub use boss::*;
 pub use builder::*;
 pub use components::*;
-pub use presets::*;
+pub use definitions::*;
+pub use elite::*;
 pub use systems::*;
+pub use waves::*;
@@ -1,53 +1,111 @@
 use crate::{
+    damage::resolve_damage,
     enemy::{
+        announce_bosses, attach_elite_name_plates, handle_burrow_abilities,
         handle_charge_abilities, handle_charge_enemy_flash, handle_death_effect,
-        handle_enemy_bullet_player_collision, handle_enemy_death, handle_enemy_splitting,
-        handle_exploding_bullets, handle_explosion_player_collision, handle_explosions,
-        handle_hit_flash, handle_ranged_movement, handle_shooting_abilities,
-        handle_summoning_abilities, handle_trail_abilities, spawn_enemies, update_enemy_bullets,
-        update_enemy_movement, update_spawn_indicators, BomberExplosionEvent,
+        handle_elite_damage, handle_enemy_bullet_player_collision, handle_enemy_death,
+        handle_enemy_splitting, handle_exploding_bullets, handle_explosion_player_collision,
+        handle_explosions, handle_hazard_abilities, handle_hit_flash, handle_ranged_movement,
+        handle_shooting_abilities, handle_spiral_abilities, handle_summoning_abilities,
+        handle_trail_abilities, spawn_enemies, sync_enemy_registry, sync_wave_scripts,
+        telegraph_boss_attacks, update_boss_enrage, update_boss_phases, update_boss_telegraphs,
+        update_enemy_bullets, update_enemy_movement, update_spawn_indicators, BomberExplosionEvent,
+        EnemyDefinitions, EnemyDefinitionsHandle, EnemyRegistry, WaveScript, WaveScriptHandles,
+        WaveScripts,
     },
-    game_state::GameState,
+    game_state::{GameState, GameplaySet},
+    utils::RonAssetLoader,
 };
 use bevy::{
-    app::{App, Plugin, Update},
-    prelude::{in_state, IntoSystemConfigs},
+    app::{App, FixedUpdate, Plugin, Update},
+    asset::{AssetApp, AssetPlugin},
+    prelude::{in_state, resource_exists, IntoSystemConfigs, OnEnter},
 };
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<BomberExplosionEvent>().add_systems(
-            Update,
-            (
-                spawn_enemies,
-                update_spawn_indicators,
-                (update_enemy_movement, handle_ranged_movement).chain(),
+        app.add_event::<BomberExplosionEvent>()
+            .init_resource::<EnemyRegistry>()
+            .init_resource::<WaveScripts>()
+            .add_systems(
+                FixedUpdate,
                 (
-                    handle_trail_abilities,
-                    handle_shooting_abilities,
-                    handle_charge_abilities,
-                    handle_summoning_abilities,
+                    spawn_enemies,
+                    update_spawn_indicators,
+                    (update_enemy_movement, handle_ranged_movement).chain(),
+                    (
+                        handle_trail_abilities,
+                        handle_shooting_abilities,
+                        handle_charge_abilities,
+                        handle_summoning_abilities,
+                        handle_spiral_abilities,
+                        handle_burrow_abilities,
+                        handle_hazard_abilities,
+                    )
+                        .chain(),
+                    (
+                        update_enemy_bullets,
+                        handle_enemy_bullet_player_collision,
+                        handle_exploding_bullets,
+                    )
+                        .chain(),
+                    (handle_explosions, handle_explosion_player_collision).chain(),
+                    (handle_enemy_death, handle_enemy_splitting).chain(),
+                    (
+                        announce_bosses,
+                        update_boss_phases,
+                        update_boss_enrage,
+                        telegraph_boss_attacks,
+                        update_boss_telegraphs,
+                    )
+                        .chain(),
                 )
-                    .after(update_enemy_movement),
+                    .chain()
+                    .in_set(GameplaySet::Enemy)
+                    .run_if(in_state(GameState::Combat)),
+            )
+            .add_systems(
+                FixedUpdate,
+                handle_elite_damage
+                    .after(resolve_damage)
+                    .in_set(GameplaySet::Damage)
+                    .run_if(in_state(GameState::Combat)),
+            )
+            .add_systems(
+                Update,
                 (
-                    update_enemy_bullets,
-                    handle_enemy_bullet_player_collision,
-                    handle_exploding_bullets,
+                    attach_elite_name_plates,
+                    handle_hit_flash,
+                    handle_charge_enemy_flash,
+                    handle_death_effect,
                 )
-                    .after(handle_shooting_abilities),
-                (handle_explosions, handle_explosion_player_collision)
-                    .after(handle_exploding_bullets),
+                    .chain()
+                    .run_if(in_state(GameState::Combat)),
+            );
+
+        if app.is_plugin_added::<AssetPlugin>() {
+            app.init_asset::<EnemyDefinitions>()
+                .init_asset::<WaveScript>()
+                .init_asset_loader::<RonAssetLoader<EnemyDefinitions>>()
+                .init_asset_loader::<RonAssetLoader<WaveScript>>()
+                .add_systems(
+                    Update,
+                    (
+                        sync_enemy_registry.run_if(resource_exists::<EnemyDefinitionsHandle>),
+                        sync_wave_scripts.run_if(resource_exists::<WaveScriptHandles>),
+                    ),
+                );
+
+            #[cfg(not(target_arch = "wasm32"))]
+            app.add_systems(
+                OnEnter(GameState::Loading),
                 (
-                    handle_charge_enemy_flash.after(handle_hit_flash),
-                    handle_hit_flash.before(handle_charge_enemy_flash),
-                    handle_death_effect,
+                    crate::enemy::load_enemy_definitions,
+                    crate::enemy::load_wave_scripts,
                 ),
-                (handle_enemy_death, handle_enemy_splitting)
-                    .after(handle_enemy_bullet_player_collision),
-            )
-                .run_if(in_state(GameState::Combat)),
-        );
+            );
+        }
     }
 }
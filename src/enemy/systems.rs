@@ -1,24 +1,31 @@
-use super::{components::*, presets::*};
+use super::{
+    boss::spawn_telegraph, builder::EnemyBuilder, components::*, definitions::*,
+    elite::roll_elite_affixes, waves::WaveScripts,
+};
 use crate::{
     audio::AudioEvent,
     configs::*,
-    enemy::EnemyBuilder,
+    coop::nearest_player,
+    damage::{DamageEvent, DamageKind, DamageSource},
     game_state::GameState,
     gun::{BulletDirection, BulletStats, HasLifespan},
     loot::LootPool,
-    player::{
-        Gold, Health, InvincibilityEffect, Player, PlayerDamagedEvent, PlayerLevelingUpEvent,
-    },
+    player::{Downed, Gold, Health, InvincibilityEffect, Player, PlayerLevelingUpEvent},
     resources::{GameMode, GlobalTextureAtlas, Level, Wave},
-    utils::{apply_movement, clamp_position, get_random_position_around, InGameEntity},
+    rng::GameRng,
+    stats::{StatKind, StatModifier, Stats},
+    status::{ApplyStatusEvent, StatusEffects, StatusSpec},
+    utils::{
+        apply_movement, clamp_position, get_random_position_around, pick_weighted, InGameEntity,
+    },
 };
 use bevy::prelude::*;
 use bevy::utils::Duration;
-use rand::{random, Rng};
+use rand::Rng;
 
 pub fn update_enemy_movement(
     time: Res<Time>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
     mut enemy_query: Query<
         (
             Entity,
@@ -30,10 +37,9 @@ pub fn update_enemy_movement(
         ),
         (Without<Player>, Without<ChargeAbility>),
     >,
+    mut rng: ResMut<GameRng>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        let player_pos = player_transform.translation;
-
+    if !player_query.is_empty() {
         let enemy_positions: Vec<(Entity, Vec3)> = enemy_query
             .iter()
             .map(|(entity, _, transform, _, _, _)| (entity, transform.translation))
@@ -42,6 +48,9 @@ pub fn update_enemy_movement(
         for (entity, enemy, mut transform, mut state, ranged_behavior, trail_ability) in
             enemy_query.iter_mut()
         {
+            let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) else {
+                continue;
+            };
             let mut movement = Vec2::ZERO;
 
             match &mut *state {
@@ -73,18 +82,16 @@ pub fn update_enemy_movement(
                                     new_direction.y += 1.0;
                                 }
 
-                                let random_angle = rand::thread_rng().gen_range(-0.5..0.5);
+                                let random_angle = rng.ai.gen_range(-0.5..0.5);
                                 let rotation = Mat2::from_angle(random_angle);
                                 *direction = (rotation * new_direction.normalize()).normalize();
                             } else {
-                                let angle_change = rand::thread_rng().gen_range(-0.8..0.8);
+                                let angle_change = rng.ai.gen_range(-0.8..0.8);
                                 let rotation = Mat2::from_angle(angle_change);
                                 *direction = (rotation * *direction).normalize();
                             }
 
-                            timer.set_duration(Duration::from_secs_f32(
-                                rand::thread_rng().gen_range(1.8..2.5),
-                            ));
+                            timer.set_duration(Duration::from_secs_f32(rng.ai.gen_range(1.8..2.5)));
                             timer.reset();
                         }
 
@@ -110,7 +117,7 @@ pub fn update_enemy_movement(
                                 movement = -direction.truncate() * enemy.speed as f32;
                             }
                         } else if distance_difference.abs() > ranged_behavior.tolerance * 0.5 {
-                            movement = direction.truncate() * (distance_difference * 0.1);
+                            movement = direction.truncate() * (distance_difference * 6.0);
                         }
                     } else {
                         *state = EnemyState::Pursuing;
@@ -122,11 +129,20 @@ pub fn update_enemy_movement(
                 }
             }
 
-            let collision_resolution =
-                calculate_collision_resolution(entity, transform.translation, &enemy_positions);
+            let collision_resolution = calculate_collision_resolution(
+                entity,
+                transform.translation,
+                &enemy_positions,
+                &mut rng.ai,
+            );
             let final_movement = movement + collision_resolution;
 
-            apply_movement(&mut transform.translation, final_movement, LAYER2);
+            apply_movement(
+                &mut transform.translation,
+                final_movement,
+                time.delta_secs(),
+                LAYER2,
+            );
         }
     }
 }
@@ -135,7 +151,9 @@ fn calculate_collision_resolution(
     current_entity: Entity,
     current_pos: Vec3,
     enemy_positions: &Vec<(Entity, Vec3)>,
+    rng: &mut impl Rng,
 ) -> Vec2 {
+    const SEPARATION_RATE: f32 = 60.0;
     let collision_radius = 30.0;
     let mut collision_resolution = Vec2::ZERO;
 
@@ -146,12 +164,14 @@ fn calculate_collision_resolution(
 
             if distance < collision_radius {
                 if distance > 0.0 {
-                    collision_resolution +=
-                        diff.truncate().normalize() * (collision_radius - distance);
+                    collision_resolution += diff.truncate().normalize()
+                        * (collision_radius - distance)
+                        * SEPARATION_RATE;
                 } else {
-                    let random_angle = rand::random::<f32>() * std::f32::consts::TAU;
-                    collision_resolution +=
-                        Vec2::new(random_angle.cos(), random_angle.sin()) * collision_radius;
+                    let random_angle = rng.gen::<f32>() * std::f32::consts::TAU;
+                    collision_resolution += Vec2::new(random_angle.cos(), random_angle.sin())
+                        * collision_radius
+                        * SEPARATION_RATE;
                 }
             }
         }
@@ -170,19 +190,25 @@ pub fn spawn_enemies(
     mut wave: ResMut<Wave>,
     game_mode: Res<GameMode>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut rng: ResMut<GameRng>,
+    registry: Res<EnemyRegistry>,
+    wave_scripts: Res<WaveScripts>,
 ) {
     if !enemy_query.is_empty() {
         return;
     }
 
-    if player_query.is_empty() {
+    let living_positions: Vec<Vec2> = player_query
+        .iter()
+        .filter(|(_, health)| health.0 > 0)
+        .map(|(transform, _)| transform.translation.truncate())
+        .collect();
+    if living_positions.is_empty() {
         return;
     }
 
-    let (player_transform, health) = player_query.single();
-    if health.0 == 0 {
-        return;
-    }
+    let script = wave_scripts.for_mode(*game_mode);
+    let elite_chance = script.elite_chance_at(wave.number);
 
     for (entity, mut indicator, mut sprite) in indicator_query.iter_mut() {
         indicator.timer.tick(time.delta());
@@ -193,16 +219,15 @@ pub fn spawn_enemies(
         if indicator.timer.finished() {
             commands.entity(entity).despawn();
 
-            let mut difficulty_multiplier = calculate_difficulty_multiplier(wave.number);
-
-            if *game_mode == GameMode::Forever && is_boss_wave(wave.number) {
-                difficulty_multiplier *= 1.5;
-            }
-
-            let enemy_base = if is_boss_wave(wave.number) {
-                create_midgame_boss_enemy()
-            } else {
-                select_enemy_type(wave.number)()
+            let Some(rule) = script.rule(wave.number) else {
+                continue;
+            };
+            let difficulty_multiplier = rule.difficulty.at(wave.number);
+            let enemy_weights = script.enemy_weights(rule, wave.number);
+            let Some(enemy_base) = pick_weighted(&enemy_weights, &mut rng.enemy)
+                .and_then(|enemy_name| registry.builder(enemy_name))
+            else {
+                continue;
             };
 
             let health = (enemy_base.health as f32 * difficulty_multiplier) as u32;
@@ -210,7 +235,10 @@ pub fn spawn_enemies(
             let damage = (enemy_base.damage as f32 * difficulty_multiplier) as u32;
             let xp = (enemy_base.xp as f32 * difficulty_multiplier) as u32;
 
-            let enemy = enemy_base.with_stats(health, speed, damage, xp);
+            let mut enemy = enemy_base.with_stats(health, speed, damage, xp);
+            if !enemy.boss && elite_chance > 0.0 {
+                enemy = enemy.with_affixes(roll_elite_affixes(elite_chance, &mut rng.enemy));
+            }
 
             enemy.spawn(&mut commands, indicator.spawn_position, &handle);
         }
@@ -219,17 +247,22 @@ pub fn spawn_enemies(
     if indicator_query.is_empty() {
         wave.number += 1;
 
-        if *game_mode == GameMode::Normal && wave.number > 10 {
+        if script.is_finished(wave.number) {
             next_state.set(GameState::Win);
             return;
         }
+        let Some(rule) = script.rule(wave.number) else {
+            return;
+        };
 
-        let player_pos = player_transform.translation.truncate();
-        let num_enemies = calculate_enemies_for_wave(wave.number);
+        let player_pos = living_positions.iter().sum::<Vec2>() / living_positions.len() as f32;
+        let num_enemies = rule.enemy_count(wave.number, &mut rng.enemy);
 
-        for _ in 0..num_enemies {
-            let (x, y) = get_random_position_around(player_pos, 250.0..1000.0);
-            let mut position = Vec3::new(x, y, LAYER2);
+        for spawn_position in rule
+            .pattern
+            .positions(player_pos, num_enemies, &mut rng.enemy)
+        {
+            let mut position = spawn_position.extend(LAYER2);
             clamp_position(&mut position);
 
             commands.spawn((
@@ -254,111 +287,6 @@ pub fn spawn_enemies(
     }
 }
 
-fn calculate_enemy_distribution(wave: u32) -> Vec<(fn() -> EnemyBuilder, f32)> {
-    let mut distributions = Vec::new();
-
-    let basic_weight = 1.0 / (1.0 + (wave as f32 * 0.1));
-    distributions.push((create_basic_enemy as fn() -> EnemyBuilder, basic_weight));
-
-    if wave >= 2 {
-        let charging_weight = (wave as f32 * 0.15).min(0.8);
-        distributions.push((
-            create_charging_enemy as fn() -> EnemyBuilder,
-            charging_weight,
-        ));
-    }
-
-    if wave >= 3 {
-        let splitter_weight = (wave as f32 * 0.03).min(0.15);
-        distributions.push((
-            create_splitting_enemy as fn() -> EnemyBuilder,
-            splitter_weight,
-        ));
-    }
-
-    if wave >= 4 {
-        let trail_weight = (wave as f32 * 0.1).min(0.6);
-        distributions.push((create_trail_enemy as fn() -> EnemyBuilder, trail_weight));
-    }
-
-    if wave >= 5 {
-        let shooter_weight = (wave as f32 * 0.08).min(0.5);
-        distributions.push((create_shooter_enemy as fn() -> EnemyBuilder, shooter_weight));
-    }
-
-    if wave >= 6 {
-        let bomber_weight = (wave as f32 * 0.07).min(0.4);
-        distributions.push((create_bomber_enemy as fn() -> EnemyBuilder, bomber_weight));
-    }
-
-    if wave >= 7 {
-        let gurgle_weight = (wave as f32 * 0.06).min(0.3);
-        distributions.push((create_gurgle_enemy as fn() -> EnemyBuilder, gurgle_weight));
-    }
-
-    distributions
-}
-
-fn select_enemy_type(wave: u32) -> fn() -> EnemyBuilder {
-    let distributions = calculate_enemy_distribution(wave);
-    let total_weight: f32 = distributions.iter().map(|(_, weight)| weight).sum();
-    let mut rng = rand::thread_rng();
-    let random_value = rng.gen::<f32>() * total_weight;
-
-    let mut cumulative_weight = 0.0;
-    for (enemy_type, weight) in distributions {
-        cumulative_weight += weight;
-        if random_value <= cumulative_weight {
-            return enemy_type;
-        }
-    }
-
-    create_basic_enemy
-}
-
-fn calculate_enemies_for_wave(wave_number: u32) -> u32 {
-    if is_boss_wave(wave_number) {
-        let base_boss_count = 3;
-        let additional_bosses = if wave_number <= 10 {
-            0
-        } else {
-            ((wave_number - 10) / 10) as u32
-        };
-
-        base_boss_count + additional_bosses
-    } else {
-        let base = match wave_number {
-            1..=3 => 15 + wave_number * 3,
-            4..=6 => 25 + wave_number * 4,
-            7..=9 => 45 + wave_number * 5,
-            _ => {
-                let scaling = if wave_number <= 15 {
-                    90 + (wave_number - 9) * 8
-                } else {
-                    140 + (wave_number - 15) * 12
-                };
-                scaling
-            }
-        };
-
-        base + (random::<u32>() % 15)
-    }
-}
-
-pub fn calculate_difficulty_multiplier(wave_number: u32) -> f32 {
-    if wave_number <= 30 {
-        1.0 + (wave_number as f32 / 10.0) * 0.1
-    } else {
-        let base_multiplier = 1.0 + (30.0 / 10.0 * 0.1);
-        let additional_multiplier = ((wave_number - 30) as f32 / 5.0) * 0.1;
-        base_multiplier + additional_multiplier
-    }
-}
-
-fn is_boss_wave(wave_number: u32) -> bool {
-    wave_number % 10 == 0 && wave_number >= 10
-}
-
 pub fn update_spawn_indicators(
     time: Res<Time>,
     mut indicator_query: Query<(&mut SpawnIndicator, &mut Sprite)>,
@@ -374,9 +302,9 @@ pub fn update_spawn_indicators(
 pub fn handle_trail_abilities(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(&Transform, &mut TrailAbility), With<Enemy>>,
+    mut query: Query<(&Transform, &mut TrailAbility, &Name), With<Enemy>>,
 ) {
-    for (transform, mut trail_ability) in query.iter_mut() {
+    for (transform, mut trail_ability, name) in query.iter_mut() {
         trail_ability.timer.tick(time.delta());
         if trail_ability.timer.just_finished() {
             let current_position = transform.translation;
@@ -385,9 +313,13 @@ pub fn handle_trail_abilities(
                     &mut commands,
                     last_position,
                     current_position,
-                    trail_ability.damage,
-                    trail_ability.trail_radius,
                     trail_ability.trail_duration,
+                    Trail {
+                        damage: trail_ability.damage,
+                        radius: trail_ability.trail_radius,
+                        source: name.clone(),
+                        status: trail_ability.status,
+                    },
                 );
             }
             trail_ability.last_position = Some(current_position);
@@ -397,22 +329,36 @@ pub fn handle_trail_abilities(
 
 pub fn handle_enemy_bullet_player_collision(
     mut commands: Commands,
-    player_query: Query<&Transform, (With<Player>, Without<InvincibilityEffect>)>,
-    bullet_query: Query<(Entity, &Transform, &BulletStats), With<EnemyBullet>>,
-    mut ev_player_damaged: EventWriter<PlayerDamagedEvent>,
+    player_query: Query<
+        (Entity, &Transform),
+        (With<Player>, Without<InvincibilityEffect>, Without<Downed>),
+    >,
+    bullet_query: Query<(Entity, &Transform, &BulletStats, &EnemyBullet)>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        for (bullet_entity, bullet_transform, bullet_stats) in bullet_query.iter() {
-            let distance = player_transform
+    for (bullet_entity, bullet_transform, bullet_stats, enemy_bullet) in bullet_query.iter() {
+        let hit_player = player_query.iter().find(|(_, player_transform)| {
+            player_transform
                 .translation
-                .distance(bullet_transform.translation);
-
-            if distance < 30.0 {
-                ev_player_damaged.send(PlayerDamagedEvent {
-                    damage: bullet_stats.damage,
+                .distance(bullet_transform.translation)
+                < 30.0
+        });
+
+        if let Some((player_entity, _)) = hit_player {
+            ev_damage.send(DamageEvent {
+                source: DamageSource::named(&enemy_bullet.source),
+                target: player_entity,
+                amount: bullet_stats.damage,
+                kind: DamageKind::Bullet,
+            });
+            if let Some(status) = enemy_bullet.status {
+                ev_status.send(ApplyStatusEvent {
+                    target: player_entity,
+                    effect: status.effect(DamageSource::named(&enemy_bullet.source)),
                 });
-                commands.entity(bullet_entity).try_despawn();
             }
+            commands.entity(bullet_entity).try_despawn();
         }
     }
 }
@@ -420,14 +366,19 @@ pub fn handle_enemy_bullet_player_collision(
 pub fn handle_hit_flash(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(Entity, &mut Sprite, &mut HitFlash)>,
+    mut query: Query<(
+        Entity,
+        &mut Sprite,
+        &mut HitFlash,
+        Option<&OriginalEnemyColor>,
+    )>,
 ) {
-    for (entity, mut sprite, mut flash) in query.iter_mut() {
+    for (entity, mut sprite, mut flash, original_color) in query.iter_mut() {
         flash.0.tick(time.delta());
         sprite.color = Color::srgba(1.0, 0.0, 0.0, 1.0);
 
         if flash.0.just_finished() {
-            sprite.color = Color::WHITE;
+            sprite.color = original_color.map_or(Color::WHITE, |color| color.0);
             commands.entity(entity).remove::<HitFlash>();
         }
     }
@@ -436,19 +387,28 @@ pub fn handle_hit_flash(
 pub fn handle_shooting_abilities(
     mut commands: Commands,
     time: Res<Time>,
-    mut enemy_query: Query<(&Transform, &mut ShootingAbility, Option<&GurgleEnemy>)>,
-    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(
+        &Transform,
+        &mut ShootingAbility,
+        &Name,
+        Option<&GurgleEnemy>,
+    )>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
     handle: Res<GlobalTextureAtlas>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        for (transform, mut shooting, gurgle_marker) in enemy_query.iter_mut() {
-            let distance = transform.translation.distance(player_transform.translation);
+    if !player_query.is_empty() {
+        for (transform, mut shooting, name, gurgle_marker) in enemy_query.iter_mut() {
+            let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) else {
+                continue;
+            };
+            let distance = transform.translation.distance(player_pos);
             shooting.in_range = distance <= shooting.range;
 
             shooting.shoot_timer.tick(time.delta());
 
             if shooting.in_range && shooting.shoot_timer.just_finished() {
-                let direction = (player_transform.translation - transform.translation).normalize();
+                let direction = (player_pos - transform.translation).normalize();
 
                 let is_exploding = gurgle_marker.is_some();
 
@@ -461,6 +421,9 @@ pub fn handle_shooting_abilities(
                     is_exploding,
                     shooting.bullet_speed,
                     shooting.bullet_damage,
+                    shooting.status,
+                    name,
+                    &mut rng.combat,
                 );
             }
         }
@@ -469,14 +432,16 @@ pub fn handle_shooting_abilities(
 
 pub fn handle_exploding_bullets(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform, &ExplodingBullet), With<EnemyBullet>>,
-    player_query: Query<&Transform, With<Player>>,
+    bullet_query: Query<(Entity, &Transform, &ExplodingBullet, &EnemyBullet)>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        for (bullet_entity, bullet_transform, exploding_bullet) in bullet_query.iter() {
-            let distance_to_player = bullet_transform
-                .translation
-                .distance(player_transform.translation);
+    if !player_query.is_empty() {
+        for (bullet_entity, bullet_transform, exploding_bullet, enemy_bullet) in bullet_query.iter()
+        {
+            let distance_to_player = nearest_player(bullet_transform.translation, &player_query)
+                .map_or(f32::MAX, |(_, player_pos)| {
+                    bullet_transform.translation.distance(player_pos)
+                });
 
             if distance_to_player <= 30.0 {
                 spawn_explosion(
@@ -484,6 +449,7 @@ pub fn handle_exploding_bullets(
                     bullet_transform.translation,
                     exploding_bullet.radius,
                     exploding_bullet.damage,
+                    enemy_bullet.source.clone(),
                 );
                 commands.entity(bullet_entity).try_despawn();
             }
@@ -494,6 +460,7 @@ pub fn handle_exploding_bullets(
                     bullet_transform.translation,
                     exploding_bullet.radius,
                     exploding_bullet.damage,
+                    enemy_bullet.source.clone(),
                 );
                 commands.entity(bullet_entity).try_despawn();
             }
@@ -504,29 +471,30 @@ pub fn handle_exploding_bullets(
 pub fn handle_charge_abilities(
     time: Res<Time>,
     mut enemy_query: Query<(Entity, &mut Transform, &mut ChargeAbility, &Enemy)>,
-    player_query: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Enemy>, Without<Downed>)>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
+    if !player_query.is_empty() {
         let enemy_positions: Vec<(Entity, Vec3)> = enemy_query
             .iter()
             .map(|(entity, transform, _, _)| (entity, transform.translation))
             .collect();
 
         for (entity, mut transform, mut charge, enemy) in enemy_query.iter_mut() {
+            let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) else {
+                continue;
+            };
             charge.charge_timer.tick(time.delta());
 
             let mut movement = Vec2::ZERO;
 
             match charge.state {
                 ChargeState::Approaching => {
-                    if transform.translation.distance(player_transform.translation)
-                        <= charge.charge_distance as f32
-                    {
+                    if transform.translation.distance(player_pos) <= charge.charge_distance as f32 {
                         charge.state = ChargeState::Preparing;
                         charge.charge_timer = Timer::from_seconds(1.5, TimerMode::Once);
                     } else {
-                        let direction =
-                            (player_transform.translation - transform.translation).normalize();
+                        let direction = (player_pos - transform.translation).normalize();
                         movement = direction.truncate() * enemy.speed as f32;
                     }
                 }
@@ -534,7 +502,7 @@ pub fn handle_charge_abilities(
                     if charge.charge_timer.just_finished() {
                         charge.state = ChargeState::Charging;
                         charge.charge_timer = Timer::from_seconds(0.5, TimerMode::Once);
-                        charge.target_position = Some(player_transform.translation.truncate());
+                        charge.target_position = Some(player_pos.truncate());
                     }
                 }
                 ChargeState::Charging => {
@@ -549,8 +517,8 @@ pub fn handle_charge_abilities(
 
                         let shake_amount = 2.0;
                         let shake_offset = Vec2::new(
-                            rand::random::<f32>() * shake_amount - shake_amount / 2.0,
-                            rand::random::<f32>() * shake_amount - shake_amount / 2.0,
+                            rng.ai.gen::<f32>() * shake_amount - shake_amount / 2.0,
+                            rng.ai.gen::<f32>() * shake_amount - shake_amount / 2.0,
                         );
                         transform.translation += shake_offset.extend(0.0);
                     }
@@ -559,25 +527,33 @@ pub fn handle_charge_abilities(
                     if charge.charge_timer.just_finished() {
                         charge.state = ChargeState::Approaching;
                     } else {
-                        let direction =
-                            (player_transform.translation - transform.translation).normalize();
+                        let direction = (player_pos - transform.translation).normalize();
                         movement = direction.truncate() * enemy.speed as f32;
 
                         let shake_amount = 1.0;
                         let shake_offset = Vec2::new(
-                            rand::random::<f32>() * shake_amount - shake_amount / 2.0,
-                            rand::random::<f32>() * shake_amount - shake_amount / 2.0,
+                            rng.ai.gen::<f32>() * shake_amount - shake_amount / 2.0,
+                            rng.ai.gen::<f32>() * shake_amount - shake_amount / 2.0,
                         );
                         transform.translation += shake_offset.extend(0.0);
                     }
                 }
             }
 
-            let collision_resolution =
-                calculate_collision_resolution(entity, transform.translation, &enemy_positions);
+            let collision_resolution = calculate_collision_resolution(
+                entity,
+                transform.translation,
+                &enemy_positions,
+                &mut rng.ai,
+            );
             let final_movement = movement + collision_resolution;
 
-            apply_movement(&mut transform.translation, final_movement, LAYER2);
+            apply_movement(
+                &mut transform.translation,
+                final_movement,
+                time.delta_secs(),
+                LAYER2,
+            );
         }
     }
 }
@@ -607,18 +583,25 @@ pub fn handle_enemy_death(
         Entity,
         &Enemy,
         &Transform,
+        &Name,
         Option<&ExplosionAbility>,
         Option<&LootPool>,
     )>,
-    mut ev_player_damaged: EventWriter<PlayerDamagedEvent>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
     mut level: ResMut<Level>,
     handle: Res<GlobalTextureAtlas>,
-    mut player_query: Query<(&Transform, Option<&InvincibilityEffect>, &mut Gold), With<Player>>,
+    mut player_query: Query<
+        (Entity, &Transform, Option<&InvincibilityEffect>, &mut Gold),
+        (With<Player>, Without<Downed>),
+    >,
     mut ev_level_up: EventWriter<PlayerLevelingUpEvent>,
     mut ew: EventWriter<AudioEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if let Ok((player_transform, is_invincible, mut gold)) = player_query.get_single_mut() {
-        for (entity, enemy, transform, explosion_ability, loot_pool) in enemy_query.iter_mut() {
+    if !player_query.is_empty() {
+        for (entity, enemy, transform, name, explosion_ability, loot_pool) in enemy_query.iter_mut()
+        {
             if enemy.health == 0 {
                 ew.send(AudioEvent::Kill);
                 if let Some(explosion) = explosion_ability {
@@ -627,17 +610,29 @@ pub fn handle_enemy_death(
                         transform.translation,
                         explosion.explosion_radius,
                         explosion.explosion_damage,
+                        name.clone(),
                     );
-                    let distance = player_transform.translation.distance(transform.translation);
-                    if distance <= explosion.explosion_radius && is_invincible.is_none() {
-                        ev_player_damaged.send(PlayerDamagedEvent {
-                            damage: explosion.explosion_damage,
-                        });
+                    for (player_entity, player_transform, is_invincible, _) in player_query.iter() {
+                        let distance = player_transform.translation.distance(transform.translation);
+                        if distance <= explosion.explosion_radius && is_invincible.is_none() {
+                            ev_damage.send(DamageEvent {
+                                source: DamageSource::new(entity, name),
+                                target: player_entity,
+                                amount: explosion.explosion_damage,
+                                kind: DamageKind::Explosion,
+                            });
+                            if let Some(status) = explosion.status {
+                                ev_status.send(ApplyStatusEvent {
+                                    target: player_entity,
+                                    effect: status.effect(DamageSource::new(entity, name)),
+                                });
+                            }
+                        }
                     }
                 }
 
                 if let Some(loot_pool) = loot_pool {
-                    let loot_defs = loot_pool.get_random_loots();
+                    let loot_defs = loot_pool.get_random_loots(&mut rng.loot);
                     for loot_def in loot_defs {
                         (loot_def.spawn_fn)(
                             &mut commands,
@@ -646,10 +641,13 @@ pub fn handle_enemy_death(
                             handle.layout_16x16.clone(),
                             loot_def.stat_range,
                             loot_def.value,
+                            &mut rng.loot,
                         );
                     }
                 }
-                gold.0 += 2;
+                for (.., mut gold) in player_query.iter_mut() {
+                    gold.0 += 2;
+                }
                 if level.add_xp(enemy.xp) {
                     ev_level_up.send(PlayerLevelingUpEvent {
                         new_level: level.level(),
@@ -659,7 +657,7 @@ pub fn handle_enemy_death(
                 commands
                     .entity(entity)
                     .insert(DeathEffect::default())
-                    .remove::<Enemy>();
+                    .remove::<(Enemy, StatusEffects)>();
             }
         }
     }
@@ -681,7 +679,7 @@ pub fn handle_death_effect(
         transform.scale = scale;
 
         if effect.timer.just_finished() {
-            commands.entity(entity).despawn();
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
@@ -690,10 +688,10 @@ fn spawn_trail_segment(
     commands: &mut Commands,
     start: Vec3,
     end: Vec3,
-    damage: u32,
-    radius: f32,
     duration: f32,
+    trail: Trail,
 ) {
+    let radius = trail.radius;
     let direction = (end - start).normalize();
     let length = (end - start).length();
     let angle = direction.y.atan2(direction.x);
@@ -707,7 +705,7 @@ fn spawn_trail_segment(
             timer: Timer::from_seconds(duration, TimerMode::Once),
             width: radius * 2.0,
         },
-        Trail { damage, radius },
+        trail,
         HasLifespan::new(Duration::from_secs_f32(duration)),
         Sprite {
             color: Color::srgba(0.0, 0.8, 0.0, 0.6),
@@ -745,7 +743,13 @@ pub fn update_trail_segments(
     }
 }
 
-pub fn spawn_explosion(commands: &mut Commands, position: Vec3, radius: f32, damage: u32) {
+pub fn spawn_explosion(
+    commands: &mut Commands,
+    position: Vec3,
+    radius: f32,
+    damage: u32,
+    source: Name,
+) {
     commands.spawn((
         Name::new("Explosion"),
         Sprite {
@@ -758,6 +762,7 @@ pub fn spawn_explosion(commands: &mut Commands, position: Vec3, radius: f32, dam
             radius,
             damage,
             timer: Timer::from_seconds(0.3, TimerMode::Once),
+            source,
         },
     ));
 }
@@ -765,18 +770,18 @@ pub fn spawn_explosion(commands: &mut Commands, position: Vec3, radius: f32, dam
 pub fn update_enemy_bullets(
     mut commands: Commands,
     time: Res<Time>,
-    mut bullet_query: Query<
-        (
-            Entity,
-            &mut Transform,
-            &BulletDirection,
-            &BulletStats,
-            Option<&ExplodingBullet>,
-        ),
-        With<EnemyBullet>,
-    >,
+    mut bullet_query: Query<(
+        Entity,
+        &mut Transform,
+        &BulletDirection,
+        &BulletStats,
+        &EnemyBullet,
+        Option<&ExplodingBullet>,
+    )>,
 ) {
-    for (entity, mut transform, direction, stats, exploding) in bullet_query.iter_mut() {
+    for (entity, mut transform, direction, stats, enemy_bullet, exploding) in
+        bullet_query.iter_mut()
+    {
         transform.translation += direction.0 * stats.speed as f32 * time.delta_secs();
 
         if transform.translation.x.abs() > WW || transform.translation.y.abs() > WH {
@@ -786,6 +791,7 @@ pub fn update_enemy_bullets(
                     transform.translation,
                     exploding_bullet.radius,
                     exploding_bullet.damage,
+                    enemy_bullet.source.clone(),
                 );
             }
             commands.entity(entity).try_despawn();
@@ -802,60 +808,93 @@ fn spawn_enemy_bullets(
     is_exploding: bool,
     bullet_speed: u32,
     bullet_damage: u32,
+    status: Option<StatusSpec>,
+    source: &Name,
+    rng: &mut impl Rng,
 ) {
     for _ in 0..num_bullets {
         let spread = Vec3::new(
-            rand::random::<f32>() * 0.2 - 0.1,
-            rand::random::<f32>() * 0.2 - 0.1,
+            rng.gen::<f32>() * 0.2 - 0.1,
+            rng.gen::<f32>() * 0.2 - 0.1,
             0.0,
         );
         let bullet_direction = (direction + spread).normalize();
 
-        let sprite_index = if is_exploding { 89 } else { 88 };
-
-        let mut bullet_entity = commands.spawn((
-            Name::new("Enemy Bullet"),
-            Sprite {
-                image: handle.image.clone().unwrap(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: handle.layout_16x16.clone().unwrap(),
-                    index: sprite_index,
-                }),
-                ..default()
-            },
-            Transform::from_translation(enemy_pos).with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            EnemyBullet,
-            BulletDirection(bullet_direction),
-            BulletStats {
-                speed: bullet_speed,
-                damage: bullet_damage,
-                lifespan: BULLET_TIME_SECS,
-            },
-        ));
-
-        if is_exploding {
-            bullet_entity.insert(ExplodingBullet {
-                radius: 135.0,
-                damage: 30,
-            });
-        }
+        spawn_enemy_bullet(
+            commands,
+            enemy_pos,
+            bullet_direction,
+            handle,
+            is_exploding,
+            bullet_speed,
+            bullet_damage,
+            status,
+            source,
+        );
+    }
+}
+
+pub fn spawn_enemy_bullet(
+    commands: &mut Commands,
+    enemy_pos: Vec3,
+    direction: Vec3,
+    handle: &GlobalTextureAtlas,
+    is_exploding: bool,
+    bullet_speed: u32,
+    bullet_damage: u32,
+    status: Option<StatusSpec>,
+    source: &Name,
+) {
+    let sprite_index = if is_exploding { 89 } else { 88 };
+
+    let mut bullet_entity = commands.spawn((
+        Name::new("Enemy Bullet"),
+        Sprite {
+            image: handle.image.clone().unwrap(),
+            texture_atlas: Some(TextureAtlas {
+                layout: handle.layout_16x16.clone().unwrap(),
+                index: sprite_index,
+            }),
+            ..default()
+        },
+        Transform::from_translation(enemy_pos).with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        EnemyBullet {
+            source: source.clone(),
+            status,
+        },
+        BulletDirection(direction),
+        BulletStats {
+            speed: bullet_speed,
+            damage: bullet_damage,
+            lifespan: BULLET_TIME_SECS,
+        },
+    ));
+
+    if is_exploding {
+        bullet_entity.insert(ExplodingBullet {
+            radius: 135.0,
+            damage: 30,
+        });
     }
 }
 
 pub fn handle_explosion_player_collision(
     explosion_query: Query<(&Transform, &Explosion)>,
-    player_query: Query<&Transform, (With<Player>, Without<InvincibilityEffect>)>,
-    mut ev_player_damaged: EventWriter<PlayerDamagedEvent>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<InvincibilityEffect>)>,
+    mut ev_damage: EventWriter<DamageEvent>,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
+    for (player_entity, player_transform) in player_query.iter() {
         for (explosion_transform, explosion) in explosion_query.iter() {
             let distance = player_transform
                 .translation
                 .distance(explosion_transform.translation);
 
             if distance <= explosion.radius {
-                ev_player_damaged.send(PlayerDamagedEvent {
-                    damage: explosion.damage,
+                ev_damage.send(DamageEvent {
+                    source: DamageSource::named(&explosion.source),
+                    target: player_entity,
+                    amount: explosion.damage,
+                    kind: DamageKind::Explosion,
                 });
             }
         }
@@ -881,16 +920,18 @@ pub fn handle_explosions(
 }
 
 pub fn handle_ranged_movement(
-    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
     mut enemy_query: Query<
         (&Enemy, &mut Transform, &mut EnemyState, &RangedBehavior),
         Without<Player>,
     >,
 ) {
-    if let Ok(player_transform) = player_query.get_single() {
-        let player_pos = player_transform.translation;
-
+    if !player_query.is_empty() {
         for (enemy, mut transform, mut state, range_behavior) in enemy_query.iter_mut() {
+            let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) else {
+                continue;
+            };
             let distance_to_player = transform.translation.distance(player_pos);
             let direction = (player_pos - transform.translation).normalize();
 
@@ -900,18 +941,33 @@ pub fn handle_ranged_movement(
                 if distance_difference > 0.0 {
                     *state = EnemyState::Pursuing;
                     let movement = direction.truncate() * enemy.speed as f32;
-                    apply_movement(&mut transform.translation, movement, LAYER2);
+                    apply_movement(
+                        &mut transform.translation,
+                        movement,
+                        time.delta_secs(),
+                        LAYER2,
+                    );
                 } else {
                     *state = EnemyState::Retreating;
                     let movement = -direction.truncate() * enemy.speed as f32;
-                    apply_movement(&mut transform.translation, movement, LAYER2);
+                    apply_movement(
+                        &mut transform.translation,
+                        movement,
+                        time.delta_secs(),
+                        LAYER2,
+                    );
                 }
             } else {
                 *state = EnemyState::MaintainingDistance;
 
                 if distance_difference.abs() > range_behavior.tolerance * 0.5 {
-                    let movement = direction.truncate() * (distance_difference * 0.1);
-                    apply_movement(&mut transform.translation, movement, LAYER2);
+                    let movement = direction.truncate() * (distance_difference * 6.0);
+                    apply_movement(
+                        &mut transform.translation,
+                        movement,
+                        time.delta_secs(),
+                        LAYER2,
+                    );
                 }
             }
         }
@@ -920,10 +976,11 @@ pub fn handle_ranged_movement(
 
 pub fn handle_enemy_splitting(
     mut commands: Commands,
-    enemy_query: Query<(Entity, &Transform, &Enemy, &SplitAbility)>,
+    enemy_query: Query<(Entity, &Transform, &Enemy, &Name, &SplitAbility)>,
     handle: Res<GlobalTextureAtlas>,
+    registry: Res<EnemyRegistry>,
 ) {
-    for (_, transform, enemy, split_ability) in enemy_query.iter() {
+    for (_, transform, enemy, name, split_ability) in enemy_query.iter() {
         if enemy.health == 0 && split_ability.splits_remaining > 0 {
             let num_spawns = match split_ability.splits_remaining {
                 3 => 4,
@@ -932,15 +989,24 @@ pub fn handle_enemy_splitting(
                 _ => 0,
             };
 
+            let (sprite_index, sprite_size) = registry
+                .get(name.as_str())
+                .map_or((56, (16, 16)), |definition| {
+                    (definition.sprite.index, definition.sprite.size)
+                });
+
             for i in 0..num_spawns {
                 let angle = (i as f32 / num_spawns as f32) * 2.0 * std::f32::consts::PI;
                 let offset = Vec2::new(angle.cos(), angle.sin()) * 30.0;
                 let new_pos = transform.translation + Vec3::new(offset.x, offset.y, 0.0);
 
                 let enemy_builder = EnemyBuilder::new()
+                    .with_name(name.as_str())
                     .with_stats(enemy.health + 20, enemy.speed, enemy.damage, enemy.xp / 2)
-                    .with_sprite(56, (16, 16))
-                    .with_splitting(split_ability.splits_remaining - 1);
+                    .with_sprite(sprite_index, sprite_size)
+                    .with_ability(AbilitySpec::Splitting {
+                        splits: split_ability.splits_remaining - 1,
+                    });
 
                 enemy_builder.spawn(&mut commands, new_pos, &handle);
             }
@@ -948,23 +1014,155 @@ pub fn handle_enemy_splitting(
     }
 }
 
+pub fn handle_spiral_abilities(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemy_query: Query<(&Transform, &mut SpiralAbility, &Name)>,
+    player_query: Query<(), (With<Player>, Without<Downed>)>,
+    handle: Res<GlobalTextureAtlas>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+    for (transform, mut spiral, name) in enemy_query.iter_mut() {
+        spiral.timer.tick(time.delta());
+        if !spiral.timer.just_finished() {
+            continue;
+        }
+
+        let arm_step = std::f32::consts::TAU / spiral.arms.max(1) as f32;
+        for arm in 0..spiral.arms {
+            let direction = Vec2::from_angle(spiral.angle + arm_step * arm as f32);
+            spawn_enemy_bullet(
+                &mut commands,
+                transform.translation,
+                direction.extend(0.0),
+                &handle,
+                false,
+                spiral.bullet_speed,
+                spiral.bullet_damage,
+                None,
+                name,
+            );
+        }
+        spiral.angle = (spiral.angle + spiral.turn) % std::f32::consts::TAU;
+    }
+}
+
+pub fn handle_burrow_abilities(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemy_query: Query<(
+        Entity,
+        &Transform,
+        &mut BurrowAbility,
+        &mut Stats,
+        &mut Visibility,
+        &Name,
+        Has<Burrowed>,
+    )>,
+) {
+    for (entity, transform, mut burrow, mut stats, mut visibility, name, burrowed) in
+        enemy_query.iter_mut()
+    {
+        burrow.timer.tick(time.delta());
+        if !burrow.timer.just_finished() {
+            continue;
+        }
+
+        if burrowed {
+            burrow.timer = Timer::from_seconds(burrow.interval, TimerMode::Once);
+            stats.remove_modifiers_from("Burrow");
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Burrowed>();
+            spawn_explosion(
+                &mut commands,
+                transform.translation,
+                burrow.eruption_radius,
+                burrow.eruption_damage,
+                name.clone(),
+            );
+        } else {
+            burrow.timer = Timer::from_seconds(burrow.duration, TimerMode::Once);
+            stats.add_modifier(StatModifier::multiply(
+                StatKind::Speed,
+                burrow.speed,
+                "Burrow",
+            ));
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).insert(Burrowed);
+        }
+    }
+}
+
+pub fn handle_hazard_abilities(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemy_query: Query<(Entity, &Transform, &mut HazardAbility)>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Downed>)>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (entity, transform, mut hazard) in enemy_query.iter_mut() {
+        hazard.timer.tick(time.delta());
+        if !hazard.timer.just_finished() {
+            continue;
+        }
+        let Some((_, player_pos)) = nearest_player(transform.translation, &player_query) else {
+            continue;
+        };
+
+        for i in 0..hazard.count {
+            let mut position = if i == 0 {
+                player_pos
+            } else {
+                let (x, y) = get_random_position_around(
+                    player_pos.truncate(),
+                    0.0..BOSS_HAZARD_SPREAD,
+                    &mut rng.enemy,
+                );
+                Vec3::new(x, y, 0.0)
+            };
+            clamp_position(&mut position);
+            spawn_telegraph(
+                &mut commands,
+                entity,
+                TelegraphKind::Hazard {
+                    radius: hazard.radius,
+                    damage: hazard.damage,
+                },
+                position,
+                hazard.delay,
+                hazard.radius * 2.0,
+            );
+        }
+    }
+}
+
 pub fn handle_summoning_abilities(
     mut commands: Commands,
     time: Res<Time>,
     mut query: Query<(&Transform, &mut SummoningAbility), With<Enemy>>,
     handle: Res<GlobalTextureAtlas>,
+    mut rng: ResMut<GameRng>,
+    registry: Res<EnemyRegistry>,
 ) {
     for (transform, mut summoning_ability) in query.iter_mut() {
         summoning_ability.timer.tick(time.delta());
         if summoning_ability.timer.just_finished() {
-            let num_minions = rand::thread_rng()
+            let num_minions = rng
+                .enemy
                 .gen_range(summoning_ability.min_minions..=summoning_ability.max_minions);
             let spread_radius_min = 200.0;
             let spread_radius_max = 1000.0;
             let angle_step = 2.0 * std::f32::consts::PI / num_minions as f32;
+            let minions = summoning_ability
+                .minions
+                .iter()
+                .map(|(name, weight)| (name.as_str(), *weight))
+                .collect::<Vec<_>>();
             for i in 0..num_minions {
                 let angle = angle_step * i as f32;
-                let radius = rand::thread_rng().gen_range(spread_radius_min..spread_radius_max);
+                let radius = rng.enemy.gen_range(spread_radius_min..spread_radius_max);
 
                 let offset_x = angle.cos() * radius;
                 let offset_y = angle.sin() * radius;
@@ -972,14 +1170,10 @@ pub fn handle_summoning_abilities(
                 let mut position = transform.translation + Vec3::new(offset_x, offset_y, 0.0);
                 clamp_position(&mut position);
 
-                let enemy = match rand::random::<f32>() {
-                    x if x < 0.03 => create_splitting_enemy(),
-                    x if x < 0.2 => create_basic_enemy(),
-                    x if x < 0.4 => create_trail_enemy(),
-                    x if x < 0.65 => create_charging_enemy(),
-                    x if x < 0.8 => create_shooter_enemy(),
-                    x if x < 0.95 => create_bomber_enemy(),
-                    _ => create_gurgle_enemy(),
+                let Some(enemy) = pick_weighted(&minions, &mut rng.enemy)
+                    .and_then(|minion| registry.builder(minion))
+                else {
+                    continue;
                 };
                 enemy.spawn(&mut commands, position, &handle);
             }
@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{resources::GameMode, utils::get_random_position_around};
+
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct WaveScript {
+    pub name: String,
+    #[serde(default)]
+    pub win_after_wave: Option<u32>,
+    #[serde(default)]
+    pub elite_chance: Option<Curve>,
+    #[serde(default)]
+    pub mix: Vec<WaveEnemy>,
+    pub waves: Vec<WaveRule>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WaveRule {
+    pub from: u32,
+    #[serde(default)]
+    pub to: Option<u32>,
+    #[serde(default)]
+    pub every: Option<u32>,
+    pub count: Curve,
+    #[serde(default)]
+    pub count_variance: u32,
+    pub difficulty: Curve,
+    #[serde(default)]
+    pub mix: Option<Vec<WaveEnemy>>,
+    #[serde(default)]
+    pub pattern: SpawnPattern,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WaveEnemy {
+    pub enemy: String,
+    pub weight: Curve,
+    #[serde(default)]
+    pub from: u32,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Curve {
+    Constant(f32),
+    Linear {
+        base: f32,
+        per_wave: f32,
+        #[serde(default)]
+        max: Option<f32>,
+    },
+    Inverse {
+        base: f32,
+        per_wave: f32,
+    },
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum SpawnPattern {
+    Scattered {
+        min_distance: f32,
+        max_distance: f32,
+    },
+    Ring {
+        distance: f32,
+    },
+    Clusters {
+        groups: u32,
+        min_distance: f32,
+        max_distance: f32,
+        spread: f32,
+    },
+}
+
+#[derive(Resource)]
+pub struct WaveScripts {
+    pub normal: WaveScript,
+    pub forever: WaveScript,
+}
+
+#[derive(Resource)]
+pub struct WaveScriptHandles {
+    pub normal: Handle<WaveScript>,
+    pub forever: Handle<WaveScript>,
+}
+
+impl Default for SpawnPattern {
+    fn default() -> Self {
+        Self::Scattered {
+            min_distance: 250.0,
+            max_distance: 1000.0,
+        }
+    }
+}
+
+impl Curve {
+    pub fn at(&self, wave: u32) -> f32 {
+        let wave = wave as f32;
+        match *self {
+            Curve::Constant(value) => value,
+            Curve::Linear {
+                base,
+                per_wave,
+                max,
+            } => {
+                let value = base + per_wave * wave;
+                max.map_or(value, |max| value.min(max))
+            }
+            Curve::Inverse { base, per_wave } => base / (1.0 + per_wave * wave),
+        }
+    }
+}
+
+impl WaveRule {
+    pub fn matches(&self, wave: u32) -> bool {
+        wave >= self.from
+            && self.to.map_or(true, |to| wave <= to)
+            && self
+                .every
+                .map_or(true, |every| (wave - self.from) % every.max(1) == 0)
+    }
+
+    pub fn enemy_count(&self, wave: u32, rng: &mut impl Rng) -> u32 {
+        let count = self.count.at(wave).max(0.0) as u32;
+        if self.count_variance == 0 {
+            count
+        } else {
+            count + rng.gen::<u32>() % self.count_variance
+        }
+    }
+}
+
+impl SpawnPattern {
+    pub fn positions(&self, around: Vec2, count: u32, rng: &mut impl Rng) -> Vec<Vec2> {
+        match *self {
+            SpawnPattern::Scattered {
+                min_distance,
+                max_distance,
+            } => (0..count)
+                .map(|_| get_random_position_around(around, min_distance..max_distance, rng).into())
+                .collect(),
+            SpawnPattern::Ring { distance } => {
+                let offset = rng.gen_range(0.0..std::f32::consts::TAU);
+                (0..count)
+                    .map(|i| {
+                        let angle = offset + std::f32::consts::TAU * i as f32 / count as f32;
+                        around + Vec2::from_angle(angle) * distance
+                    })
+                    .collect()
+            }
+            SpawnPattern::Clusters {
+                groups,
+                min_distance,
+                max_distance,
+                spread,
+            } => {
+                let centers: Vec<Vec2> = (0..groups.max(1))
+                    .map(|_| {
+                        get_random_position_around(around, min_distance..max_distance, rng).into()
+                    })
+                    .collect();
+                (0..count)
+                    .map(|i| {
+                        let center = centers[i as usize % centers.len()];
+                        get_random_position_around(center, 0.0..spread, rng).into()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl WaveScript {
+    pub fn rule(&self, wave: u32) -> Option<&WaveRule> {
+        self.waves.iter().find(|rule| rule.matches(wave))
+    }
+
+    pub fn elite_chance_at(&self, wave: u32) -> f32 {
+        self.elite_chance
+            .map_or(0.0, |curve| curve.at(wave).clamp(0.0, 1.0))
+    }
+
+    pub fn is_finished(&self, wave: u32) -> bool {
+        self.win_after_wave.is_some_and(|last| wave > last) || self.rule(wave).is_none()
+    }
+
+    pub fn enemy_weights<'a>(&'a self, rule: &'a WaveRule, wave: u32) -> Vec<(&'a str, f32)> {
+        rule.mix
+            .as_ref()
+            .unwrap_or(&self.mix)
+            .iter()
+            .filter(|entry| wave >= entry.from)
+            .map(|entry| (entry.enemy.as_str(), entry.weight.at(wave)))
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect()
+    }
+}
+
+impl Default for WaveScripts {
+    fn default() -> Self {
+        Self {
+            normal: ron::from_str(include_str!("../../assets/waves/normal.ron"))
+                .expect("built-in normal wave script should parse"),
+            forever: ron::from_str(include_str!("../../assets/waves/forever.ron"))
+                .expect("built-in forever wave script should parse"),
+        }
+    }
+}
+
+impl WaveScripts {
+    pub fn for_mode(&self, game_mode: GameMode) -> &WaveScript {
+        match game_mode {
+            GameMode::Normal => &self.normal,
+            GameMode::Forever => &self.forever,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_wave_scripts(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(WaveScriptHandles {
+        normal: asset_server.load(crate::configs::NORMAL_WAVE_SCRIPT_PATH),
+        forever: asset_server.load(crate::configs::FOREVER_WAVE_SCRIPT_PATH),
+    });
+}
+
+pub fn sync_wave_scripts(
+    mut events: EventReader<AssetEvent<WaveScript>>,
+    handles: Res<WaveScriptHandles>,
+    scripts: Res<Assets<WaveScript>>,
+    mut wave_scripts: ResMut<WaveScripts>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(script) = scripts.get(*id) else {
+            continue;
+        };
+        if *id == handles.normal.id() {
+            wave_scripts.normal = script.clone();
+        } else if *id == handles.forever.id() {
+            wave_scripts.forever = script.clone();
+        } else {
+            continue;
+        }
+        info!("Loaded wave script {}", script.name);
+    }
+}
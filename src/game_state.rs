@@ -5,11 +5,24 @@ pub enum GameState {
     #[default]
     Loading,
     MainMenu,
+    ClassSelect,
     Initializing,
     Combat,
     Paused,
     Ui,
     Shopping,
+    LevelUp,
     End,
     Win,
 }
+
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameplaySet {
+    Status,
+    Stats,
+    Enemy,
+    Collision,
+    Damage,
+    Player,
+    Gun,
+}
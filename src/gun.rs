@@ -1,27 +1,27 @@
 use std::{f32::consts::PI, ops::RangeInclusive};
 
-use bevy::{
-    math::vec3,
-    prelude::*,
-    time::Stopwatch,
-    utils::{Duration, Instant},
-};
+use bevy::{math::vec3, prelude::*, time::Stopwatch, utils::Duration};
 use leafwing_input_manager::prelude::ActionState;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    aim::{select_aim_target, AimMode, AimTarget},
     audio::AudioEvent,
     collision::EnemyKdTree,
     configs::*,
-    game_state::GameState,
-    input::Action,
+    game_state::{GameState, GameplaySet},
+    input::CombatAction,
+    interpolation::Interpolated,
     loot::Description,
-    player::{handle_player_movement, Player, PlayerInventory},
+    perk::Perks,
+    player::{Downed, Player, PlayerInventory},
     resources::GlobalTextureAtlas,
+    rng::GameRng,
     utils::{get_nearest_enemy_position, InGameEntity},
 };
 
-use crate::enemy::{spawn_explosion, ExplodingBullet};
+use crate::enemy::{spawn_explosion, Enemy, EnemyBullet, ExplodingBullet};
 
 pub struct GunPlugin;
 
@@ -34,13 +34,13 @@ pub struct GunPlugin;
                 bullets_per_shot: NUM_BULLETS_PER_SHOT,
                 firing_interval: FIRING_INTERVAL,
                 bullet_spread: BULLET_SPREAD,
-            }), InGameEntity, Sprite, Description)]
+            }), InGameEntity, Sprite, Description, Interpolated)]
 pub struct Gun;
 
 #[derive(Component, Default)]
 pub struct GunTimer(pub Stopwatch);
 
-#[derive(Component, Clone, Default, Copy)]
+#[derive(Component, Clone, Default, Copy, Serialize, Deserialize)]
 pub enum GunType {
     #[default]
     SingleDirectionSpread,
@@ -51,16 +51,20 @@ pub enum GunType {
 #[derive(Component)]
 pub struct ActiveGun;
 #[derive(Component)]
+#[require(Interpolated)]
 pub struct Bullet;
 
-#[derive(Component, Default)]
+#[derive(Component)]
+pub struct BulletOwner(pub Entity);
+
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct BulletStats {
     pub speed: u32,
     pub damage: u32,
     pub lifespan: f32,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct GunStats {
     pub bullets_per_shot: usize,
     pub firing_interval: f32,
@@ -69,15 +73,13 @@ pub struct GunStats {
 
 #[derive(Component)]
 pub struct HasLifespan {
-    pub spawn_time: Instant,
-    pub lifespan: Duration,
+    pub timer: Timer,
 }
 
 impl HasLifespan {
     pub fn new(lifespan: Duration) -> Self {
         HasLifespan {
-            spawn_time: Instant::now(),
-            lifespan,
+            timer: Timer::new(lifespan, TimerMode::Once),
         }
     }
 }
@@ -88,61 +90,91 @@ pub struct BulletDirection(pub Vec3);
 impl Plugin for GunPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
-                update_gun_transform.after(handle_player_movement),
+                select_aim_target,
+                update_gun_transform,
                 move_bullets,
                 handle_gun_firing,
                 despawn_entities_reach_lifespan,
-                switch_gun,
             )
+                .chain()
+                .in_set(GameplaySet::Gun)
                 .run_if(in_state(GameState::Combat)),
-        );
+        )
+        .add_systems(Update, switch_gun.run_if(in_state(GameState::Combat)));
     }
 }
 
 fn update_gun_transform(
-    player_query: Query<&Transform, With<Player>>,
-    enemy_kd_tree: Res<EnemyKdTree>,
+    player_query: Query<
+        (
+            &Transform,
+            &PlayerInventory,
+            &AimTarget,
+            Option<&ActionState<CombatAction>>,
+        ),
+        With<Player>,
+    >,
+    target_query: Query<&Transform, (With<Enemy>, Without<ActiveGun>)>,
+    aim_mode: Res<AimMode>,
+    action_state: Res<ActionState<CombatAction>>,
     mut gun_query: Query<&mut Transform, (With<ActiveGun>, Without<Player>)>,
 ) {
-    let player_transform = if let Ok(transform) = player_query.get_single() {
-        transform
-    } else {
-        return;
-    };
-
-    let mut gun_transform = if let Ok(transform) = gun_query.get_single_mut() {
-        transform
-    } else {
-        return;
-    };
-
-    let player_pos = player_transform.translation.truncate();
-    if let Some(nearest_enemy_pos) = get_nearest_enemy_position(player_pos, &enemy_kd_tree, 700.0) {
-        let angle =
-            (player_pos.y - nearest_enemy_pos.y).atan2(player_pos.x - nearest_enemy_pos.x) + PI;
-        gun_transform.rotation = Quat::from_rotation_z(angle);
+    for (player_transform, inventory, aim_target, player_state) in player_query.iter() {
+        let Some(mut gun_transform) = inventory
+            .guns
+            .get(inventory.active_gun_index)
+            .and_then(|gun| gun_query.get_mut(*gun).ok())
+        else {
+            continue;
+        };
+
+        let player_pos = player_transform.translation.truncate();
+        if aim_mode.is_manual() {
+            let aim = player_state
+                .unwrap_or(&action_state)
+                .clamped_axis_pair(&CombatAction::Aim);
+            if aim != Vec2::ZERO {
+                gun_transform.rotation = Quat::from_rotation_z(aim.y.atan2(aim.x));
+            }
+        } else if let Some(target_pos) = aim_target
+            .0
+            .and_then(|target| target_query.get(target).ok())
+            .map(|transform| transform.translation.truncate())
+        {
+            let angle = (player_pos.y - target_pos.y).atan2(player_pos.x - target_pos.x) + PI;
+            gun_transform.rotation = Quat::from_rotation_z(angle);
+        }
+        gun_transform.translation = vec3(
+            player_pos.x + 5.0, // offset from player, need adjustment
+            player_pos.y - 5.0,
+            gun_transform.translation.z,
+        );
     }
-    gun_transform.translation = vec3(
-        player_pos.x + 5.0, // offset from player, need adjustment
-        player_pos.y - 5.0,
-        gun_transform.translation.z,
-    );
 }
 
 fn despawn_entities_reach_lifespan(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform, &HasLifespan, Option<&ExplodingBullet>)>,
+    time: Res<Time>,
+    mut bullet_query: Query<(
+        Entity,
+        &Transform,
+        &mut HasLifespan,
+        Option<&ExplodingBullet>,
+        Option<&EnemyBullet>,
+    )>,
 ) {
-    for (entity, transform, lifespan, exploding) in bullet_query.iter() {
-        if lifespan.spawn_time.elapsed() > lifespan.lifespan {
-            if let Some(exploding_bullet) = exploding {
+    for (entity, transform, mut lifespan, exploding, enemy_bullet) in bullet_query.iter_mut() {
+        lifespan.timer.tick(time.delta());
+        if lifespan.timer.finished() {
+            if let (Some(exploding_bullet), Some(enemy_bullet)) = (exploding, enemy_bullet) {
                 spawn_explosion(
                     &mut commands,
                     transform.translation,
                     exploding_bullet.radius,
                     exploding_bullet.damage,
+                    enemy_bullet.source.clone(),
                 );
             }
             commands.entity(entity).try_despawn();
@@ -153,30 +185,46 @@ fn despawn_entities_reach_lifespan(
 fn handle_gun_firing(
     mut commands: Commands,
     time: Res<Time>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<
+        (
+            Entity,
+            &Perks,
+            &PlayerInventory,
+            &AimTarget,
+            Option<&ActionState<CombatAction>>,
+        ),
+        (With<Player>, Without<Downed>),
+    >,
     mut gun_query: Query<
         (&Transform, &mut GunTimer, &GunType, &BulletStats, &GunStats),
         With<ActiveGun>,
     >,
     handle: Res<GlobalTextureAtlas>,
     mut ew: EventWriter<AudioEvent>,
-    enemy_kd_tree: Res<EnemyKdTree>,
+    mut rng: ResMut<GameRng>,
+    aim_mode: Res<AimMode>,
+    action_state: Res<ActionState<CombatAction>>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    if let Ok((gun_transform, mut gun_timer, gun_type, bullet_stats, gun_stats)) =
-        gun_query.get_single_mut()
-    {
-        let player_pos = player_query.single().translation.truncate();
-        if get_nearest_enemy_position(player_pos, &enemy_kd_tree, 700.0).is_none() {
-            return;
+    for (player_entity, perks, inventory, aim_target, player_state) in player_query.iter() {
+        let Some((gun_transform, mut gun_timer, gun_type, bullet_stats, gun_stats)) = inventory
+            .guns
+            .get(inventory.active_gun_index)
+            .and_then(|gun| gun_query.get_mut(*gun).ok())
+        else {
+            continue;
+        };
+        if !aim_mode.is_manual() && aim_target.0.is_none() {
+            continue;
         }
         gun_timer.0.tick(time.delta());
 
-        if gun_timer.0.elapsed_secs() < gun_stats.firing_interval {
-            return;
+        if gun_timer.0.elapsed_secs() < gun_stats.firing_interval * perks.firing_interval_multiplier
+            || (aim_mode.is_manual()
+                && !player_state
+                    .unwrap_or(&action_state)
+                    .pressed(&CombatAction::Fire))
+        {
+            continue;
         }
         ew.send(AudioEvent::Fire);
         gun_timer.0.reset();
@@ -186,43 +234,50 @@ fn handle_gun_firing(
         match gun_type {
             GunType::SingleDirectionSpread => fire_bullets(
                 &mut commands,
+                player_entity,
                 gun_pos,
                 *bullet_direction,
-                gun_stats.bullets_per_shot,
+                gun_stats.bullets_per_shot + perks.extra_bullets,
                 gun_stats.bullet_spread,
                 bullet_stats,
                 &handle,
                 80..=83,
                 *gun_type,
+                &mut rng.combat,
             ),
             GunType::OmniSpread => fire_omni_bullets(
                 &mut commands,
+                player_entity,
                 gun_pos,
-                gun_stats.bullets_per_shot,
+                gun_stats.bullets_per_shot + perks.extra_bullets,
                 bullet_stats,
                 &handle,
                 84..=87,
                 *gun_type,
+                &mut rng.combat,
             ),
             GunType::FocusedAim => {
                 fire_bullets(
                     &mut commands,
+                    player_entity,
                     gun_pos,
                     *bullet_direction,
-                    1,
+                    1 + perks.extra_bullets,
                     0.0,
                     bullet_stats,
                     &handle,
                     84..=87,
                     *gun_type,
+                    &mut rng.combat,
                 );
             }
         }
     }
 }
 
-fn fire_bullets(
+pub fn fire_bullets(
     commands: &mut Commands,
+    owner: Entity,
     gun_pos: Vec2,
     bullet_direction: Vec3,
     bullets_per_shot: usize,
@@ -231,9 +286,8 @@ fn fire_bullets(
     handle: &GlobalTextureAtlas,
     texture_index_range: RangeInclusive<usize>,
     gun_type: GunType,
+    rng: &mut impl Rng,
 ) {
-    let mut rng = rand::thread_rng();
-
     for _ in 0..bullets_per_shot {
         let dir = vec3(
             bullet_direction.x + rng.gen_range(-bullet_spread..=bullet_spread),
@@ -254,6 +308,7 @@ fn fire_bullets(
             Transform::from_translation(vec3(gun_pos.x, gun_pos.y, LAYER4))
                 .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
             Bullet,
+            BulletOwner(owner),
             BulletDirection(dir),
             BulletStats {
                 speed: bullet_stats.speed,
@@ -269,15 +324,16 @@ fn fire_bullets(
 
 fn fire_omni_bullets(
     commands: &mut Commands,
+    owner: Entity,
     gun_pos: Vec2,
     bullets_per_shot: usize,
     bullet_stats: &BulletStats,
     handle: &GlobalTextureAtlas,
     texture_index_range: RangeInclusive<usize>,
     gun_type: GunType,
+    rng: &mut impl Rng,
 ) {
     let angle_step = 360.0 / bullets_per_shot as f32;
-    let mut rng = rand::thread_rng();
 
     for i in 0..bullets_per_shot {
         let angle = i as f32 * angle_step;
@@ -297,6 +353,7 @@ fn fire_omni_bullets(
             Transform::from_translation(vec3(gun_pos.x, gun_pos.y, LAYER4))
                 .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
             Bullet,
+            BulletOwner(owner),
             BulletDirection(dir),
             BulletStats {
                 speed: bullet_stats.speed,
@@ -311,19 +368,27 @@ fn fire_omni_bullets(
 }
 
 fn switch_gun(
-    mut player_query: Query<(&mut PlayerInventory, &Transform), With<Player>>,
-    action_state: Res<ActionState<Action>>,
+    mut player_query: Query<
+        (
+            &mut PlayerInventory,
+            &Transform,
+            Option<&ActionState<CombatAction>>,
+        ),
+        With<Player>,
+    >,
+    action_state: Res<ActionState<CombatAction>>,
     mut commands: Commands,
     mut gun_query: Query<(&mut Transform, &mut Visibility, Entity), (With<Gun>, Without<Player>)>,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let (mut inventory, player_transform) = player_query.single_mut();
+    for (mut inventory, player_transform, player_state) in player_query.iter_mut() {
+        if !player_state
+            .unwrap_or(&action_state)
+            .just_pressed(&CombatAction::SwitchGun)
+        {
+            continue;
+        }
 
-    if action_state.just_pressed(&Action::SwitchGun) {
         inventory.active_gun_index = (inventory.active_gun_index + 1) % inventory.guns.len();
         for (gun_index, gun_entity) in inventory.guns.iter().enumerate() {
             if let Ok((mut gun_transform, mut gun_visibility, entity)) =
@@ -348,40 +413,54 @@ fn switch_gun(
 }
 
 fn move_bullets(
+    time: Res<Time>,
     mut bullet_query: Query<
-        (&mut Transform, &mut BulletDirection, &BulletStats, &GunType),
+        (
+            &mut Transform,
+            &mut BulletDirection,
+            &BulletStats,
+            &GunType,
+            &BulletOwner,
+        ),
         With<Bullet>,
     >,
     enemy_kd_tree: Res<EnemyKdTree>,
+    target_query: Query<&Transform, (With<Enemy>, Without<Bullet>)>,
+    aim_target_query: Query<&AimTarget>,
+    aim_mode: Res<AimMode>,
 ) {
-    if bullet_query.is_empty() {
-        return;
-    }
-
-    for (mut bullet_transform, mut bullet_direction, bullet_stats, gun_type) in
+    for (mut bullet_transform, mut bullet_direction, bullet_stats, gun_type, owner) in
         bullet_query.iter_mut()
     {
         match gun_type {
             GunType::SingleDirectionSpread => {
-                bullet_transform.translation +=
-                    bullet_direction.0.normalize() * Vec3::splat(bullet_stats.speed as f32);
+                bullet_transform.translation += bullet_direction.0.normalize()
+                    * Vec3::splat(bullet_stats.speed as f32 * time.delta_secs());
                 bullet_transform.translation.z = LAYER5;
             }
             GunType::OmniSpread => {
-                bullet_transform.translation +=
-                    bullet_direction.0.normalize() * Vec3::splat(bullet_stats.speed as f32);
+                bullet_transform.translation += bullet_direction.0.normalize()
+                    * Vec3::splat(bullet_stats.speed as f32 * time.delta_secs());
                 bullet_transform.translation.z = LAYER5;
             }
             GunType::FocusedAim => {
                 let bullet_pos = bullet_transform.translation.truncate();
-                if let Some(nearest_enemy_pos) =
-                    get_nearest_enemy_position(bullet_pos, &enemy_kd_tree, 300.0)
-                {
-                    let new_direction = (nearest_enemy_pos - bullet_pos).normalize();
+                let target_pos = aim_target_query
+                    .get(owner.0)
+                    .ok()
+                    .filter(|_| !aim_mode.is_manual())
+                    .and_then(|aim_target| aim_target.0)
+                    .and_then(|target| target_query.get(target).ok())
+                    .map(|transform| transform.translation.truncate())
+                    .filter(|target_pos| target_pos.distance(bullet_pos) <= HOMING_RADIUS);
+                if let Some(homing_pos) = target_pos.or_else(|| {
+                    get_nearest_enemy_position(bullet_pos, &enemy_kd_tree, HOMING_RADIUS)
+                }) {
+                    let new_direction = (homing_pos - bullet_pos).normalize();
                     bullet_direction.0 = vec3(new_direction.x, new_direction.y, 0.0);
                 }
-                bullet_transform.translation +=
-                    bullet_direction.0.normalize() * Vec3::splat(bullet_stats.speed as f32);
+                bullet_transform.translation += bullet_direction.0.normalize()
+                    * Vec3::splat(bullet_stats.speed as f32 * time.delta_secs());
                 bullet_transform.translation.z = LAYER5;
             }
         }
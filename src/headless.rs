@@ -0,0 +1,68 @@
+use bevy::{input::InputPlugin, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use crate::{
+    audio::AudioEvent,
+    configs::HEADLESS_MAX_INIT_UPDATES,
+    game_state::GameState,
+    plugins::GameplayPlugins,
+    resources::{GameMode, GlobalTextureAtlas, UiFont},
+    rng::RunSeed,
+    ui::components::LootSaleEvent,
+};
+
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+            .add_plugins(GameplayPlugins)
+            .add_event::<AudioEvent>()
+            .add_event::<LootSaleEvent>()
+            .insert_resource(GlobalTextureAtlas {
+                layout_16x16: Some(Handle::default()),
+                layout_32x32: Some(Handle::default()),
+                image: Some(Handle::default()),
+            })
+            .insert_resource(UiFont(Handle::default()))
+            .init_resource::<GameMode>()
+            .insert_state(GameState::Initializing);
+
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    }
+}
+
+pub fn headless_app(game_mode: GameMode, seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin)
+        .insert_resource(game_mode)
+        .insert_resource(RunSeed(Some(seed)));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+pub fn step_combat(app: &mut App, ticks: u32) -> u32 {
+    let mut init_updates = 0;
+    while current_state(app) == GameState::Initializing {
+        if init_updates == HEADLESS_MAX_INIT_UPDATES {
+            panic!(
+                "Headless app still initializing after {} updates",
+                HEADLESS_MAX_INIT_UPDATES
+            );
+        }
+        app.update();
+        init_updates += 1;
+    }
+
+    let mut stepped = 0;
+    while stepped < ticks && current_state(app) == GameState::Combat {
+        app.update();
+        stepped += 1;
+    }
+    stepped
+}
+
+pub fn current_state(app: &App) -> GameState {
+    *app.world().resource::<State<GameState>>().get()
+}
@@ -1,58 +1,296 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    state::state::StateTransitionSteps,
+    utils::{Duration, HashMap},
+};
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
-pub enum Action {
+use crate::{
+    bindings::Bindings,
+    configs::{
+        GAMEPAD_DEVICE_DEADZONE, INPUT_BUFFER_WINDOW, NAVIGATION_REPEAT_DELAY,
+        NAVIGATION_REPEAT_INTERVAL,
+    },
+    game_state::GameState,
+};
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
+pub enum CombatAction {
     #[actionlike(DualAxis)]
     Move,
+    #[actionlike(DualAxis)]
+    Aim,
+    Fire,
+    Dash,
     SwitchGun,
-    TogglePause,
     SwitchArmor,
-    Confirm,
-    NavigateUp,
-    NavigateDown,
-    NavigationLeft,
-    NavigationRight,
     UsePotion1,
     UsePotion2,
+    CycleAimMode,
+    CycleTargetPriority,
+    TogglePause,
+    ToggleShop,
     ToggleLootBoard,
+}
+
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
+pub enum MenuAction {
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+    Confirm,
+    Back,
     SellLoot,
     ToggleShop,
+    ToggleLootBoard,
+}
+
+impl CombatAction {
+    pub const BUTTONS: [CombatAction; 11] = [
+        CombatAction::Fire,
+        CombatAction::Dash,
+        CombatAction::SwitchGun,
+        CombatAction::SwitchArmor,
+        CombatAction::UsePotion1,
+        CombatAction::UsePotion2,
+        CombatAction::CycleAimMode,
+        CombatAction::CycleTargetPriority,
+        CombatAction::TogglePause,
+        CombatAction::ToggleShop,
+        CombatAction::ToggleLootBoard,
+    ];
+}
+
+pub fn set_combat_input(
+    action_state: &mut ActionState<CombatAction>,
+    pressed: &[CombatAction],
+    movement: Vec2,
+    aim: Vec2,
+) {
+    for action in CombatAction::BUTTONS {
+        if pressed.contains(&action) {
+            action_state.press(&action);
+        } else {
+            action_state.release(&action);
+        }
+    }
+    action_state.set_axis_pair(&CombatAction::Move, movement);
+    action_state.set_axis_pair(&CombatAction::Aim, aim);
+}
+
+impl MenuAction {
+    pub const BUTTONS: [MenuAction; 9] = [
+        MenuAction::NavigateUp,
+        MenuAction::NavigateDown,
+        MenuAction::NavigateLeft,
+        MenuAction::NavigateRight,
+        MenuAction::Confirm,
+        MenuAction::Back,
+        MenuAction::SellLoot,
+        MenuAction::ToggleShop,
+        MenuAction::ToggleLootBoard,
+    ];
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputContext {
+    Combat,
+    #[default]
+    Menu,
+}
+
+impl InputContext {
+    pub fn for_state(state: GameState) -> Self {
+        match state {
+            GameState::Combat => InputContext::Combat,
+            _ => InputContext::Menu,
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Resource, Default)]
+pub struct NavigationRepeat {
+    timers: HashMap<MenuAction, Timer>,
+    triggered: Vec<MenuAction>,
 }
 
-impl Action {
-    fn input_map() -> InputMap<Self> {
-        InputMap::new([
-            (Self::SwitchGun, KeyCode::KeyQ),
-            (Self::TogglePause, KeyCode::KeyP),
-            (Self::TogglePause, KeyCode::Escape),
-            (Self::SwitchArmor, KeyCode::KeyZ),
-            (Self::Confirm, KeyCode::Enter),
-            (Self::NavigateUp, KeyCode::ArrowUp),
-            (Self::NavigateUp, KeyCode::KeyW),
-            (Self::NavigateDown, KeyCode::ArrowDown),
-            (Self::NavigateDown, KeyCode::KeyS),
-            (Self::NavigationLeft, KeyCode::ArrowLeft),
-            (Self::NavigationLeft, KeyCode::KeyA),
-            (Self::NavigationRight, KeyCode::ArrowRight),
-            (Self::NavigationRight, KeyCode::KeyD),
-            (Self::UsePotion1, KeyCode::Digit1),
-            (Self::UsePotion2, KeyCode::Digit2),
-            (Self::ToggleLootBoard, KeyCode::Tab),
-            (Self::SellLoot, KeyCode::Delete),
-            (Self::ToggleShop, KeyCode::KeyO),
-        ])
-        .with_dual_axis(
-            Self::Move,
-            VirtualDPad::new(KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD),
-        )
+impl NavigationRepeat {
+    pub fn triggered(&self, action: MenuAction) -> bool {
+        self.triggered.contains(&action)
     }
 }
+
+#[derive(Resource, Component, Default)]
+pub struct InputBuffer {
+    presses: HashMap<CombatAction, Timer>,
+}
+
+impl InputBuffer {
+    pub fn consume(&mut self, action: CombatAction) -> bool {
+        self.presses.remove(&action).is_some()
+    }
+
+    fn update(&mut self, delta: Duration, action_state: &ActionState<CombatAction>) {
+        self.presses
+            .retain(|_, timer| !timer.tick(delta).finished());
+        for action in CombatAction::BUTTONS {
+            if action_state.just_pressed(&action) {
+                self.presses.insert(
+                    action,
+                    Timer::from_seconds(INPUT_BUFFER_WINDOW, TimerMode::Once),
+                );
+            }
+        }
+    }
+}
+
 pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(InputManagerPlugin::<Action>::default())
-            .init_resource::<ActionState<Action>>()
-            .insert_resource(Action::input_map());
+        app.add_plugins(InputManagerPlugin::<CombatAction>::default())
+            .add_plugins(InputManagerPlugin::<MenuAction>::default())
+            .init_resource::<ActionState<CombatAction>>()
+            .init_resource::<ActionState<MenuAction>>()
+            .init_resource::<InputContext>()
+            .init_resource::<InputDevice>()
+            .init_resource::<NavigationRepeat>()
+            .init_resource::<InputBuffer>()
+            .init_resource::<Bindings>()
+            .insert_resource(Bindings::default().combat_input_map())
+            .insert_resource(Bindings::default().menu_input_map())
+            .add_systems(
+                PreUpdate,
+                (
+                    detect_input_device,
+                    update_navigation_repeat,
+                    update_input_buffer,
+                )
+                    .after(InputManagerSystem::Update),
+            )
+            .add_systems(
+                StateTransition,
+                switch_input_context
+                    .after(StateTransitionSteps::EnterSchedules)
+                    .run_if(state_changed::<GameState>),
+            )
+            .add_systems(
+                Update,
+                apply_bindings
+                    .run_if(resource_changed::<Bindings>.and(not(resource_added::<Bindings>))),
+            );
+    }
+}
+
+pub fn apply_bindings(mut commands: Commands, bindings: Res<Bindings>) {
+    commands.insert_resource(bindings.combat_input_map());
+    commands.insert_resource(bindings.menu_input_map());
+}
+
+fn switch_input_context(
+    state: Res<State<GameState>>,
+    mut context: ResMut<InputContext>,
+    mut combat_state: ResMut<ActionState<CombatAction>>,
+    mut menu_state: ResMut<ActionState<MenuAction>>,
+    mut repeat: ResMut<NavigationRepeat>,
+    mut buffer: ResMut<InputBuffer>,
+    mut player_inputs: Query<(&mut ActionState<CombatAction>, &mut InputBuffer)>,
+) {
+    *context = InputContext::for_state(*state.get());
+    match *context {
+        InputContext::Combat => menu_state.disable_all(),
+        InputContext::Menu => menu_state.enable_all(),
+    }
+    for action in MenuAction::BUTTONS {
+        menu_state.consume(&action);
+    }
+    *repeat = NavigationRepeat::default();
+
+    reset_combat_state(&mut combat_state, *context);
+    *buffer = InputBuffer::default();
+    for (mut combat_state, mut buffer) in player_inputs.iter_mut() {
+        reset_combat_state(&mut combat_state, *context);
+        *buffer = InputBuffer::default();
+    }
+}
+
+fn reset_combat_state(combat_state: &mut ActionState<CombatAction>, context: InputContext) {
+    match context {
+        InputContext::Combat => combat_state.enable_all(),
+        InputContext::Menu => combat_state.disable_all(),
+    }
+    for action in CombatAction::BUTTONS {
+        combat_state.consume(&action);
+    }
+}
+
+fn detect_input_device(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut device: ResMut<InputDevice>,
+) {
+    if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some() {
+        device.set_if_neq(InputDevice::Keyboard);
+    }
+    for gamepad in gamepads.iter() {
+        if gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > GAMEPAD_DEVICE_DEADZONE
+            || gamepad.right_stick().length() > GAMEPAD_DEVICE_DEADZONE
+        {
+            device.set_if_neq(InputDevice::Gamepad);
+        }
+    }
+}
+
+pub fn update_navigation_repeat(
+    time: Res<Time<Real>>,
+    action_state: Res<ActionState<MenuAction>>,
+    mut repeat: ResMut<NavigationRepeat>,
+) {
+    repeat.triggered.clear();
+    for action in [
+        MenuAction::NavigateUp,
+        MenuAction::NavigateDown,
+        MenuAction::NavigateLeft,
+        MenuAction::NavigateRight,
+    ] {
+        if action_state.just_pressed(&action) {
+            repeat.timers.insert(
+                action,
+                Timer::from_seconds(NAVIGATION_REPEAT_DELAY, TimerMode::Once),
+            );
+            repeat.triggered.push(action);
+        } else if action_state.pressed(&action) {
+            let Some(timer) = repeat.timers.get_mut(&action) else {
+                continue;
+            };
+            if timer.tick(time.delta()).just_finished() {
+                *timer = Timer::from_seconds(NAVIGATION_REPEAT_INTERVAL, TimerMode::Once);
+                repeat.triggered.push(action);
+            }
+        } else {
+            repeat.timers.remove(&action);
+        }
+    }
+}
+
+pub fn update_input_buffer(
+    time: Res<Time>,
+    action_state: Res<ActionState<CombatAction>>,
+    mut buffer: ResMut<InputBuffer>,
+    mut player_inputs: Query<(&ActionState<CombatAction>, &mut InputBuffer)>,
+) {
+    buffer.update(time.delta(), &action_state);
+    for (action_state, mut buffer) in player_inputs.iter_mut() {
+        buffer.update(time.delta(), action_state);
     }
 }
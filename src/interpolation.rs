@@ -0,0 +1,70 @@
+use bevy::{
+    app::RunFixedMainLoopSystem,
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+};
+
+pub struct InterpolationPlugin;
+
+#[derive(Component, Default)]
+#[component(on_add = snap_interpolation)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            RunFixedMainLoop,
+            restore_simulated_translation.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+        )
+        .add_systems(FixedFirst, store_previous_translation)
+        .add_systems(FixedLast, store_current_translation)
+        .add_systems(
+            RunFixedMainLoop,
+            interpolate_translation.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+        );
+    }
+}
+
+fn snap_interpolation(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(translation) = world
+        .get::<Transform>(entity)
+        .map(|transform| transform.translation)
+    else {
+        return;
+    };
+    if let Some(mut interpolated) = world.get_mut::<Interpolated>(entity) {
+        interpolated.previous = translation;
+        interpolated.current = translation;
+    }
+}
+
+fn restore_simulated_translation(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in query.iter_mut() {
+        transform.translation = interpolated.current;
+    }
+}
+
+fn store_previous_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn store_current_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translation(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let overstep = fixed_time.overstep_fraction();
+    for (mut transform, interpolated) in query.iter_mut() {
+        transform.translation = interpolated.previous.lerp(interpolated.current, overstep);
+    }
+}
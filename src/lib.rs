@@ -1,17 +1,33 @@
+pub mod aim;
 pub mod animation;
 pub mod armor;
 pub mod audio;
+pub mod bindings;
 pub mod camera;
+pub mod class;
 pub mod collision;
 pub mod configs;
+pub mod coop;
+pub mod damage;
 pub mod enemy;
 pub mod game_state;
 pub mod gun;
+pub mod headless;
 pub mod input;
+pub mod interpolation;
 pub mod loot;
+pub mod net;
+pub mod perk;
 pub mod player;
+pub mod plugins;
 pub mod potion;
+pub mod replay;
 pub mod resources;
+pub mod rng;
+pub mod save;
+pub mod settings;
+pub mod stats;
+pub mod status;
 pub mod ui;
 pub mod utils;
 pub mod world;
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 use rand::Rng;
+use serde::Deserialize;
 
 use crate::{
     armor::{Armor, ArmorStats},
     configs::{LAYER3, SPRITE_SCALE_FACTOR},
     gun::{BulletStats, Gun, GunStats, GunType},
+    interpolation::Interpolated,
     potion::{Potion, PotionStats, PotionType},
+    rng::RngStream,
     utils::{generate_random_cool_name, get_random_position_around, Pickable},
 };
 
@@ -60,12 +63,14 @@ pub struct LootDefinition {
         Option<Handle<TextureAtlasLayout>>,
         LootStatRange,
         u32,
+        &mut RngStream,
     ),
     pub stat_range: LootStatRange,
 }
 
 #[derive(Component)]
-pub struct MovingToPlayer;
+#[require(Interpolated)]
+pub struct MovingToPlayer(pub Entity);
 
 #[derive(Component)]
 pub struct ReadyForPickup;
@@ -91,8 +96,14 @@ pub struct LootPool {
 }
 
 impl LootPool {
-    pub fn get_random_loots(&self) -> Vec<LootDefinition> {
-        let mut rng = rand::thread_rng();
+    pub fn boosted(mut self, factor: f32) -> Self {
+        for item in &mut self.items {
+            item.drop_chance = (item.drop_chance * factor).min(1.0);
+        }
+        self
+    }
+
+    pub fn get_random_loots(&self, rng: &mut impl Rng) -> Vec<LootDefinition> {
         let mut successful_loots = Vec::new();
 
         for item in &self.items {
@@ -113,8 +124,8 @@ pub fn spawn_gun_entity(
     layout: Handle<TextureAtlasLayout>,
     stat_range: GunStatRange,
     value: u32,
+    rng: &mut impl Rng,
 ) -> Entity {
-    let mut rng = rand::thread_rng();
     let bullet_stats = BulletStats {
         speed: rng.gen_range(stat_range.bullet_speed.0..=stat_range.bullet_speed.1),
         lifespan: rng.gen_range(stat_range.bullet_lifespan.0..=stat_range.bullet_lifespan.1),
@@ -176,7 +187,7 @@ pub fn spawn_gun_entity(
             },
             gun_type,
             Description {
-                name: generate_random_cool_name(LootType::Gun),
+                name: generate_random_cool_name(LootType::Gun, rng),
                 description: format!(
                     "Damage: {}; Speed: {}; Firing Interval: {:.2}; Bullet Per Shot: {}",
                     bullet_stats.damage,
@@ -199,8 +210,8 @@ pub fn spawn_armor_entity(
     layout: Handle<TextureAtlasLayout>,
     stat_range: ArmorStatRange,
     value: u32,
+    rng: &mut impl Rng,
 ) -> Entity {
-    let mut rng = rand::thread_rng();
     let armor_stats = ArmorStats {
         defense: rng.gen_range(stat_range.defense.0..=stat_range.defense.1),
         durability: rng.gen_range(stat_range.durability.0..=stat_range.durability.1),
@@ -219,7 +230,7 @@ pub fn spawn_armor_entity(
                 ..default()
             },
             Description {
-                name: generate_random_cool_name(LootType::Armor),
+                name: generate_random_cool_name(LootType::Armor, rng),
                 description: format!(
                     "Defense: {}; Durability: {}",
                     armor_stats.defense, armor_stats.durability
@@ -238,8 +249,8 @@ pub fn spawn_potion_entity(
     layout: Handle<TextureAtlasLayout>,
     stat_range: PotionStatRange,
     value: u32,
+    rng: &mut impl Rng,
 ) -> Entity {
-    let mut rng = rand::thread_rng();
     let potion_stats = PotionStats {
         effect_duration: rng.gen_range(stat_range.effect_duration.0..=stat_range.effect_duration.1),
         effect_amount: rng.gen_range(stat_range.effect_amount.0..=stat_range.effect_amount.1),
@@ -264,7 +275,7 @@ pub fn spawn_potion_entity(
                 ..default()
             },
             Description {
-                name: generate_random_cool_name(LootType::Potion),
+                name: generate_random_cool_name(LootType::Potion, rng),
                 description: match potion_type.0 {
                     PotionType::Speed => format!(
                         "Duration: {:.1}s; Amount: {}",
@@ -287,9 +298,10 @@ fn spawn_gun(
     layout: Option<Handle<TextureAtlasLayout>>,
     stat_range: LootStatRange,
     value: u32,
+    rng: &mut RngStream,
 ) {
     if let LootStatRange::Gun(range) = stat_range {
-        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0);
+        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0, rng);
         let id = spawn_gun_entity(
             commands,
             Vec3::new(x, y, LAYER3),
@@ -297,6 +309,7 @@ fn spawn_gun(
             layout.unwrap(),
             range,
             value,
+            rng,
         );
         commands.entity(id).insert(Pickable);
     }
@@ -309,9 +322,10 @@ fn spawn_armor(
     layout: Option<Handle<TextureAtlasLayout>>,
     stat_range: LootStatRange,
     value: u32,
+    rng: &mut RngStream,
 ) {
     if let LootStatRange::Armor(range) = stat_range {
-        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0);
+        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0, rng);
         let id = spawn_armor_entity(
             commands,
             Vec3::new(x, y, LAYER3),
@@ -319,6 +333,7 @@ fn spawn_armor(
             layout.unwrap(),
             range,
             value,
+            rng,
         );
         commands.entity(id).insert(Pickable);
     }
@@ -331,9 +346,10 @@ fn spawn_potion(
     layout: Option<Handle<TextureAtlasLayout>>,
     stat_range: LootStatRange,
     value: u32,
+    rng: &mut RngStream,
 ) {
     if let LootStatRange::Potion(range) = stat_range {
-        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0);
+        let (x, y) = get_random_position_around(transform.translation.xy(), 30.0..60.0, rng);
         let id = spawn_potion_entity(
             commands,
             Vec3::new(x, y, LAYER3),
@@ -341,11 +357,39 @@ fn spawn_potion(
             layout.unwrap(),
             range,
             value,
+            rng,
         );
         commands.entity(id).insert(Pickable);
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum LootTier {
+    Weak,
+    Medium,
+    Strong,
+    Boss,
+    Tempest,
+    Queen,
+    Wyrm,
+    Warden,
+}
+
+impl LootTier {
+    pub fn loot_pool(&self) -> LootPool {
+        match self {
+            LootTier::Weak => weak_enemies_loots(),
+            LootTier::Medium => medium_enemies_loots(),
+            LootTier::Strong => strong_enemies_loots(),
+            LootTier::Boss => boss_enemy_loots(),
+            LootTier::Tempest => tempest_boss_loots(),
+            LootTier::Queen => queen_boss_loots(),
+            LootTier::Wyrm => wyrm_boss_loots(),
+            LootTier::Warden => warden_boss_loots(),
+        }
+    }
+}
+
 pub fn weak_enemies_loots() -> LootPool {
     LootPool {
         items: vec![
@@ -368,7 +412,7 @@ pub fn weak_enemies_loots() -> LootPool {
                     bullets_per_shot: (10, 20),
                     firing_interval: (0.3, 0.5),
                     bullet_spread: (0.15, 0.2),
-                    bullet_speed: (10, 20),
+                    bullet_speed: (600, 1200),
                     bullet_lifespan: (1.0, 2.0),
                     bullet_damage: (30, 40),
                 }),
@@ -411,7 +455,7 @@ pub fn medium_enemies_loots() -> LootPool {
                     bullet_spread: (0.1, 0.15),
                     bullet_damage: (50, 100),
                     bullet_lifespan: (1.5, 3.0),
-                    bullet_speed: (20, 30),
+                    bullet_speed: (1200, 1800),
                 }),
             },
         ],
@@ -442,7 +486,7 @@ pub fn strong_enemies_loots() -> LootPool {
                     bullet_spread: (0.05, 0.15),
                     bullet_damage: (70, 120),
                     bullet_lifespan: (2.0, 4.0),
-                    bullet_speed: (30, 50),
+                    bullet_speed: (1800, 3000),
                 }),
             },
             LootDefinition {
@@ -483,7 +527,7 @@ pub fn boss_enemy_loots() -> LootPool {
                     bullet_spread: (0.05, 0.1),
                     bullet_damage: (140, 200),
                     bullet_lifespan: (3.0, 5.0),
-                    bullet_speed: (50, 80),
+                    bullet_speed: (3000, 4800),
                 }),
             },
             LootDefinition {
@@ -499,3 +543,83 @@ pub fn boss_enemy_loots() -> LootPool {
         ],
     }
 }
+
+pub fn tempest_boss_loots() -> LootPool {
+    let mut loot_pool = boss_enemy_loots();
+    loot_pool.items.push(LootDefinition {
+        loot_type: LootType::Gun,
+        drop_chance: 0.35,
+        value: 60,
+        spawn_fn: spawn_gun,
+        stat_range: LootStatRange::Gun(GunStatRange {
+            bullets_per_shot: (90, 120),
+            firing_interval: (0.08, 0.15),
+            bullet_spread: (0.2, 0.3),
+            bullet_damage: (120, 180),
+            bullet_lifespan: (2.0, 3.0),
+            bullet_speed: (2400, 3600),
+        }),
+    });
+    loot_pool
+}
+
+pub fn queen_boss_loots() -> LootPool {
+    let mut loot_pool = boss_enemy_loots();
+    loot_pool.items.push(LootDefinition {
+        loot_type: LootType::Potion,
+        drop_chance: 0.8,
+        value: 25,
+        spawn_fn: spawn_potion,
+        stat_range: LootStatRange::Potion(PotionStatRange {
+            effect_duration: (15.0, 25.0),
+            effect_amount: (15, 30),
+        }),
+    });
+    loot_pool
+}
+
+pub fn wyrm_boss_loots() -> LootPool {
+    let mut loot_pool = boss_enemy_loots();
+    loot_pool.items.push(LootDefinition {
+        loot_type: LootType::Armor,
+        drop_chance: 0.35,
+        value: 60,
+        spawn_fn: spawn_armor,
+        stat_range: LootStatRange::Armor(ArmorStatRange {
+            defense: (5, 9),
+            durability: (50, 90),
+        }),
+    });
+    loot_pool
+}
+
+pub fn warden_boss_loots() -> LootPool {
+    let mut loot_pool = boss_enemy_loots();
+    loot_pool.items.extend([
+        LootDefinition {
+            loot_type: LootType::Gun,
+            drop_chance: 0.25,
+            value: 60,
+            spawn_fn: spawn_gun,
+            stat_range: LootStatRange::Gun(GunStatRange {
+                bullets_per_shot: (50, 70),
+                firing_interval: (0.1, 0.2),
+                bullet_spread: (0.02, 0.06),
+                bullet_damage: (180, 260),
+                bullet_lifespan: (4.0, 6.0),
+                bullet_speed: (4000, 5400),
+            }),
+        },
+        LootDefinition {
+            loot_type: LootType::Armor,
+            drop_chance: 0.25,
+            value: 60,
+            spawn_fn: spawn_armor,
+            stat_range: LootStatRange::Armor(ArmorStatRange {
+                defense: (4, 8),
+                durability: (40, 70),
+            }),
+        },
+    ]);
+    loot_pool
+}
@@ -10,22 +10,23 @@ use bevy::{
 #[cfg(debug_assertions)]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_kira_audio::AudioPlugin;
+#[cfg(debug_assertions)]
+use fishmans_adventure::configs::INSPECTOR_TOGGLE_KEY;
 use fishmans_adventure::{
     animation::AnimationPlugin,
-    armor::ArmorPlugin,
     audio::GameAudioPlugin,
     camera::FollowCameraPlugin,
-    collision::CollisionPlugin,
     configs::{BG_COLOR, WH, WW},
-    enemy::plugin::EnemyPlugin,
     game_state::GameState,
-    gun::GunPlugin,
-    input::InputPlugin,
-    player::{plugin::PlayerPlugin, PlayerInventory},
-    potion::PotionPlugin,
-    resources::{GameMode, ResourcesPlugin},
+    interpolation::InterpolationPlugin,
+    net::NetPlugin,
+    player::PlayerInventory,
+    plugins::GameplayPlugins,
+    replay::ReplayPlugin,
+    resources::GameMode,
+    save::SavePlugin,
+    settings::SettingsPlugin,
     ui::{components::GridSlot, plugin::UiPlugin},
-    world::WorldPlugin,
 };
 use wasm_bindgen::prelude::wasm_bindgen;
 
@@ -71,21 +72,18 @@ fn main() {
         .add_plugins(GameAudioPlugin)
         .add_plugins(AudioPlugin)
         .add_plugins(FollowCameraPlugin)
-        .add_plugins(GunPlugin)
-        .add_plugins(PlayerPlugin)
         .add_plugins(AnimationPlugin)
-        .add_plugins(ResourcesPlugin)
-        .add_plugins(WorldPlugin)
-        .add_plugins(EnemyPlugin)
-        .add_plugins(CollisionPlugin)
-        .add_plugins(PotionPlugin)
-        .add_plugins(ArmorPlugin)
-        .add_plugins(InputPlugin)
+        .add_plugins(GameplayPlugins)
+        .add_plugins(SavePlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(InterpolationPlugin)
+        .add_plugins(NetPlugin)
         .init_state::<GameState>()
         .init_resource::<GameMode>();
     #[cfg(debug_assertions)]
     app.add_plugins(
-        WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::Escape)),
+        WorldInspectorPlugin::default().run_if(input_toggle_active(false, INSPECTOR_TOGGLE_KEY)),
     );
 
     #[cfg(target_arch = "wasm32")]
@@ -0,0 +1,832 @@
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    class::Familiar,
+    configs::{
+        LAYER1, LAYER2, NET_CLOCK_SNAP_THRESHOLD, NET_CONNECT_ENV_VAR, NET_DEFAULT_PORT,
+        NET_HELLO_INTERVAL, NET_HOST_ENV_VAR, NET_INTERPOLATION_DELAY, NET_MAX_PACKET_SIZE,
+        NET_MAX_SNAPSHOT_BYTES, NET_SNAPSHOT_INTERVAL, NET_TIMEOUT,
+    },
+    coop::CoopSession,
+    enemy::{BossTelegraph, Enemy, EnemyBullet, TrailSegment},
+    game_state::{GameState, GameplaySet},
+    gun::Bullet,
+    input::{set_combat_input, update_input_buffer, CombatAction},
+    loot::Description,
+    player::{Health, Player},
+    resources::{GlobalTextureAtlas, Level, Wave},
+    utils::InGameEntity,
+};
+
+pub struct NetPlugin;
+
+#[derive(Component)]
+pub struct RemotePlayer;
+
+#[derive(Component)]
+pub struct NetProxy {
+    pub id: u64,
+    pub health: Option<[u32; 2]>,
+}
+
+#[derive(Component)]
+pub struct NetPlayerProxy;
+
+#[derive(Component)]
+struct NetHealthBar;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetKind {
+    Player,
+    Enemy,
+    Bullet,
+    Item,
+    Effect,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetPhase {
+    Lobby,
+    Running,
+    Lost,
+    Won,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NetEntity {
+    pub id: u64,
+    pub kind: NetKind,
+    pub position: [f32; 3],
+    pub rotation: f32,
+    pub scale: f32,
+    pub large: bool,
+    pub index: Option<usize>,
+    pub custom_size: Option<[f32; 2]>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub color: [f32; 4],
+    pub health: Option<[u32; 2]>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub time: f64,
+    pub phase: NetPhase,
+    pub wave: u32,
+    pub entities: Vec<NetEntity>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NetInput {
+    pub sequence: u32,
+    pub pressed: Vec<CombatAction>,
+    pub movement: [f32; 2],
+    pub aim: [f32; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+enum NetMessage {
+    Hello,
+    Welcome,
+    Input(NetInput),
+    Snapshot(Snapshot),
+}
+
+#[derive(Resource)]
+pub struct NetHost {
+    socket: UdpSocket,
+    client: Option<SocketAddr>,
+    last_heard: f64,
+    input: Option<NetInput>,
+    last_sequence: u32,
+    snapshot_timer: Timer,
+}
+
+impl NetHost {
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            client: None,
+            last_heard: 0.0,
+            input: None,
+            last_sequence: 0,
+            snapshot_timer: Timer::from_seconds(NET_SNAPSHOT_INTERVAL, TimerMode::Repeating),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn client(&self) -> Option<SocketAddr> {
+        self.client
+    }
+}
+
+#[derive(Resource)]
+pub struct NetClient {
+    socket: UdpSocket,
+    host: SocketAddr,
+    connected: bool,
+    last_heard: f64,
+    hello_timer: Timer,
+    sequence: u32,
+    snapshots: VecDeque<Snapshot>,
+    render_time: Option<f64>,
+    proxies: HashMap<u64, Entity>,
+}
+
+impl NetClient {
+    pub fn connect(host: impl ToSocketAddrs) -> io::Result<Self> {
+        let host = host
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no address to connect to"))?;
+        let socket = UdpSocket::bind(if host.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        })?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            host,
+            connected: false,
+            last_heard: 0.0,
+            hello_timer: Timer::from_seconds(NET_HELLO_INTERVAL, TimerMode::Repeating),
+            sequence: 0,
+            snapshots: VecDeque::new(),
+            render_time: None,
+            proxies: HashMap::new(),
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn latest_snapshot(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+}
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (
+                GameplaySet::Status,
+                GameplaySet::Stats,
+                GameplaySet::Enemy,
+                GameplaySet::Collision,
+                GameplaySet::Damage,
+                GameplaySet::Player,
+                GameplaySet::Gun,
+            )
+                .run_if(has_authority),
+        )
+        .add_systems(
+            PreUpdate,
+            (
+                (receive_host_messages, apply_remote_input)
+                    .chain()
+                    .run_if(resource_exists::<NetHost>),
+                receive_client_messages.run_if(resource_exists::<NetClient>),
+            )
+                .after(InputManagerSystem::Update)
+                .before(update_input_buffer),
+        )
+        .add_systems(
+            FixedPreUpdate,
+            apply_remote_input
+                .after(InputManagerSystem::Update)
+                .run_if(resource_exists::<NetHost>),
+        )
+        .add_systems(
+            Update,
+            (
+                send_client_hello,
+                follow_host_phase,
+                (interpolate_snapshots, update_proxy_health_bars)
+                    .chain()
+                    .run_if(in_state(GameState::Combat)),
+            )
+                .run_if(resource_exists::<NetClient>),
+        )
+        .add_systems(
+            Last,
+            (
+                send_snapshots.run_if(resource_exists::<NetHost>),
+                send_client_input
+                    .run_if(resource_exists::<NetClient>.and(in_state(GameState::Combat))),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::Initializing),
+            start_client_run.run_if(resource_exists::<NetClient>),
+        )
+        .add_systems(
+            OnEnter(GameState::MainMenu),
+            reset_client_view.run_if(resource_exists::<NetClient>),
+        );
+
+        if let Some(port) = port_from_env() {
+            match NetHost::bind(("0.0.0.0", port)) {
+                Ok(host) => {
+                    info!("Hosting co-op on port {}", port);
+                    app.insert_resource(host);
+                }
+                Err(err) => warn!("Failed to host on port {}: {}", port, err),
+            }
+        } else if let Ok(address) = std::env::var(NET_CONNECT_ENV_VAR) {
+            match NetClient::connect(address.trim()) {
+                Ok(client) => {
+                    info!("Connecting to host at {}", client.host);
+                    app.insert_resource(client);
+                }
+                Err(err) => warn!("Failed to connect to {:?}: {}", address, err),
+            }
+        }
+    }
+}
+
+pub fn has_authority(client: Option<Res<NetClient>>) -> bool {
+    client.is_none()
+}
+
+fn port_from_env() -> Option<u16> {
+    let value = std::env::var(NET_HOST_ENV_VAR).ok()?;
+    if value.trim().is_empty() {
+        return Some(NET_DEFAULT_PORT);
+    }
+    match value.trim().parse() {
+        Ok(port) => Some(port),
+        Err(err) => {
+            warn!("Ignoring invalid {}={:?}: {}", NET_HOST_ENV_VAR, value, err);
+            None
+        }
+    }
+}
+
+fn send_message(socket: &UdpSocket, address: SocketAddr, message: &NetMessage) {
+    match ron::to_string(message) {
+        Ok(contents) => send_packet(socket, address, contents.as_bytes()),
+        Err(err) => warn!("Failed to serialize packet: {}", err),
+    }
+}
+
+fn send_packet(socket: &UdpSocket, address: SocketAddr, packet: &[u8]) {
+    if packet.len() > NET_MAX_PACKET_SIZE {
+        warn!("Dropping {} byte packet to {}", packet.len(), address);
+        return;
+    }
+    if let Err(err) = socket.send_to(packet, address) {
+        if err.kind() != ErrorKind::WouldBlock {
+            warn!("Failed to send packet to {}: {}", address, err);
+        }
+    }
+}
+
+fn receive_messages(socket: &UdpSocket) -> Vec<(SocketAddr, NetMessage)> {
+    let mut buffer = vec![0; u16::MAX as usize];
+    let mut messages = Vec::new();
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((length, address)) => {
+                let message = std::str::from_utf8(&buffer[..length])
+                    .ok()
+                    .and_then(|contents| ron::from_str(contents).ok());
+                match message {
+                    Some(message) => messages.push((address, message)),
+                    None => warn!("Ignoring malformed packet from {}", address),
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) if err.kind() == ErrorKind::ConnectionReset => continue,
+            Err(err) => {
+                warn!("Failed to receive packet: {}", err);
+                break;
+            }
+        }
+    }
+    messages
+}
+
+fn is_lobby(state: GameState) -> bool {
+    matches!(state, GameState::MainMenu | GameState::ClassSelect)
+}
+
+fn receive_host_messages(
+    mut host: ResMut<NetHost>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mut coop: ResMut<CoopSession>,
+) {
+    let now = time.elapsed_secs_f64();
+    let mut received_input = false;
+    for (address, message) in receive_messages(&host.socket) {
+        if host.client.is_some_and(|client| client != address) {
+            continue;
+        }
+        match message {
+            NetMessage::Hello => {
+                if host.client.is_none() {
+                    if !is_lobby(*state.get()) || coop.gamepad.is_some() {
+                        continue;
+                    }
+                    host.client = Some(address);
+                    host.last_sequence = 0;
+                    info!("Client {} joined", address);
+                }
+                if is_lobby(*state.get()) {
+                    coop.remote = true;
+                }
+                host.last_heard = now;
+                send_message(&host.socket, address, &NetMessage::Welcome);
+            }
+            NetMessage::Input(input) => {
+                if host.client.is_none() || input.sequence <= host.last_sequence {
+                    continue;
+                }
+                host.last_heard = now;
+                host.last_sequence = input.sequence;
+                let mut pressed = input.pressed.clone();
+                if let Some(previous) = host.input.take().filter(|_| received_input) {
+                    pressed.extend(
+                        previous
+                            .pressed
+                            .into_iter()
+                            .filter(|action| !input.pressed.contains(action)),
+                    );
+                }
+                host.input = Some(NetInput { pressed, ..input });
+                received_input = true;
+            }
+            NetMessage::Welcome | NetMessage::Snapshot(_) => {}
+        }
+    }
+
+    if let Some(client) = host.client {
+        if now - host.last_heard > NET_TIMEOUT {
+            warn!("Client {} timed out", client);
+            host.client = None;
+            host.input = None;
+            if is_lobby(*state.get()) {
+                coop.remote = false;
+            }
+        }
+    }
+}
+
+fn apply_remote_input(
+    host: Res<NetHost>,
+    mut player_query: Query<&mut ActionState<CombatAction>, With<RemotePlayer>>,
+) {
+    let input = host.input.clone().unwrap_or_default();
+    for mut action_state in player_query.iter_mut() {
+        set_combat_input(
+            &mut action_state,
+            &input.pressed,
+            Vec2::from(input.movement),
+            Vec2::from(input.aim),
+        );
+    }
+}
+
+fn send_snapshots(
+    mut host: ResMut<NetHost>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    wave: Option<Res<Wave>>,
+    handle: Res<GlobalTextureAtlas>,
+    entity_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Sprite,
+            Option<&Visibility>,
+            Option<&Health>,
+            Has<Player>,
+            Has<Enemy>,
+            Has<Bullet>,
+            Has<EnemyBullet>,
+            Has<Description>,
+        ),
+        Or<(
+            With<Player>,
+            With<Enemy>,
+            With<Bullet>,
+            With<EnemyBullet>,
+            With<Description>,
+            With<Familiar>,
+            With<TrailSegment>,
+            With<BossTelegraph>,
+        )>,
+    >,
+) {
+    host.snapshot_timer.tick(time.delta());
+    let Some(client) = host.client else {
+        return;
+    };
+    if !host.snapshot_timer.just_finished() {
+        return;
+    }
+
+    let phase = match state.get() {
+        GameState::Combat
+        | GameState::Paused
+        | GameState::Ui
+        | GameState::Shopping
+        | GameState::LevelUp => NetPhase::Running,
+        GameState::End => NetPhase::Lost,
+        GameState::Win => NetPhase::Won,
+        _ => NetPhase::Lobby,
+    };
+    let mut entities = Vec::new();
+    if phase == NetPhase::Running {
+        for (
+            entity,
+            transform,
+            sprite,
+            visibility,
+            health,
+            player,
+            enemy,
+            bullet,
+            enemy_bullet,
+            item,
+        ) in entity_query.iter()
+        {
+            if visibility == Some(&Visibility::Hidden) {
+                continue;
+            }
+            let kind = if player {
+                NetKind::Player
+            } else if enemy {
+                NetKind::Enemy
+            } else if bullet || enemy_bullet {
+                NetKind::Bullet
+            } else if item {
+                NetKind::Item
+            } else {
+                NetKind::Effect
+            };
+            let color = sprite.color.to_srgba();
+            entities.push(NetEntity {
+                id: entity.to_bits(),
+                kind,
+                position: transform.translation.to_array(),
+                rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+                scale: transform.scale.x,
+                large: sprite.texture_atlas.as_ref().is_some_and(|atlas| {
+                    handle.layout_32x32.as_ref() == Some(&atlas.layout)
+                        && handle.layout_32x32 != handle.layout_16x16
+                }),
+                index: sprite.texture_atlas.as_ref().map(|atlas| atlas.index),
+                custom_size: sprite.custom_size.map(|size| size.to_array()),
+                flip_x: sprite.flip_x,
+                flip_y: sprite.flip_y,
+                color: color.to_f32_array(),
+                health: health.filter(|_| player).map(|health| [health.0, health.1]),
+            });
+        }
+        entities.sort_by_key(|entity| entity.kind != NetKind::Player);
+    }
+
+    let snapshot = Snapshot {
+        time: time.elapsed_secs_f64(),
+        phase,
+        wave: wave.map_or(0, |wave| wave.number),
+        entities: Vec::new(),
+    };
+    let contents = match ron::to_string(&NetMessage::Snapshot(snapshot)) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Failed to serialize snapshot: {}", err);
+            return;
+        }
+    };
+    // Entities are spliced into the serialized snapshot so each one is encoded once.
+    let Some(split) = contents
+        .rfind("entities:[]")
+        .map(|index| index + "entities:[".len())
+    else {
+        warn!("Unexpected snapshot layout: {}", contents);
+        return;
+    };
+
+    let mut packet = contents.as_bytes()[..split].to_vec();
+    for entity in &entities {
+        let start = packet.len();
+        if start > split {
+            packet.push(b',');
+        }
+        if ron::ser::to_writer(&mut packet, entity).is_err()
+            || packet.len() - split > NET_MAX_SNAPSHOT_BYTES
+        {
+            packet.truncate(start);
+            break;
+        }
+    }
+    packet.extend_from_slice(&contents.as_bytes()[split..]);
+    send_packet(&host.socket, client, &packet);
+}
+
+fn send_client_hello(
+    mut client: ResMut<NetClient>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+) {
+    if client.connected && !is_lobby(*state.get()) {
+        return;
+    }
+    client.hello_timer.tick(time.delta());
+    if client.hello_timer.just_finished() {
+        send_message(&client.socket, client.host, &NetMessage::Hello);
+    }
+}
+
+fn receive_client_messages(
+    mut client: ResMut<NetClient>,
+    time: Res<Time<Real>>,
+    mut coop: ResMut<CoopSession>,
+) {
+    let now = time.elapsed_secs_f64();
+    for (address, message) in receive_messages(&client.socket) {
+        if address != client.host {
+            continue;
+        }
+        client.last_heard = now;
+        match message {
+            NetMessage::Welcome => {
+                if !client.connected {
+                    info!("Connected to host {}", address);
+                }
+                client.connected = true;
+                coop.remote = true;
+            }
+            NetMessage::Snapshot(snapshot) => {
+                if client
+                    .snapshots
+                    .back()
+                    .is_none_or(|latest| snapshot.time > latest.time)
+                {
+                    client.snapshots.push_back(snapshot);
+                }
+            }
+            NetMessage::Hello | NetMessage::Input(_) => {}
+        }
+    }
+}
+
+fn follow_host_phase(
+    mut client: ResMut<NetClient>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    wave: Option<ResMut<Wave>>,
+) {
+    if !client.connected {
+        return;
+    }
+    if time.elapsed_secs_f64() - client.last_heard > NET_TIMEOUT {
+        warn!("Lost connection to host {}", client.host);
+        client.connected = false;
+        if *state.get() != GameState::MainMenu {
+            next_state.set(GameState::MainMenu);
+        }
+        return;
+    }
+
+    let Some(snapshot) = client.latest_snapshot() else {
+        return;
+    };
+    match (snapshot.phase, state.get()) {
+        (NetPhase::Running, GameState::MainMenu | GameState::ClassSelect) => {
+            next_state.set(GameState::Initializing);
+        }
+        (NetPhase::Lobby, GameState::Combat) => next_state.set(GameState::MainMenu),
+        (NetPhase::Lost, GameState::Combat) => next_state.set(GameState::End),
+        (NetPhase::Won, GameState::Combat) => next_state.set(GameState::Win),
+        _ => {}
+    }
+    if let Some(mut wave) = wave.filter(|wave| wave.number != snapshot.wave) {
+        wave.number = snapshot.wave;
+    }
+}
+
+fn start_client_run(
+    mut commands: Commands,
+    client: Res<NetClient>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    commands.insert_resource(Wave {
+        number: client.latest_snapshot().map_or(0, |snapshot| snapshot.wave),
+    });
+    commands.insert_resource(Level::default());
+    next_state.set(GameState::Combat);
+}
+
+fn reset_client_view(mut client: ResMut<NetClient>) {
+    client.snapshots.clear();
+    client.proxies.clear();
+    client.render_time = None;
+}
+
+fn send_client_input(mut client: ResMut<NetClient>, action_state: Res<ActionState<CombatAction>>) {
+    client.sequence += 1;
+    let movement = action_state.axis_pair(&CombatAction::Move);
+    let aim = action_state.axis_pair(&CombatAction::Aim);
+    let input = NetInput {
+        sequence: client.sequence,
+        pressed: action_state.get_pressed(),
+        movement: [movement.x, movement.y],
+        aim: [aim.x, aim.y],
+    };
+    send_message(&client.socket, client.host, &NetMessage::Input(input));
+}
+
+fn interpolate_snapshots(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    time: Res<Time<Real>>,
+    handle: Res<GlobalTextureAtlas>,
+    mut proxy_query: Query<(&mut NetProxy, &mut Transform, &mut Sprite)>,
+) {
+    let Some(latest_time) = client.latest_snapshot().map(|snapshot| snapshot.time) else {
+        return;
+    };
+    let target_time = latest_time - NET_INTERPOLATION_DELAY;
+    let render_time = match client.render_time {
+        Some(render_time) => render_time + time.delta_secs_f64(),
+        None => target_time,
+    };
+    let render_time = if (render_time - target_time).abs() > NET_CLOCK_SNAP_THRESHOLD {
+        target_time
+    } else {
+        render_time
+    };
+    client.render_time = Some(render_time);
+
+    while client.snapshots.len() > 2 && client.snapshots[1].time <= render_time {
+        client.snapshots.pop_front();
+    }
+    let client = client.into_inner();
+    let (from, to) = match (client.snapshots.front(), client.snapshots.get(1)) {
+        (Some(from), Some(to)) if from.time <= render_time => (from, to),
+        (Some(from), _) => (from, from),
+        _ => return,
+    };
+    let progress = if to.time > from.time {
+        ((render_time - from.time) / (to.time - from.time)).clamp(0.0, 1.0) as f32
+    } else {
+        1.0
+    };
+    let previous: HashMap<u64, &NetEntity> = from
+        .entities
+        .iter()
+        .map(|entity| (entity.id, entity))
+        .collect();
+
+    let mut seen = Vec::with_capacity(to.entities.len());
+    for entity in to.entities.iter() {
+        seen.push(entity.id);
+        let start = previous.get(&entity.id).copied().unwrap_or(entity);
+        let translation = Vec3::from(start.position).lerp(Vec3::from(entity.position), progress);
+        let rotation = Quat::from_rotation_z(start.rotation)
+            .slerp(Quat::from_rotation_z(entity.rotation), progress);
+        let transform = Transform::from_translation(translation)
+            .with_rotation(rotation)
+            .with_scale(Vec3::splat(entity.scale));
+
+        let proxy = client
+            .proxies
+            .get(&entity.id)
+            .and_then(|&proxy| proxy_query.get_mut(proxy).ok());
+        match proxy {
+            Some((mut proxy, mut proxy_transform, mut sprite)) => {
+                proxy.health = entity.health;
+                *proxy_transform = transform;
+                apply_net_sprite(&mut sprite, &handle, entity);
+            }
+            None if client
+                .proxies
+                .get(&entity.id)
+                .is_some_and(|&proxy| commands.get_entity(proxy).is_some()) => {}
+            None => {
+                let mut sprite = Sprite::default();
+                apply_net_sprite(&mut sprite, &handle, entity);
+                let proxy = spawn_proxy(&mut commands, entity, sprite, transform);
+                client.proxies.insert(entity.id, proxy);
+            }
+        }
+    }
+
+    client.proxies.retain(|id, proxy| {
+        let keep = seen.contains(id);
+        if !keep {
+            if let Some(proxy) = commands.get_entity(*proxy) {
+                proxy.despawn_recursive();
+            }
+        }
+        keep
+    });
+}
+
+fn apply_net_sprite(sprite: &mut Sprite, handle: &GlobalTextureAtlas, entity: &NetEntity) {
+    let layout = if entity.large {
+        handle.layout_32x32.clone()
+    } else {
+        handle.layout_16x16.clone()
+    };
+    match (entity.index, handle.image.clone(), layout) {
+        (Some(index), Some(image), Some(layout)) => {
+            sprite.image = image;
+            match sprite.texture_atlas.as_mut() {
+                Some(atlas) => {
+                    atlas.layout = layout;
+                    atlas.index = index;
+                }
+                None => sprite.texture_atlas = Some(TextureAtlas { layout, index }),
+            }
+        }
+        _ => sprite.texture_atlas = None,
+    }
+    sprite.custom_size = entity.custom_size.map(Vec2::from);
+    sprite.flip_x = entity.flip_x;
+    sprite.flip_y = entity.flip_y;
+    sprite.color = Color::srgba(
+        entity.color[0],
+        entity.color[1],
+        entity.color[2],
+        entity.color[3],
+    );
+}
+
+fn spawn_proxy(
+    commands: &mut Commands,
+    entity: &NetEntity,
+    sprite: Sprite,
+    transform: Transform,
+) -> Entity {
+    let mut proxy = commands.spawn((
+        Name::new(format!("NetProxy {:?}", entity.kind)),
+        NetProxy {
+            id: entity.id,
+            health: entity.health,
+        },
+        sprite,
+        transform,
+        InGameEntity,
+    ));
+    if entity.kind == NetKind::Player {
+        proxy.insert(NetPlayerProxy).with_children(|parent| {
+            parent.spawn((
+                Sprite {
+                    color: Color::linear_rgb(0.5, 0.5, 0.5),
+                    custom_size: Some(Vec2::new(12.0, 3.0)),
+                    ..default()
+                },
+                Transform::from_translation(Vec3::new(0.0, 4.0, LAYER1)),
+            ));
+            parent.spawn((
+                Sprite {
+                    color: Color::linear_rgb(0.0, 1.0, 0.0),
+                    custom_size: Some(Vec2::new(12.0, 3.0)),
+                    ..default()
+                },
+                Transform::from_translation(Vec3::new(0.0, 4.0, LAYER2)),
+                NetHealthBar,
+            ));
+        });
+    }
+    proxy.id()
+}
+
+fn update_proxy_health_bars(
+    proxy_query: Query<&NetProxy, Changed<NetProxy>>,
+    mut bar_query: Query<(&mut Transform, &mut Sprite, &Parent), With<NetHealthBar>>,
+) {
+    for (mut transform, mut sprite, parent) in bar_query.iter_mut() {
+        let Some([health, max_health]) = proxy_query
+            .get(parent.get())
+            .ok()
+            .and_then(|proxy| proxy.health)
+        else {
+            continue;
+        };
+        let health_percentage = health as f32 / max_health.max(1) as f32;
+        sprite.custom_size = Some(Vec2::new(12.0 * health_percentage, 3.0));
+        transform.translation.x = -6.0 + (6.0 * health_percentage);
+    }
+}
@@ -0,0 +1,410 @@
+use bevy::{asset::AssetPlugin, prelude::*};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::AudioEvent,
+    configs::{INVENTORY_SLOTS, MAX_POTION_SLOTS, PERK_CHOICES, PLAYER_PICKUP_RADIUS},
+    game_state::GameState,
+    input::{MenuAction, NavigationRepeat},
+    player::{Downed, Health, Player, PlayerSlot},
+    rng::GameRng,
+    stats::{StatKind, Stats},
+    status::{StatusKind, StatusSpec},
+    utils::{
+        calculate_damage_boost_increase, calculate_defense_increase, calculate_health_increase,
+        pick_weighted, RonAssetLoader,
+    },
+};
+
+pub struct PerkPlugin;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerkRarity {
+    Common,
+    Rare,
+    Epic,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum PerkEffect {
+    AddStat {
+        stat: StatKind,
+        amount: f32,
+    },
+    Heal(u32),
+    PickupRadius(f32),
+    PotionSlots(usize),
+    ExtraBullets(usize),
+    FireRate(f32),
+    BulletStatus {
+        kind: StatusKind,
+        magnitude: f32,
+        duration: f32,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PerkDefinition {
+    pub name: String,
+    pub description: String,
+    pub rarity: PerkRarity,
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: u32,
+    pub effects: Vec<PerkEffect>,
+}
+
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct PerkDefinitions {
+    pub perks: Vec<PerkDefinition>,
+}
+
+#[derive(Resource)]
+pub struct PerkRegistry {
+    perks: Vec<PerkDefinition>,
+}
+
+#[derive(Resource)]
+pub struct PerkDefinitionsHandle(pub Handle<PerkDefinitions>);
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Perks {
+    pub taken: Vec<String>,
+    pub pickup_radius: f32,
+    pub potion_slots: usize,
+    pub extra_bullets: usize,
+    pub firing_interval_multiplier: f32,
+    pub bullet_statuses: Vec<StatusSpec>,
+}
+
+#[derive(Resource, Default)]
+pub struct PendingLevelUps(pub Vec<u32>);
+
+#[derive(Resource, Default)]
+pub struct PerkChoices {
+    pub player: Option<Entity>,
+    pub perks: Vec<PerkDefinition>,
+    pub waiting: Vec<Entity>,
+}
+
+#[derive(Resource, Default)]
+pub struct SelectedPerk(pub usize);
+
+#[derive(Event)]
+pub struct ChoosePerkEvent(pub usize);
+
+fn default_max_stacks() -> u32 {
+    1
+}
+
+impl PerkRarity {
+    pub fn weight(self) -> f32 {
+        match self {
+            PerkRarity::Common => 6.0,
+            PerkRarity::Rare => 3.0,
+            PerkRarity::Epic => 1.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PerkRarity::Common => "Common",
+            PerkRarity::Rare => "Rare",
+            PerkRarity::Epic => "Epic",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PerkRarity::Common => Color::srgb_u8(90, 90, 90),
+            PerkRarity::Rare => Color::srgb_u8(40, 90, 200),
+            PerkRarity::Epic => Color::srgb_u8(150, 50, 180),
+        }
+    }
+}
+
+impl Default for Perks {
+    fn default() -> Self {
+        Self {
+            taken: Vec::new(),
+            pickup_radius: PLAYER_PICKUP_RADIUS,
+            potion_slots: INVENTORY_SLOTS,
+            extra_bullets: 0,
+            firing_interval_multiplier: 1.0,
+            bullet_statuses: Vec::new(),
+        }
+    }
+}
+
+impl Perks {
+    pub fn stacks(&self, name: &str) -> u32 {
+        self.taken.iter().filter(|taken| *taken == name).count() as u32
+    }
+
+    pub fn can_take(&self, definition: &PerkDefinition) -> bool {
+        self.stacks(&definition.name) < definition.max_stacks
+            && definition
+                .prerequisites
+                .iter()
+                .all(|prerequisite| self.stacks(prerequisite) > 0)
+    }
+
+    pub fn apply(&mut self, definition: &PerkDefinition, stats: &mut Stats, health: &mut Health) {
+        for effect in &definition.effects {
+            match *effect {
+                PerkEffect::AddStat { stat, amount } => stats.add_base(stat, amount),
+                PerkEffect::Heal(amount) => {
+                    health.0 = (health.0 + amount).min(stats.value_u32(StatKind::MaxHealth));
+                }
+                PerkEffect::PickupRadius(radius) => self.pickup_radius += radius,
+                PerkEffect::PotionSlots(slots) => {
+                    self.potion_slots = (self.potion_slots + slots).min(MAX_POTION_SLOTS);
+                }
+                PerkEffect::ExtraBullets(bullets) => self.extra_bullets += bullets,
+                PerkEffect::FireRate(multiplier) => self.firing_interval_multiplier *= multiplier,
+                PerkEffect::BulletStatus {
+                    kind,
+                    magnitude,
+                    duration,
+                } => {
+                    let status = StatusSpec {
+                        kind,
+                        magnitude,
+                        duration,
+                    };
+                    match self
+                        .bullet_statuses
+                        .iter_mut()
+                        .find(|existing| existing.kind == status.kind)
+                    {
+                        Some(existing) => {
+                            existing.magnitude = existing.magnitude.max(status.magnitude);
+                            existing.duration = existing.duration.max(status.duration);
+                        }
+                        None => self.bullet_statuses.push(status),
+                    }
+                }
+            }
+        }
+        self.taken.push(definition.name.clone());
+    }
+}
+
+impl Default for PerkRegistry {
+    fn default() -> Self {
+        let definitions = ron::from_str::<PerkDefinitions>(include_str!("../assets/perks.ron"))
+            .expect("built-in perk definitions should parse");
+        let mut registry = Self { perks: Vec::new() };
+        registry.replace(&definitions);
+        registry
+    }
+}
+
+impl PerkRegistry {
+    pub fn get(&self, name: &str) -> Option<&PerkDefinition> {
+        self.perks.iter().find(|definition| definition.name == name)
+    }
+
+    pub fn replace(&mut self, definitions: &PerkDefinitions) {
+        self.perks = definitions.perks.clone();
+
+        for definition in &self.perks {
+            for prerequisite in &definition.prerequisites {
+                if self.get(prerequisite).is_none() {
+                    warn!("{} requires unknown perk {}", definition.name, prerequisite);
+                }
+            }
+        }
+    }
+
+    pub fn roll(&self, perks: &Perks, rng: &mut GameRng) -> Vec<PerkDefinition> {
+        let mut candidates: Vec<(usize, f32)> = self
+            .perks
+            .iter()
+            .enumerate()
+            .filter(|(_, definition)| perks.can_take(definition))
+            .map(|(index, definition)| (index, definition.rarity.weight()))
+            .collect();
+
+        let mut choices = Vec::new();
+        while choices.len() < PERK_CHOICES {
+            let Some(index) = pick_weighted(&candidates, &mut rng.perk) else {
+                break;
+            };
+            candidates.retain(|(candidate, _)| *candidate != index);
+            choices.push(self.perks[index].clone());
+        }
+        choices
+    }
+}
+
+impl Plugin for PerkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChoosePerkEvent>()
+            .init_resource::<PerkRegistry>()
+            .init_resource::<PendingLevelUps>()
+            .init_resource::<PerkChoices>()
+            .init_resource::<SelectedPerk>()
+            .add_systems(
+                Update,
+                (navigate_perk_choices, handle_perk_choice)
+                    .chain()
+                    .run_if(in_state(GameState::LevelUp)),
+            )
+            .add_systems(OnExit(GameState::LevelUp), clear_perk_choices)
+            .add_systems(OnEnter(GameState::Initializing), clear_pending_level_ups);
+
+        if app.is_plugin_added::<AssetPlugin>() {
+            app.init_asset::<PerkDefinitions>()
+                .init_asset_loader::<RonAssetLoader<PerkDefinitions>>()
+                .add_systems(
+                    Update,
+                    sync_perk_registry.run_if(resource_exists::<PerkDefinitionsHandle>),
+                );
+
+            #[cfg(not(target_arch = "wasm32"))]
+            app.add_systems(OnEnter(GameState::Loading), load_perk_definitions);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_perk_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PerkDefinitionsHandle(
+        asset_server.load(crate::configs::PERK_DEFINITIONS_PATH),
+    ));
+}
+
+fn sync_perk_registry(
+    mut events: EventReader<AssetEvent<PerkDefinitions>>,
+    handle: Res<PerkDefinitionsHandle>,
+    definitions: Res<Assets<PerkDefinitions>>,
+    mut registry: ResMut<PerkRegistry>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != handle.0.id() {
+            continue;
+        }
+        if let Some(definitions) = definitions.get(*id) {
+            registry.replace(definitions);
+            info!("Loaded {} perk definitions", definitions.perks.len());
+        }
+    }
+}
+
+fn navigate_perk_choices(
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
+    choices: Res<PerkChoices>,
+    mut selected: ResMut<SelectedPerk>,
+    mut ev_choose: EventWriter<ChoosePerkEvent>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    let count = choices.perks.len();
+    if count == 0 {
+        return;
+    }
+
+    if navigation.triggered(MenuAction::NavigateUp) {
+        selected.0 = (selected.0 + count - 1) % count;
+        ew.send(AudioEvent::UI);
+    }
+
+    if navigation.triggered(MenuAction::NavigateDown) {
+        selected.0 = (selected.0 + 1) % count;
+        ew.send(AudioEvent::UI);
+    }
+
+    if action_state.just_pressed(&MenuAction::Confirm) {
+        ew.send(AudioEvent::PopUp);
+        ev_choose.send(ChoosePerkEvent(selected.0));
+    }
+}
+
+pub fn handle_perk_choice(
+    mut events: EventReader<ChoosePerkEvent>,
+    mut player_query: Query<(&mut Perks, &mut Stats, &mut Health, Has<Downed>), With<Player>>,
+    slot_query: Query<(Entity, &PlayerSlot), With<Player>>,
+    registry: Res<PerkRegistry>,
+    mut pending: ResMut<PendingLevelUps>,
+    mut choices: ResMut<PerkChoices>,
+    mut selected: ResMut<SelectedPerk>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    for event in events.read() {
+        let (Some(definition), Some(player)) =
+            (choices.perks.get(event.0).cloned(), choices.player)
+        else {
+            continue;
+        };
+        if let Ok((mut perks, mut stats, mut health, is_downed)) = player_query.get_mut(player) {
+            perks.apply(&definition, &mut stats, &mut health);
+            if is_downed {
+                health.0 = 0;
+            }
+        }
+        if let Ok((_, slot)) = slot_query.get(player) {
+            info!("{} took perk {}", slot.label(), definition.name);
+        }
+        if choices.waiting.is_empty() && !pending.0.is_empty() {
+            pending.0.remove(0);
+        }
+        choices.player = None;
+        choices.perks.clear();
+    }
+
+    if !choices.perks.is_empty() {
+        return;
+    }
+    while let Some(&level) = pending.0.first() {
+        if choices.waiting.is_empty() {
+            let mut players = slot_query
+                .iter()
+                .map(|(player, slot)| (slot.0, player))
+                .collect::<Vec<_>>();
+            players.sort();
+            choices.waiting = players.into_iter().map(|(_, player)| player).collect();
+        }
+        let player = choices.waiting.remove(0);
+        if let Ok((perks, mut stats, mut health, is_downed)) = player_query.get_mut(player) {
+            let rolled = registry.roll(&perks, &mut rng);
+            if !rolled.is_empty() {
+                choices.player = Some(player);
+                choices.perks = rolled;
+                selected.0 = 0;
+                return;
+            }
+            if !is_downed {
+                health.0 += calculate_health_increase(level);
+            }
+            stats.add_base(StatKind::Defense, calculate_defense_increase(level) as f32);
+            stats.add_base(
+                StatKind::DamageBoost,
+                calculate_damage_boost_increase(level) as f32,
+            );
+        }
+        if choices.waiting.is_empty() {
+            pending.0.remove(0);
+        }
+    }
+    next_state.set(GameState::Combat);
+}
+
+fn clear_perk_choices(mut choices: ResMut<PerkChoices>) {
+    *choices = PerkChoices::default();
+}
+
+fn clear_pending_level_ups(mut pending: ResMut<PendingLevelUps>) {
+    pending.0.clear();
+}
@@ -1,8 +1,24 @@
 use bevy::{prelude::*, time::Stopwatch};
 
+use crate::configs::{PLAYER_DASH_COOLDOWN, PLAYER_DASH_DURATION};
+
 #[derive(Component)]
 pub struct Player;
 
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PlayerSlot(pub usize);
+
+impl PlayerSlot {
+    pub fn label(&self) -> String {
+        format!("P{}", self.0 + 1)
+    }
+}
+
+#[derive(Component)]
+pub struct Downed {
+    pub revive: Timer,
+}
+
 #[derive(Component)]
 pub struct OriginalColor(pub Color);
 
@@ -36,7 +52,31 @@ pub struct PlayerInventory {
 pub struct InvincibilityEffect(pub Stopwatch, pub f32);
 
 #[derive(Component)]
-pub struct AccelerationEffect(pub Stopwatch, pub f32, pub u32);
+pub struct Dash {
+    pub direction: Vec2,
+    pub timer: Timer,
+    pub cooldown: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(PLAYER_DASH_DURATION, TimerMode::Once);
+        let mut cooldown = Timer::from_seconds(PLAYER_DASH_COOLDOWN, TimerMode::Once);
+        timer.tick(timer.duration());
+        cooldown.tick(cooldown.duration());
+        Self {
+            direction: Vec2::X,
+            timer,
+            cooldown,
+        }
+    }
+}
+
+impl Dash {
+    pub fn is_dashing(&self) -> bool {
+        !self.timer.finished()
+    }
+}
 
 #[derive(Component, Default, Debug)]
 pub enum PlayerState {
@@ -45,11 +85,6 @@ pub enum PlayerState {
     Run,
 }
 
-#[derive(Event)]
-pub struct PlayerDamagedEvent {
-    pub damage: u32,
-}
-
 #[derive(Event)]
 pub struct PlayerLevelingUpEvent {
     pub new_level: u32,
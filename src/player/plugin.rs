@@ -1,18 +1,16 @@
 use crate::{
-    collision::handle_enemy_player_collision,
-    game_state::GameState,
+    game_state::{GameState, GameplaySet},
     player::{
-        handle_acceleration_effect, handle_invincibility_effect, handle_leveling_up,
-        handle_loot_pickup, handle_player_damaged_events, handle_player_death,
-        handle_player_movement, handle_sprite_reset, mark_loot_for_pickup, move_loot_to_player,
-        update_player_invincibility_visual, InvincibilityEffect, PlayerDamagedEvent,
-        PlayerLevelingUpEvent,
+        handle_invincibility_effect, handle_leveling_up, handle_loot_pickup, handle_player_dash,
+        handle_player_death, handle_player_movement, handle_sprite_reset, mark_loot_for_pickup,
+        move_loot_to_player, start_player_dash, update_player_invincibility_visual,
+        InvincibilityEffect, PlayerLevelingUpEvent,
     },
     ui::components::LootSaleEvent,
     utils::cleanup_entities,
 };
 use bevy::{
-    app::{App, Plugin, PostUpdate, Update},
+    app::{App, FixedUpdate, Plugin, PostUpdate, Update},
     prelude::{any_component_removed, in_state, on_event, IntoSystemConfigs, OnEnter},
 };
 
@@ -22,22 +20,26 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerDamagedEvent>()
-            .add_event::<PlayerLevelingUpEvent>()
+        app.add_event::<PlayerLevelingUpEvent>()
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
+                    handle_player_dash,
                     handle_player_movement,
-                    handle_player_damaged_events
-                        .run_if(on_event::<PlayerDamagedEvent>)
-                        .after(handle_enemy_player_collision),
                     handle_invincibility_effect,
-                    handle_acceleration_effect,
                     handle_leveling_up.run_if(on_event::<PlayerLevelingUpEvent>),
-                    handle_sprite_reset.run_if(any_component_removed::<InvincibilityEffect>),
                     handle_loot_pickup,
                     move_loot_to_player,
                     mark_loot_for_pickup,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Player)
+                    .run_if(in_state(GameState::Combat)),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_sprite_reset.run_if(any_component_removed::<InvincibilityEffect>),
                     update_player_invincibility_visual,
                 )
                     .run_if(in_state(GameState::Combat)),
@@ -46,6 +48,10 @@ impl Plugin for PlayerPlugin {
                 Update,
                 handle_loot_sale_event.run_if(on_event::<LootSaleEvent>),
             )
+            .add_systems(
+                Update,
+                start_player_dash.run_if(in_state(GameState::Combat)),
+            )
             .add_systems(PostUpdate, handle_player_death)
             .add_systems(OnEnter(GameState::End), cleanup_entities)
             .add_systems(OnEnter(GameState::Win), cleanup_entities);
@@ -1,138 +1,26 @@
 use bevy::{prelude::*, time::Stopwatch};
-use chrono::prelude::*;
 use leafwing_input_manager::prelude::*;
 
 use super::*;
 use crate::{
-    armor::{ActiveArmor, Armor, ArmorStats},
+    armor::{ActiveArmor, Armor},
     audio::AudioEvent,
     configs::*,
     gun::{ActiveGun, Gun},
-    input::Action,
+    input::{CombatAction, InputBuffer},
     loot::{LootType, MovingToPlayer, ReadyForPickup, Value},
+    perk::{PendingLevelUps, Perks},
     potion::PotionType,
     resources::UiFont,
     ui::{components::LootSaleEvent, systems::in_game_ui::spawn_floating_text},
     utils::*,
 };
 
-pub fn handle_player_damaged_events(
-    mut commands: Commands,
-    mut player_query: Query<
-        (
-            &mut Health,
-            &PlayerState,
-            &Defense,
-            &mut PlayerInventory,
-            &Transform,
-            Entity,
-        ),
-        (With<Player>, Without<InvincibilityEffect>),
-    >,
-    mut armor_query: Query<(&mut ArmorStats, Entity), With<Armor>>,
-    mut events: EventReader<PlayerDamagedEvent>,
-    font: Res<UiFont>,
-    mut ew: EventWriter<AudioEvent>,
-) {
-    if player_query.is_empty() || events.is_empty() {
-        return;
-    }
-    let (mut health, _player_state, player_defense, mut inventory, player_transform, entity) =
-        player_query.single_mut();
-
-    for event in events.read() {
-        if health.0 > 0 {
-            let mut total_defense = player_defense.0;
-
-            if let Some(active_armor_entity) = inventory.armors.get(inventory.active_armor_index) {
-                if let Ok((mut armor_stats, armor_entity)) =
-                    armor_query.get_mut(*active_armor_entity)
-                {
-                    total_defense += armor_stats.defense;
-                    let damage_after_defense =
-                        (event.damage as f32 * calculate_defense_percentage(total_defense)) as u32;
-
-                    armor_stats.durability =
-                        armor_stats.durability.saturating_sub(damage_after_defense);
-
-                    if armor_stats.durability == 0 {
-                        commands.entity(armor_entity).despawn();
-                        let armor_to_remove = inventory.active_armor_index;
-                        inventory.armors.remove(armor_to_remove);
-                        if inventory.active_armor_index >= inventory.armors.len() {
-                            inventory.active_armor_index = 0;
-                        }
-                        if let Some(new_active_armor_entity) =
-                            inventory.armors.get(inventory.active_armor_index)
-                        {
-                            commands
-                                .entity(*new_active_armor_entity)
-                                .insert(ActiveArmor);
-                        }
-                    }
-                    if damage_after_defense > 0 {
-                        health.0 = health.0.saturating_sub(damage_after_defense);
-                        println!("Player took {} damage", damage_after_defense);
-                        let current_time = chrono::Local::now();
-                        println!(
-                            "Current time: {:02}:{:02}",
-                            current_time.minute(),
-                            current_time.second()
-                        );
-                        ew.send(AudioEvent::Hit);
-                        commands.entity(entity).insert(InvincibilityEffect(
-                            Stopwatch::new(),
-                            PLAYER_INVINCIBLE_TIME,
-                        ));
-                        spawn_floating_text(
-                            &mut commands,
-                            &font.0,
-                            player_transform.translation,
-                            format!("-{}", damage_after_defense.to_owned()),
-                            None,
-                        );
-                        return;
-                    }
-                }
-            } else {
-                let damage_after_defense =
-                    (event.damage as f32 * calculate_defense_percentage(player_defense.0)) as u32;
-                health.0 = health.0.saturating_sub(damage_after_defense);
-                if damage_after_defense > 0 {
-                    println!("Player took {} damage", damage_after_defense);
-                    let current_time = chrono::Local::now();
-                    println!(
-                        "Current time: {:02}:{:02}",
-                        current_time.minute(),
-                        current_time.second()
-                    );
-                    ew.send(AudioEvent::Hit);
-                    commands.entity(entity).insert(InvincibilityEffect(
-                        Stopwatch::new(),
-                        PLAYER_INVINCIBLE_TIME,
-                    ));
-                    spawn_floating_text(
-                        &mut commands,
-                        &font.0,
-                        player_transform.translation,
-                        format!("-{}", damage_after_defense.to_owned()),
-                        None,
-                    );
-                    return;
-                }
-            }
-        } else {
-            println!("Dead");
-        }
-    }
-}
-
 pub fn handle_leveling_up(
     mut event_reader: EventReader<PlayerLevelingUpEvent>,
-    mut player_query: Query<
-        (&mut DamageBoost, &mut Health, &mut Defense, &Transform),
-        With<Player>,
-    >,
+    player_query: Query<&Transform, With<Player>>,
+    mut pending: ResMut<PendingLevelUps>,
+    mut next_state: ResMut<NextState<GameState>>,
     mut commands: Commands,
     font: Res<UiFont>,
     mut ew: EventWriter<AudioEvent>,
@@ -141,35 +29,35 @@ pub fn handle_leveling_up(
         return;
     }
 
-    let (mut damage_boost, mut health, mut defense, transform) = player_query.single_mut();
-
     for event in event_reader.read() {
-        let level = event.new_level;
-        health.0 += calculate_health_increase(level);
-        defense.0 += calculate_defense_increase(level);
-        damage_boost.0 += calculate_damage_boost_increase(level);
+        pending.0.push(event.new_level);
         ew.send(AudioEvent::LevelUp);
-        spawn_floating_text(
-            &mut commands,
-            &font.0,
-            transform.translation,
-            format!("Level Up!"),
-            Some(Color::srgb_u8(0, 128, 0)),
-        );
+        for transform in player_query.iter() {
+            spawn_floating_text(
+                &mut commands,
+                &font.0,
+                transform.translation,
+                format!("Level Up!"),
+                Some(Color::srgb_u8(0, 128, 0)),
+            );
+        }
+    }
+
+    if !pending.0.is_empty() {
+        next_state.set(GameState::LevelUp);
     }
 }
 
 pub fn handle_player_death(
     commands: Commands,
     all_entities: Query<Entity, With<InGameEntity>>,
-    player_query: Query<(Entity, &Health), With<Player>>,
+    player_query: Query<&Health, With<Player>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     if player_query.is_empty() {
         return;
     }
-    let player = player_query.single();
-    if player.1 .0 == 0 {
+    if player_query.iter().all(|health| health.0 == 0) {
         cleanup_entities(commands, all_entities);
         next_state.set(GameState::End);
     }
@@ -180,79 +68,155 @@ pub fn handle_invincibility_effect(
     mut commands: Commands,
     mut player_query: Query<(&mut InvincibilityEffect, Entity), With<Player>>,
 ) {
-    if player_query.is_empty() {
-        return;
+    for (mut invincibility_effect, entity) in player_query.iter_mut() {
+        if invincibility_effect.0.elapsed_secs() >= invincibility_effect.1 {
+            commands.entity(entity).remove::<InvincibilityEffect>();
+        }
+        invincibility_effect.0.tick(time.delta());
     }
-    let (mut invincibility_effect, entity) = player_query.single_mut();
+}
 
-    if invincibility_effect.0.elapsed_secs() >= invincibility_effect.1 {
-        commands.entity(entity).remove::<InvincibilityEffect>();
+pub fn handle_sprite_reset(
+    mut player_query: Query<
+        (&OriginalColor, &mut Sprite),
+        (With<Player>, Without<InvincibilityEffect>, Without<Downed>),
+    >,
+) {
+    for (color, mut sprite) in player_query.iter_mut() {
+        sprite.color = color.0;
     }
-    invincibility_effect.0.tick(time.delta());
 }
 
-pub fn handle_sprite_reset(mut player_query: Query<(&OriginalColor, &mut Sprite), With<Player>>) {
-    if player_query.is_empty() {
-        return;
+pub fn start_player_dash(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Dash,
+            &Sprite,
+            Option<&InvincibilityEffect>,
+            Option<&ActionState<CombatAction>>,
+            Option<&mut InputBuffer>,
+        ),
+        (With<Player>, Without<Downed>),
+    >,
+    action_state: Res<ActionState<CombatAction>>,
+    mut buffer: ResMut<InputBuffer>,
+) {
+    for (entity, mut dash, sprite, invincibility, player_state, player_buffer) in
+        player_query.iter_mut()
+    {
+        if !dash.cooldown.finished() {
+            continue;
+        }
+        let buffered = match player_buffer {
+            Some(mut player_buffer) => player_buffer.consume(CombatAction::Dash),
+            None => buffer.consume(CombatAction::Dash),
+        };
+        if !buffered {
+            continue;
+        }
+
+        let axis_pair = player_state
+            .unwrap_or(&action_state)
+            .clamped_axis_pair(&CombatAction::Move);
+        dash.direction = if axis_pair != Vec2::ZERO {
+            axis_pair.normalize()
+        } else if sprite.flip_x {
+            Vec2::NEG_X
+        } else {
+            Vec2::X
+        };
+        dash.timer.reset();
+        dash.cooldown.reset();
+
+        let remaining_invincibility =
+            invincibility.map_or(0.0, |effect| effect.1 - effect.0.elapsed_secs());
+        if remaining_invincibility < PLAYER_DASH_INVINCIBLE_TIME {
+            commands.entity(entity).insert(InvincibilityEffect(
+                Stopwatch::new(),
+                PLAYER_DASH_INVINCIBLE_TIME,
+            ));
+        }
     }
-    let (color, mut sprite) = player_query.single_mut();
-    sprite.color = color.0;
 }
 
-pub fn handle_acceleration_effect(
+pub fn handle_player_dash(
     time: Res<Time>,
-    mut commands: Commands,
-    mut player_query: Query<(&mut AccelerationEffect, &mut Speed, Entity), With<Player>>,
+    mut player_query: Query<(&mut Transform, &mut Dash), With<Player>>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-    let (mut acceleration_effect, mut speed, entity) = player_query.single_mut();
+    for (mut transform, mut dash) in player_query.iter_mut() {
+        dash.cooldown.tick(time.delta());
+        if !dash.is_dashing() {
+            continue;
+        }
 
-    if acceleration_effect.0.elapsed_secs() >= acceleration_effect.1 {
-        commands.entity(entity).remove::<AccelerationEffect>();
-        speed.0 -= acceleration_effect.2;
+        dash.timer.tick(time.delta());
+        let layer = transform.translation.z;
+        apply_movement(
+            &mut transform.translation,
+            dash.direction * PLAYER_DASH_SPEED,
+            time.delta_secs(),
+            layer,
+        );
     }
-    acceleration_effect.0.tick(time.delta());
 }
 
 pub fn handle_player_movement(
-    mut player_query: Query<(&mut Transform, &mut PlayerState, &Speed, &mut Sprite), With<Player>>,
-    action_state: Res<ActionState<Action>>,
+    time: Res<Time>,
+    mut player_query: Query<
+        (
+            &mut Transform,
+            &mut PlayerState,
+            &Speed,
+            &mut Sprite,
+            &Dash,
+            Option<&ActionState<CombatAction>>,
+        ),
+        (With<Player>, Without<Downed>),
+    >,
+    action_state: Res<ActionState<CombatAction>>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let (mut transform, mut player_state, speed, mut sprite) = player_query.single_mut();
+    for (mut transform, mut player_state, speed, mut sprite, dash, player_state_input) in
+        player_query.iter_mut()
+    {
+        if dash.is_dashing() {
+            continue;
+        }
 
-    let axis_pair = action_state.clamped_axis_pair(&Action::Move);
-    if axis_pair != Vec2::ZERO {
-        let movement = axis_pair * speed.0 as f32;
-        sprite.flip_x = movement.x < 0.0;
-        transform.translation.x += movement.x;
-        transform.translation.y += movement.y;
-        clamp_position(&mut transform.translation);
-    } else {
-        *player_state = PlayerState::Idle;
+        let axis_pair = player_state_input
+            .unwrap_or(&action_state)
+            .clamped_axis_pair(&CombatAction::Move);
+        if axis_pair != Vec2::ZERO {
+            let movement = axis_pair * speed.0 as f32 * time.delta_secs();
+            *player_state = PlayerState::Run;
+            sprite.flip_x = movement.x < 0.0;
+            transform.translation.x += movement.x;
+            transform.translation.y += movement.y;
+            clamp_position(&mut transform.translation);
+        } else {
+            *player_state = PlayerState::Idle;
+        }
     }
 }
 
 pub fn mark_loot_for_pickup(
     mut commands: Commands,
     loot_query: Query<(Entity, &Transform), (With<Pickable>, Without<MovingToPlayer>)>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(Entity, &Transform, &Perks), (With<Player>, Without<Downed>)>,
 ) {
-    let Ok(player_transform) = player_query.get_single() else {
-        return;
-    };
-    let player_pos = player_transform.translation.xy();
-
     for (loot_entity, loot_transform) in loot_query.iter() {
         let loot_pos = loot_transform.translation.xy();
-        if player_pos.distance(loot_pos) <= 400.0 {
+        let player = player_query
+            .iter()
+            .map(|(entity, transform, perks)| {
+                (entity, transform.translation.xy().distance(loot_pos), perks)
+            })
+            .filter(|(_, distance, perks)| *distance <= perks.pickup_radius)
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+        if let Some((player_entity, _, _)) = player {
             if let Some(mut entity_commands) = commands.get_entity(loot_entity) {
-                entity_commands.insert(MovingToPlayer);
+                entity_commands.insert(MovingToPlayer(player_entity));
             }
         }
     }
@@ -261,17 +225,14 @@ pub fn mark_loot_for_pickup(
 pub fn move_loot_to_player(
     mut commands: Commands,
     time: Res<Time>,
-    mut loot_query: Query<(Entity, &mut Transform), (With<MovingToPlayer>, Without<Player>)>,
+    mut loot_query: Query<(Entity, &mut Transform, &MovingToPlayer), Without<Player>>,
     player_query: Query<&Transform, With<Player>>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let player_transform = player_query.single();
-    let player_pos = player_transform.translation.xy();
-
-    for (loot_entity, mut transform) in loot_query.iter_mut() {
+    for (loot_entity, mut transform, moving_to) in loot_query.iter_mut() {
+        let Ok(player_transform) = player_query.get(moving_to.0) else {
+            continue;
+        };
+        let player_pos = player_transform.translation.xy();
         let current_pos = transform.translation.xy();
         let direction = (player_pos - current_pos).normalize_or_zero();
         let distance = player_pos.distance(current_pos);
@@ -287,11 +248,12 @@ pub fn move_loot_to_player(
 
 pub fn handle_loot_pickup(
     mut commands: Commands,
-    mut player_query: Query<(&mut PlayerInventory, &mut Gold), With<Player>>,
+    mut player_query: Query<(&mut PlayerInventory, &mut Gold, &Perks), With<Player>>,
     loot_query: Query<
         (
             Entity,
             &Value,
+            &MovingToPlayer,
             Option<&PotionType>,
             Option<&Gun>,
             Option<&Armor>,
@@ -300,17 +262,14 @@ pub fn handle_loot_pickup(
     >,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if player_query.is_empty() {
-        return;
-    }
-
-    let (mut inventory, mut gold) = player_query.single_mut();
-
-    for (loot_entity, value, potion_type, gun, armor) in loot_query.iter() {
+    for (loot_entity, value, moving_to, potion_type, gun, armor) in loot_query.iter() {
+        let Ok((mut inventory, mut gold, perks)) = player_query.get_mut(moving_to.0) else {
+            continue;
+        };
         match (potion_type, gun, armor) {
             (Some(PotionType::Speed), _, _) => {
                 if !inventory.speed_potions.contains(&loot_entity) {
-                    if inventory.speed_potions.len() < 4 {
+                    if inventory.speed_potions.len() < perks.potion_slots {
                         inventory.speed_potions.push(loot_entity);
                     } else {
                         gold.0 += value.0;
@@ -319,7 +278,7 @@ pub fn handle_loot_pickup(
             }
             (Some(PotionType::Health), _, _) => {
                 if !inventory.health_potions.contains(&loot_entity) {
-                    if inventory.health_potions.len() < 4 {
+                    if inventory.health_potions.len() < perks.potion_slots {
                         inventory.health_potions.push(loot_entity);
                     } else {
                         gold.0 += value.0;
@@ -328,7 +287,7 @@ pub fn handle_loot_pickup(
             }
             (_, Some(_), _) => {
                 if !inventory.guns.contains(&loot_entity) {
-                    if inventory.guns.len() < 4 {
+                    if inventory.guns.len() < INVENTORY_SLOTS {
                         inventory.guns.push(loot_entity);
                     } else {
                         gold.0 += value.0;
@@ -337,7 +296,7 @@ pub fn handle_loot_pickup(
             }
             (_, _, Some(_)) => {
                 if !inventory.armors.contains(&loot_entity) {
-                    if inventory.armors.len() < 4 {
+                    if inventory.armors.len() < INVENTORY_SLOTS {
                         inventory.armors.push(loot_entity);
                     } else {
                         gold.0 += value.0;
@@ -360,7 +319,7 @@ pub fn update_player_invincibility_visual(
     mut player_query: Query<&mut Sprite, (With<Player>, With<InvincibilityEffect>)>,
     time: Res<Time>,
 ) {
-    if let Ok(mut sprite) = player_query.get_single_mut() {
+    for mut sprite in player_query.iter_mut() {
         let flash_rate = 2.0;
         sprite.color = sprite
             .color
@@ -374,8 +333,14 @@ pub fn handle_loot_sale_event(
     mut loot_sale_event_reader: EventReader<LootSaleEvent>,
     loot_query: Query<&Value>,
 ) {
-    if let Ok((mut inventory, mut gold)) = player_query.get_single_mut() {
-        for event in loot_sale_event_reader.read() {
+    for event in loot_sale_event_reader.read() {
+        let owner = player_query.iter_mut().find(|(inventory, _)| {
+            inventory.health_potions.contains(&event.0)
+                || inventory.speed_potions.contains(&event.0)
+                || inventory.guns.contains(&event.0)
+                || inventory.armors.contains(&event.0)
+        });
+        if let Some((mut inventory, mut gold)) = owner {
             if let Ok(value) = loot_query.get(event.0) {
                 gold.0 += value.0;
 
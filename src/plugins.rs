@@ -0,0 +1,34 @@
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+use crate::{
+    aim::AimPlugin, armor::ArmorPlugin, class::ClassPlugin, collision::CollisionPlugin,
+    coop::CoopPlugin, damage::DamagePlugin, enemy::plugin::EnemyPlugin, gun::GunPlugin,
+    input::InputPlugin, perk::PerkPlugin, player::plugin::PlayerPlugin, potion::PotionPlugin,
+    resources::ResourcesPlugin, rng::RngPlugin, stats::StatsPlugin, status::StatusPlugin,
+    world::WorldPlugin,
+};
+
+pub struct GameplayPlugins;
+
+impl PluginGroup for GameplayPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GunPlugin)
+            .add(PlayerPlugin)
+            .add(ResourcesPlugin)
+            .add(WorldPlugin)
+            .add(EnemyPlugin)
+            .add(CollisionPlugin)
+            .add(DamagePlugin)
+            .add(PotionPlugin)
+            .add(ArmorPlugin)
+            .add(InputPlugin)
+            .add(RngPlugin)
+            .add(StatsPlugin)
+            .add(StatusPlugin)
+            .add(AimPlugin)
+            .add(PerkPlugin)
+            .add(ClassPlugin)
+            .add(CoopPlugin)
+    }
+}
@@ -1,27 +1,31 @@
 use crate::{
+    configs::SPEED_POTION_SCALE,
+    damage::DamageSource,
     game_state::GameState,
-    input::Action,
+    input::CombatAction,
     loot::{Description, Value},
-    player::{AccelerationEffect, Health, Player, PlayerInventory, Speed},
+    player::{Downed, Health, Player, PlayerInventory},
     resources::UiFont,
+    status::{ApplyStatusEvent, StatusEffect, StatusEffects, StatusKind},
     ui::systems::in_game_ui::spawn_floating_text,
     utils::InGameEntity,
 };
-use bevy::{prelude::*, time::Stopwatch};
+use bevy::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 #[require(PotionStats, PotionType, InGameEntity, Sprite, Description, Value)]
 pub struct Potion;
 
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
 pub enum PotionType {
     #[default]
     Health,
     Speed,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Serialize, Deserialize)]
 pub struct PotionStats {
     pub effect_duration: f32,
     pub effect_amount: u32,
@@ -42,52 +46,61 @@ fn apply_potion_effects(
     mut commands: Commands,
     mut player_query: Query<
         (
+            Entity,
             &mut Health,
             &mut PlayerInventory,
-            Entity,
-            &mut Speed,
+            &StatusEffects,
             &Transform,
-            Option<&AccelerationEffect>,
+            &Name,
+            Option<&ActionState<CombatAction>>,
         ),
-        With<Player>,
+        (With<Player>, Without<Downed>),
     >,
     potion_query: Query<(Entity, &PotionStats), With<Potion>>,
-    action_state: Res<ActionState<Action>>,
+    action_state: Res<ActionState<CombatAction>>,
+    mut ev_status: EventWriter<ApplyStatusEvent>,
     font: Res<UiFont>,
 ) {
-    let (mut health, mut player_inventory, entity, mut speed, transform, acceleration_effect) =
-        player_query.single_mut();
-    if action_state.just_pressed(&Action::UsePotion1) {
-        if let Some(health_potion_entity) = player_inventory.health_potions.first() {
-            if let Ok((potion_entity, potion_stats)) = potion_query.get(*health_potion_entity) {
-                health.0 = (health.0 + potion_stats.effect_amount).min(health.1);
-                commands.entity(potion_entity).despawn();
-                player_inventory.health_potions.remove(0);
+    for (player_entity, mut health, mut player_inventory, effects, transform, name, player_state) in
+        player_query.iter_mut()
+    {
+        let action_state = player_state.unwrap_or(&action_state);
+        if action_state.just_pressed(&CombatAction::UsePotion1) {
+            if let Some(health_potion_entity) = player_inventory.health_potions.first() {
+                if let Ok((potion_entity, potion_stats)) = potion_query.get(*health_potion_entity) {
+                    health.0 = (health.0 + potion_stats.effect_amount).min(health.1);
+                    commands.entity(potion_entity).despawn();
+                    player_inventory.health_potions.remove(0);
+                }
             }
         }
-    }
 
-    if action_state.just_pressed(&Action::UsePotion2) {
-        if acceleration_effect.is_some() {
-            spawn_floating_text(
-                &mut commands,
-                &font.0,
-                transform.translation,
-                "Speed potion already active!".to_owned(),
-                None,
-            );
-            return;
-        }
-        if let Some(speed_potion_entity) = player_inventory.speed_potions.first() {
-            if let Ok((potion_entity, potion_stats)) = potion_query.get(*speed_potion_entity) {
-                commands.entity(entity).insert(AccelerationEffect(
-                    Stopwatch::new(),
-                    potion_stats.effect_duration,
-                    potion_stats.effect_amount,
-                ));
-                speed.0 += potion_stats.effect_amount;
-                commands.entity(potion_entity).despawn();
-                player_inventory.speed_potions.remove(0);
+        if action_state.just_pressed(&CombatAction::UsePotion2) {
+            if effects.has(StatusKind::Haste) {
+                spawn_floating_text(
+                    &mut commands,
+                    &font.0,
+                    transform.translation,
+                    "Speed potion already active!".to_owned(),
+                    None,
+                );
+                continue;
+            }
+            if let Some(speed_potion_entity) = player_inventory.speed_potions.first() {
+                if let Ok((potion_entity, potion_stats)) = potion_query.get(*speed_potion_entity) {
+                    let speed_boost = potion_stats.effect_amount * SPEED_POTION_SCALE;
+                    ev_status.send(ApplyStatusEvent {
+                        target: player_entity,
+                        effect: StatusEffect::new(
+                            StatusKind::Haste,
+                            speed_boost as f32,
+                            potion_stats.effect_duration,
+                            DamageSource::new(player_entity, name),
+                        ),
+                    });
+                    commands.entity(potion_entity).despawn();
+                    player_inventory.speed_potions.remove(0);
+                }
             }
         }
     }
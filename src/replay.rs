@@ -0,0 +1,353 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Duration};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aim::{AimMode, TargetPriority},
+    class::PlayerClass,
+    configs::{REPLAY_FILE_PATH, REPLAY_VERSION},
+    coop::CoopSession,
+    game_state::GameState,
+    input::{
+        set_combat_input, update_input_buffer, update_navigation_repeat, CombatAction, MenuAction,
+    },
+    resources::GameMode,
+    rng::GameRng,
+    save::{default_player_class, LoadedRun},
+    world::init_world,
+};
+
+pub struct ReplayPlugin;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayFrame {
+    pub delta: Duration,
+    pub pressed: Vec<CombatAction>,
+    #[serde(default)]
+    pub menu_pressed: Vec<MenuAction>,
+    pub movement: [f32; 2],
+    #[serde(default)]
+    pub aim: [f32; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub version: u32,
+    pub game_mode: GameMode,
+    pub seed: u64,
+    #[serde(default)]
+    pub aim_mode: AimMode,
+    #[serde(default)]
+    pub target_priority: TargetPriority,
+    #[serde(default = "default_player_class")]
+    pub player_class: String,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    aim_mode: AimMode,
+    target_priority: TargetPriority,
+    player_class: String,
+    frames: Vec<ReplayFrame>,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    next_frame: usize,
+    frame_applied: bool,
+    input_maps: Option<(InputMap<CombatAction>, InputMap<MenuAction>)>,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_frame: 0,
+            frame_applied: false,
+            input_maps: None,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next_frame >= self.replay.frames.len()
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Initializing),
+            (
+                discard_fixed_overstep,
+                start_recording
+                    .before(init_world)
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+                apply_replay_settings
+                    .before(init_world)
+                    .run_if(resource_exists::<ReplayPlayback>),
+            ),
+        )
+        .add_systems(
+            PreUpdate,
+            apply_replay_frame
+                .after(InputManagerSystem::Update)
+                .before(update_navigation_repeat)
+                .before(update_input_buffer)
+                .run_if(resource_exists::<ReplayPlayback>),
+        )
+        .add_systems(
+            FixedPreUpdate,
+            apply_replay_frame
+                .after(InputManagerSystem::Update)
+                .run_if(resource_exists::<ReplayPlayback>),
+        )
+        .add_systems(
+            Last,
+            (
+                record_frame.run_if(resource_exists::<ReplayRecorder>),
+                write_recording_on_exit
+                    .after(record_frame)
+                    .run_if(resource_exists::<ReplayRecorder>.and(on_event::<AppExit>)),
+                advance_replay.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+
+        for state in [GameState::End, GameState::Win, GameState::MainMenu] {
+            app.add_systems(
+                OnEnter(state),
+                finish_recording.run_if(resource_exists::<ReplayRecorder>),
+            );
+        }
+        for state in [GameState::End, GameState::Win] {
+            app.add_systems(
+                OnExit(state),
+                stop_playback.run_if(resource_exists::<ReplayPlayback>),
+            );
+        }
+        app.add_systems(
+            OnEnter(GameState::MainMenu),
+            stop_playback.run_if(resource_exists::<ReplayPlayback>),
+        );
+    }
+}
+
+pub fn has_replay() -> bool {
+    replay_file_exists()
+}
+
+pub fn load_replay() -> Option<Replay> {
+    let contents = read_replay_file()?;
+    match ron::from_str::<Replay>(&contents) {
+        Ok(replay) if replay.version == REPLAY_VERSION => Some(replay),
+        Ok(replay) => {
+            warn!(
+                "Ignoring replay file with version {} (expected {})",
+                replay.version, REPLAY_VERSION
+            );
+            None
+        }
+        Err(err) => {
+            warn!("Failed to parse replay file: {}", err);
+            None
+        }
+    }
+}
+
+fn discard_fixed_overstep(mut fixed_time: ResMut<Time<Fixed>>) {
+    let overstep = fixed_time.overstep();
+    fixed_time.discard_overstep(overstep);
+}
+
+fn start_recording(
+    mut commands: Commands,
+    loaded_run: Option<Res<LoadedRun>>,
+    aim_mode: Res<AimMode>,
+    target_priority: Res<TargetPriority>,
+    player_class: Res<PlayerClass>,
+    coop: Res<CoopSession>,
+) {
+    if loaded_run.is_none() && !coop.is_active() {
+        commands.insert_resource(ReplayRecorder {
+            aim_mode: *aim_mode,
+            target_priority: *target_priority,
+            player_class: player_class.0.clone(),
+            frames: Vec::new(),
+        });
+    }
+}
+
+fn apply_replay_settings(
+    playback: Res<ReplayPlayback>,
+    mut aim_mode: ResMut<AimMode>,
+    mut target_priority: ResMut<TargetPriority>,
+    mut player_class: ResMut<PlayerClass>,
+) {
+    *aim_mode = playback.replay.aim_mode;
+    *target_priority = playback.replay.target_priority;
+    player_class.0 = playback.replay.player_class.clone();
+}
+
+fn record_frame(
+    mut recorder: ResMut<ReplayRecorder>,
+    time: Res<Time<Real>>,
+    action_state: Res<ActionState<CombatAction>>,
+    menu_state: Res<ActionState<MenuAction>>,
+) {
+    let movement = action_state.axis_pair(&CombatAction::Move);
+    let aim = action_state.axis_pair(&CombatAction::Aim);
+    recorder.frames.push(ReplayFrame {
+        delta: time.delta(),
+        pressed: action_state.get_pressed(),
+        menu_pressed: menu_state.get_pressed(),
+        movement: [movement.x, movement.y],
+        aim: [aim.x, aim.y],
+    });
+}
+
+fn write_recording(recorder: &ReplayRecorder, game_mode: GameMode, seed: u64) {
+    let replay = Replay {
+        version: REPLAY_VERSION,
+        game_mode,
+        seed,
+        aim_mode: recorder.aim_mode,
+        target_priority: recorder.target_priority,
+        player_class: recorder.player_class.clone(),
+        frames: recorder.frames.clone(),
+    };
+
+    match ron::to_string(&replay) {
+        Ok(contents) => write_replay_file(&contents),
+        Err(err) => warn!("Failed to serialize replay: {}", err),
+    }
+}
+
+fn write_recording_on_exit(
+    recorder: Res<ReplayRecorder>,
+    game_mode: Res<GameMode>,
+    rng: Res<GameRng>,
+) {
+    write_recording(&recorder, *game_mode, rng.seed);
+}
+
+fn finish_recording(
+    mut commands: Commands,
+    recorder: Res<ReplayRecorder>,
+    game_mode: Res<GameMode>,
+    rng: Res<GameRng>,
+) {
+    write_recording(&recorder, *game_mode, rng.seed);
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+fn apply_replay_frame(
+    mut playback: ResMut<ReplayPlayback>,
+    mut action_state: ResMut<ActionState<CombatAction>>,
+    mut menu_state: ResMut<ActionState<MenuAction>>,
+) {
+    let Some(frame) = playback.replay.frames.get(playback.next_frame).cloned() else {
+        return;
+    };
+
+    set_combat_input(
+        &mut action_state,
+        &frame.pressed,
+        Vec2::from(frame.movement),
+        Vec2::from(frame.aim),
+    );
+    for action in MenuAction::BUTTONS {
+        if frame.menu_pressed.contains(&action) {
+            menu_state.press(&action);
+        } else {
+            menu_state.release(&action);
+        }
+    }
+    playback.frame_applied = true;
+}
+
+fn advance_replay(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    combat_map: Option<Res<InputMap<CombatAction>>>,
+    menu_map: Option<Res<InputMap<MenuAction>>>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if playback.finished() {
+        return;
+    }
+    if playback.input_maps.is_none() {
+        if let (Some(combat_map), Some(menu_map)) = (combat_map, menu_map) {
+            playback.input_maps = Some((combat_map.clone(), menu_map.clone()));
+            commands.remove_resource::<InputMap<CombatAction>>();
+            commands.remove_resource::<InputMap<MenuAction>>();
+        }
+    }
+    if playback.frame_applied {
+        playback.next_frame += 1;
+        playback.frame_applied = false;
+    }
+
+    match playback.replay.frames.get(playback.next_frame) {
+        Some(frame) => *time_strategy = TimeUpdateStrategy::ManualDuration(frame.delta),
+        None => {
+            info!("Replay finished");
+            restore_live_input(&mut commands, &mut playback, &mut time_strategy);
+        }
+    }
+}
+
+fn stop_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    restore_live_input(&mut commands, &mut playback, &mut time_strategy);
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+fn restore_live_input(
+    commands: &mut Commands,
+    playback: &mut ReplayPlayback,
+    time_strategy: &mut TimeUpdateStrategy,
+) {
+    *time_strategy = TimeUpdateStrategy::Automatic;
+    if let Some((combat_map, menu_map)) = playback.input_maps.take() {
+        commands.insert_resource(combat_map);
+        commands.insert_resource(menu_map);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn replay_file_exists() -> bool {
+    std::path::Path::new(REPLAY_FILE_PATH).exists()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_replay_file() -> Option<String> {
+    std::fs::read_to_string(REPLAY_FILE_PATH).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_replay_file(contents: &str) {
+    if let Err(err) = std::fs::write(REPLAY_FILE_PATH, contents) {
+        warn!("Failed to write replay file: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn replay_file_exists() -> bool {
+    false
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_replay_file() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_replay_file(_contents: &str) {}
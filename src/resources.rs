@@ -1,6 +1,10 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{configs::*, game_state::GameState};
+use crate::{
+    configs::*,
+    game_state::{GameState, GameplaySet},
+};
 
 pub struct ResourcesPlugin;
 
@@ -18,7 +22,16 @@ pub struct Wave {
     pub number: u32,
 }
 
-#[derive(Resource, Clone, Copy, PartialEq)]
+#[derive(Resource)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum GameMode {
     Normal,
     Forever,
@@ -30,7 +43,7 @@ impl Default for GameMode {
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct Level {
     current_xp: u32,
     xp_threshold: u32,
@@ -80,10 +93,51 @@ impl Level {
 impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GlobalTextureAtlas::default())
-            .add_systems(OnEnter(GameState::Loading), load_assets);
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .configure_sets(
+                FixedUpdate,
+                (
+                    GameplaySet::Status,
+                    GameplaySet::Stats,
+                    GameplaySet::Enemy,
+                    GameplaySet::Collision,
+                    GameplaySet::Damage,
+                    GameplaySet::Player,
+                    GameplaySet::Gun,
+                )
+                    .chain(),
+            )
+            .init_resource::<GameSpeed>()
+            .add_systems(OnEnter(GameState::Loading), load_assets)
+            .add_systems(
+                Update,
+                apply_game_speed.run_if(resource_changed::<GameSpeed>),
+            );
+
+        for state in [
+            GameState::Paused,
+            GameState::Ui,
+            GameState::Shopping,
+            GameState::LevelUp,
+        ] {
+            app.add_systems(OnEnter(state), pause_game_time)
+                .add_systems(OnExit(state), resume_game_time);
+        }
     }
 }
 
+fn apply_game_speed(game_speed: Res<GameSpeed>, mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(game_speed.0);
+}
+
+fn pause_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_game_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
 fn load_assets(
     mut commands: Commands,
     mut handle: ResMut<GlobalTextureAtlas>,
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    configs::SEED_ENV_VAR, game_state::GameState, replay::ReplayPlayback, save::LoadedRun,
+    world::spawn_background,
+};
+
+pub type RngStream = ChaCha8Rng;
+
+pub struct RngPlugin;
+
+#[derive(Resource, Default)]
+pub struct RunSeed(pub Option<u64>);
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct GameRng {
+    pub seed: u64,
+    pub world: RngStream,
+    pub enemy: RngStream,
+    pub ai: RngStream,
+    pub loot: RngStream,
+    pub combat: RngStream,
+    pub perk: RngStream,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        let stream = |id: u64| {
+            let mut rng = RngStream::seed_from_u64(seed);
+            rng.set_stream(id);
+            rng
+        };
+        Self {
+            seed,
+            world: stream(0),
+            enemy: stream(1),
+            ai: stream(2),
+            loot: stream(3),
+            combat: stream(4),
+            perk: stream(5),
+        }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::random())
+    }
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .insert_resource(RunSeed(seed_from_env()))
+            .add_systems(
+                OnEnter(GameState::Initializing),
+                seed_game_rng.before(spawn_background),
+            );
+    }
+}
+
+fn seed_from_env() -> Option<u64> {
+    let value = std::env::var(SEED_ENV_VAR).ok()?;
+    match value.trim().parse() {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            warn!("Ignoring invalid {}={:?}: {}", SEED_ENV_VAR, value, err);
+            None
+        }
+    }
+}
+
+fn seed_game_rng(
+    mut rng: ResMut<GameRng>,
+    run_seed: Res<RunSeed>,
+    loaded_run: Option<Res<LoadedRun>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if let Some(saved_rng) = loaded_run.and_then(|loaded_run| loaded_run.0.rng.clone()) {
+        *rng = saved_rng;
+        info!("Resuming run with seed {}", rng.seed);
+        return;
+    }
+
+    if let Some(playback) = playback {
+        *rng = GameRng::from_seed(playback.seed());
+        info!("Replaying run with seed {}", rng.seed);
+        return;
+    }
+
+    *rng = GameRng::from_seed(run_seed.0.unwrap_or_else(rand::random));
+    info!("Starting run with seed {}", rng.seed);
+}
@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    armor::ArmorStats,
+    class::PlayerClass,
+    configs::{DEFAULT_CLASS, SAVE_FILE_PATH, SAVE_VERSION},
+    coop::CoopSession,
+    game_state::GameState,
+    gun::{BulletStats, GunStats, GunType},
+    loot::{Description, Value},
+    perk::Perks,
+    player::{Gold, Health, Player, PlayerInventory},
+    potion::{PotionStats, PotionType},
+    replay::ReplayPlayback,
+    resources::{GameMode, Level, Wave},
+    rng::GameRng,
+    stats::{StatKind, Stats},
+};
+
+pub struct SavePlugin;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedItem {
+    pub name: String,
+    pub description: String,
+    pub value: u32,
+    pub sprite_index: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedGun {
+    pub item: SavedItem,
+    pub gun_type: GunType,
+    pub gun_stats: GunStats,
+    pub bullet_stats: BulletStats,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedArmor {
+    pub item: SavedItem,
+    pub stats: ArmorStats,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedPotion {
+    pub item: SavedItem,
+    pub potion_type: PotionType,
+    pub stats: PotionStats,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedPlayer {
+    pub health: u32,
+    pub max_health: u32,
+    pub speed: u32,
+    pub defense: u32,
+    pub gold: u32,
+    pub damage_boost: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveData {
+    pub version: u32,
+    pub game_mode: GameMode,
+    pub wave: u32,
+    pub level: Level,
+    pub player: SavedPlayer,
+    pub guns: Vec<SavedGun>,
+    pub active_gun_index: usize,
+    pub health_potions: Vec<SavedPotion>,
+    pub speed_potions: Vec<SavedPotion>,
+    pub armors: Vec<SavedArmor>,
+    pub active_armor_index: usize,
+    #[serde(default)]
+    pub rng: Option<GameRng>,
+    #[serde(default)]
+    pub perks: Perks,
+    #[serde(default = "default_player_class")]
+    pub player_class: String,
+}
+
+#[derive(Resource)]
+pub struct LoadedRun(pub SaveData);
+
+pub fn default_player_class() -> String {
+    DEFAULT_CLASS.to_string()
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            save_run_on_wave_start.run_if(
+                in_state(GameState::Combat)
+                    .and(resource_changed::<Wave>)
+                    .and(not(resource_exists::<ReplayPlayback>)),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::End),
+            delete_save.run_if(not(resource_exists::<ReplayPlayback>)),
+        )
+        .add_systems(
+            OnEnter(GameState::Win),
+            delete_save.run_if(not(resource_exists::<ReplayPlayback>)),
+        );
+    }
+}
+
+pub fn load_save() -> Option<SaveData> {
+    let contents = read_save_file()?;
+    match ron::from_str::<SaveData>(&contents) {
+        Ok(save) if save.version == SAVE_VERSION => Some(save),
+        Ok(save) => {
+            warn!(
+                "Ignoring save file with version {} (expected {})",
+                save.version, SAVE_VERSION
+            );
+            None
+        }
+        Err(err) => {
+            warn!("Failed to parse save file: {}", err);
+            None
+        }
+    }
+}
+
+fn save_run_on_wave_start(
+    wave: Res<Wave>,
+    level: Res<Level>,
+    game_mode: Res<GameMode>,
+    player_class: Res<PlayerClass>,
+    rng: Res<GameRng>,
+    coop: Res<CoopSession>,
+    player_query: Query<(&Health, &Stats, &Gold, &PlayerInventory, &Perks), With<Player>>,
+    gun_query: Query<(
+        &Description,
+        &Value,
+        &Sprite,
+        &GunType,
+        &GunStats,
+        &BulletStats,
+    )>,
+    armor_query: Query<(&Description, &Value, &Sprite, &ArmorStats)>,
+    potion_query: Query<(&Description, &Value, &Sprite, &PotionType, &PotionStats)>,
+) {
+    if wave.number == 0 || coop.is_active() {
+        return;
+    }
+    let Ok((health, stats, gold, inventory, perks)) = player_query.get_single() else {
+        return;
+    };
+
+    let guns = inventory
+        .guns
+        .iter()
+        .filter_map(|entity| gun_query.get(*entity).ok())
+        .map(
+            |(description, value, sprite, gun_type, gun_stats, bullet_stats)| SavedGun {
+                item: saved_item(description, value, sprite),
+                gun_type: *gun_type,
+                gun_stats: gun_stats.clone(),
+                bullet_stats: bullet_stats.clone(),
+            },
+        )
+        .collect();
+    let armors = inventory
+        .armors
+        .iter()
+        .filter_map(|entity| armor_query.get(*entity).ok())
+        .map(|(description, value, sprite, stats)| SavedArmor {
+            item: saved_item(description, value, sprite),
+            stats: stats.clone(),
+        })
+        .collect();
+    let saved_potions = |potions: &Vec<Entity>| {
+        potions
+            .iter()
+            .filter_map(|entity| potion_query.get(*entity).ok())
+            .map(
+                |(description, value, sprite, potion_type, stats)| SavedPotion {
+                    item: saved_item(description, value, sprite),
+                    potion_type: potion_type.clone(),
+                    stats: stats.clone(),
+                },
+            )
+            .collect::<Vec<_>>()
+    };
+
+    let save = SaveData {
+        version: SAVE_VERSION,
+        game_mode: *game_mode,
+        wave: wave.number,
+        level: level.clone(),
+        player: SavedPlayer {
+            health: health.0,
+            max_health: stats.base(StatKind::MaxHealth) as u32,
+            speed: stats.base(StatKind::Speed) as u32,
+            defense: stats.base(StatKind::Defense) as u32,
+            gold: gold.0,
+            damage_boost: stats.base(StatKind::DamageBoost) as u32,
+        },
+        guns,
+        active_gun_index: inventory.active_gun_index,
+        health_potions: saved_potions(&inventory.health_potions),
+        speed_potions: saved_potions(&inventory.speed_potions),
+        armors,
+        active_armor_index: inventory.active_armor_index,
+        rng: Some(rng.clone()),
+        perks: perks.clone(),
+        player_class: player_class.0.clone(),
+    };
+
+    match ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => write_save_file(&contents),
+        Err(err) => warn!("Failed to serialize save: {}", err),
+    }
+}
+
+fn saved_item(description: &Description, value: &Value, sprite: &Sprite) -> SavedItem {
+    SavedItem {
+        name: description.name.clone(),
+        description: description.description.clone(),
+        value: value.0,
+        sprite_index: sprite
+            .texture_atlas
+            .as_ref()
+            .map_or(0, |texture_atlas| texture_atlas.index),
+    }
+}
+
+fn delete_save() {
+    remove_save_file();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_save_file() -> Option<String> {
+    std::fs::read_to_string(SAVE_FILE_PATH).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save_file(contents: &str) {
+    if let Err(err) = std::fs::write(SAVE_FILE_PATH, contents) {
+        warn!("Failed to write save file: {}", err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn remove_save_file() {
+    let _ = std::fs::remove_file(SAVE_FILE_PATH);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_save_file() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_save_file(_contents: &str) {}
+
+#[cfg(target_arch = "wasm32")]
+fn remove_save_file() {}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bindings::Bindings,
+    configs::{SETTINGS_FILE_PATH, SETTINGS_VERSION},
+};
+
+pub struct SettingsPlugin;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Settings {
+    pub version: u32,
+    pub bindings: Bindings,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_settings).add_systems(
+            Update,
+            save_settings.run_if(resource_changed::<Bindings>.and(not(resource_added::<Bindings>))),
+        );
+    }
+}
+
+fn load_settings(mut commands: Commands) {
+    let Some(contents) = read_settings_file() else {
+        return;
+    };
+    match ron::from_str::<Settings>(&contents) {
+        Ok(settings) if settings.version == SETTINGS_VERSION => {
+            commands.insert_resource(settings.bindings.combat_input_map());
+            commands.insert_resource(settings.bindings.menu_input_map());
+            commands.insert_resource(settings.bindings);
+        }
+        Ok(settings) => warn!(
+            "Ignoring settings file with version {} (expected {})",
+            settings.version, SETTINGS_VERSION
+        ),
+        Err(err) => warn!("Failed to parse settings file: {}", err),
+    }
+}
+
+fn save_settings(bindings: Res<Bindings>) {
+    let settings = Settings {
+        version: SETTINGS_VERSION,
+        bindings: bindings.clone(),
+    };
+    match ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => write_settings_file(&contents),
+        Err(err) => warn!("Failed to serialize settings: {}", err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_settings_file() -> Option<String> {
+    std::fs::read_to_string(SETTINGS_FILE_PATH).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_settings_file(contents: &str) {
+    if let Err(err) = std::fs::write(SETTINGS_FILE_PATH, contents) {
+        warn!("Failed to write settings file: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_settings_file() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_settings_file(_contents: &str) {}
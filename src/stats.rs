@@ -0,0 +1,186 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    enemy::Enemy,
+    game_state::{GameState, GameplaySet},
+    player::{DamageBoost, Defense, Health, Speed},
+};
+
+pub struct StatsPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum StatKind {
+    MaxHealth,
+    Speed,
+    Defense,
+    DamageBoost,
+    Damage,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModifierOp {
+    Add(f32),
+    Multiply(f32),
+}
+
+#[derive(Clone, Debug)]
+pub struct StatModifier {
+    pub stat: StatKind,
+    pub op: ModifierOp,
+    pub source: String,
+    pub expiry: Option<Timer>,
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct Stats {
+    base: HashMap<StatKind, f32>,
+    modifiers: Vec<StatModifier>,
+}
+
+impl StatModifier {
+    pub fn add(stat: StatKind, amount: f32, source: impl Into<String>) -> Self {
+        Self {
+            stat,
+            op: ModifierOp::Add(amount),
+            source: source.into(),
+            expiry: None,
+        }
+    }
+
+    pub fn multiply(stat: StatKind, factor: f32, source: impl Into<String>) -> Self {
+        Self {
+            stat,
+            op: ModifierOp::Multiply(factor),
+            source: source.into(),
+            expiry: None,
+        }
+    }
+
+    pub fn with_duration(mut self, seconds: f32) -> Self {
+        self.expiry = Some(Timer::from_seconds(seconds, TimerMode::Once));
+        self
+    }
+}
+
+impl Stats {
+    pub fn new(base: impl IntoIterator<Item = (StatKind, f32)>) -> Self {
+        Self {
+            base: base.into_iter().collect(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn base(&self, stat: StatKind) -> f32 {
+        self.base.get(&stat).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_base(&mut self, stat: StatKind, value: f32) {
+        self.base.insert(stat, value);
+    }
+
+    pub fn add_base(&mut self, stat: StatKind, amount: f32) {
+        *self.base.entry(stat).or_default() += amount;
+    }
+
+    pub fn add_modifier(&mut self, modifier: StatModifier) {
+        self.modifiers.push(modifier);
+    }
+
+    pub fn remove_modifiers_from(&mut self, source: &str) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+    }
+
+    pub fn has_modifier_from(&self, source: &str) -> bool {
+        self.modifiers
+            .iter()
+            .any(|modifier| modifier.source == source)
+    }
+
+    pub fn modifiers(&self) -> &[StatModifier] {
+        &self.modifiers
+    }
+
+    pub fn value(&self, stat: StatKind) -> f32 {
+        let mut additive = 0.0;
+        let mut multiplier = 1.0;
+        for modifier in self
+            .modifiers
+            .iter()
+            .filter(|modifier| modifier.stat == stat)
+        {
+            match modifier.op {
+                ModifierOp::Add(amount) => additive += amount,
+                ModifierOp::Multiply(factor) => multiplier *= factor,
+            }
+        }
+        ((self.base(stat) + additive) * multiplier).max(0.0)
+    }
+
+    pub fn value_u32(&self, stat: StatKind) -> u32 {
+        self.value(stat).round() as u32
+    }
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                expire_stat_modifiers,
+                (apply_player_stats, apply_enemy_stats),
+            )
+                .chain()
+                .in_set(GameplaySet::Stats)
+                .run_if(in_state(GameState::Combat)),
+        );
+    }
+}
+
+fn expire_stat_modifiers(time: Res<Time>, mut stats_query: Query<&mut Stats>) {
+    for mut stats in stats_query.iter_mut() {
+        let mut expired = false;
+        for modifier in stats.bypass_change_detection().modifiers.iter_mut() {
+            if let Some(expiry) = modifier.expiry.as_mut() {
+                expiry.tick(time.delta());
+                expired |= expiry.finished();
+            }
+        }
+
+        if expired {
+            stats.modifiers.retain(|modifier| {
+                modifier
+                    .expiry
+                    .as_ref()
+                    .map_or(true, |expiry| !expiry.finished())
+            });
+        }
+    }
+}
+
+fn apply_player_stats(
+    mut player_query: Query<
+        (
+            &Stats,
+            &mut Health,
+            &mut Speed,
+            &mut Defense,
+            &mut DamageBoost,
+        ),
+        Changed<Stats>,
+    >,
+) {
+    for (stats, mut health, mut speed, mut defense, mut damage_boost) in player_query.iter_mut() {
+        health.1 = stats.value_u32(StatKind::MaxHealth);
+        speed.0 = stats.value_u32(StatKind::Speed);
+        defense.0 = stats.value_u32(StatKind::Defense);
+        damage_boost.0 = stats.value_u32(StatKind::DamageBoost);
+    }
+}
+
+fn apply_enemy_stats(mut enemy_query: Query<(&Stats, &mut Enemy), Changed<Stats>>) {
+    for (stats, mut enemy) in enemy_query.iter_mut() {
+        enemy.speed = stats.value_u32(StatKind::Speed);
+        enemy.damage = stats.value_u32(StatKind::Damage);
+    }
+}
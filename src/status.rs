@@ -0,0 +1,378 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    configs::LAYER3,
+    damage::{DamageEvent, DamageKind, DamageSource},
+    enemy::Enemy,
+    game_state::{GameState, GameplaySet},
+    player::{Health, Player},
+    stats::{StatKind, StatModifier, Stats},
+};
+
+pub struct StatusPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StatusKind {
+    Burn,
+    Poison,
+    Slow,
+    Freeze,
+    Regen,
+    Shield,
+    Haste,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackRule {
+    Refresh,
+    Stack(u32),
+    Ignore,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub stacks: u32,
+    pub duration: Timer,
+    pub tick: Option<Timer>,
+    pub source: DamageSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct StatusSpec {
+    pub kind: StatusKind,
+    pub magnitude: f32,
+    pub duration: f32,
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+#[derive(Event, Clone, Debug)]
+pub struct ApplyStatusEvent {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct StatusTickEvent {
+    pub target: Entity,
+    pub kind: StatusKind,
+    pub amount: f32,
+    pub source: DamageSource,
+}
+
+#[derive(Component)]
+pub struct StatusIndicator;
+
+impl StatusKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::Burn => "Burn",
+            StatusKind::Poison => "Poison",
+            StatusKind::Slow => "Slow",
+            StatusKind::Freeze => "Freeze",
+            StatusKind::Regen => "Regen",
+            StatusKind::Shield => "Shield",
+            StatusKind::Haste => "Haste",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusKind::Burn => "BRN",
+            StatusKind::Poison => "PSN",
+            StatusKind::Slow => "SLW",
+            StatusKind::Freeze => "FRZ",
+            StatusKind::Regen => "RGN",
+            StatusKind::Shield => "SHD",
+            StatusKind::Haste => "HST",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            StatusKind::Burn => Color::srgb(1.0, 0.45, 0.1),
+            StatusKind::Poison => Color::srgb(0.45, 0.85, 0.2),
+            StatusKind::Slow => Color::srgb(0.55, 0.45, 0.85),
+            StatusKind::Freeze => Color::srgb(0.55, 0.9, 1.0),
+            StatusKind::Regen => Color::srgb(0.2, 1.0, 0.5),
+            StatusKind::Shield => Color::srgb(0.95, 0.85, 0.3),
+            StatusKind::Haste => Color::srgb(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn stack_rule(self) -> StackRule {
+        match self {
+            StatusKind::Poison => StackRule::Stack(5),
+            StatusKind::Freeze | StatusKind::Haste => StackRule::Ignore,
+            _ => StackRule::Refresh,
+        }
+    }
+
+    pub fn tick_interval(self) -> Option<f32> {
+        match self {
+            StatusKind::Burn => Some(0.5),
+            StatusKind::Poison | StatusKind::Regen => Some(1.0),
+            _ => None,
+        }
+    }
+
+    fn modifier(self, strength: f32) -> Option<StatModifier> {
+        match self {
+            StatusKind::Slow => Some(StatModifier::multiply(
+                StatKind::Speed,
+                (1.0 - strength).clamp(0.0, 1.0),
+                self.name(),
+            )),
+            StatusKind::Freeze => Some(StatModifier::multiply(StatKind::Speed, 0.0, self.name())),
+            StatusKind::Haste => Some(StatModifier::add(StatKind::Speed, strength, self.name())),
+            _ => None,
+        }
+    }
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, magnitude: f32, seconds: f32, source: DamageSource) -> Self {
+        Self {
+            kind,
+            magnitude,
+            stacks: 1,
+            duration: Timer::from_seconds(seconds, TimerMode::Once),
+            tick: kind
+                .tick_interval()
+                .map(|interval| Timer::from_seconds(interval, TimerMode::Repeating)),
+            source,
+        }
+    }
+
+    pub fn strength(&self) -> f32 {
+        self.magnitude * self.stacks as f32
+    }
+
+    pub fn remaining_secs(&self) -> f32 {
+        self.duration.remaining_secs()
+    }
+}
+
+impl StatusSpec {
+    pub fn effect(&self, source: DamageSource) -> StatusEffect {
+        StatusEffect::new(self.kind, self.magnitude, self.duration, source)
+    }
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: StatusKind) -> Option<&StatusEffect> {
+        self.0.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn apply(&mut self, effect: StatusEffect) -> bool {
+        let Some(existing) = self.0.iter_mut().find(|active| active.kind == effect.kind) else {
+            self.0.push(effect);
+            return true;
+        };
+
+        match effect.kind.stack_rule() {
+            StackRule::Ignore => return false,
+            StackRule::Refresh => {
+                existing.magnitude = existing.magnitude.max(effect.magnitude);
+            }
+            StackRule::Stack(max_stacks) => {
+                existing.stacks = (existing.stacks + 1).min(max_stacks);
+            }
+        }
+        if effect.duration.remaining_secs() > existing.duration.remaining_secs() {
+            existing.duration = effect.duration;
+        }
+        existing.source = effect.source;
+        true
+    }
+
+    pub fn remove(&mut self, kind: StatusKind) {
+        self.0.retain(|effect| effect.kind != kind);
+    }
+
+    pub fn absorb(&mut self, amount: u32) -> u32 {
+        let Some(shield) = self
+            .0
+            .iter_mut()
+            .find(|effect| effect.kind == StatusKind::Shield)
+        else {
+            return amount;
+        };
+
+        let absorbed = (amount as f32).min(shield.magnitude);
+        shield.magnitude -= absorbed;
+        if shield.magnitude < 1.0 {
+            self.remove(StatusKind::Shield);
+        }
+        amount - absorbed as u32
+    }
+}
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatusEvent>()
+            .add_event::<StatusTickEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_status_events.run_if(on_event::<ApplyStatusEvent>),
+                    tick_status_effects,
+                    handle_status_ticks.run_if(on_event::<StatusTickEvent>),
+                )
+                    .chain()
+                    .in_set(GameplaySet::Status)
+                    .run_if(in_state(GameState::Combat)),
+            )
+            .add_systems(
+                Update,
+                update_status_indicators.run_if(in_state(GameState::Combat)),
+            );
+    }
+}
+
+fn sync_status_modifiers(stats: &mut Stats, effects: &StatusEffects) {
+    for kind in [StatusKind::Slow, StatusKind::Freeze, StatusKind::Haste] {
+        stats.remove_modifiers_from(kind.name());
+        if let Some(modifier) = effects
+            .get(kind)
+            .and_then(|effect| kind.modifier(effect.strength()))
+        {
+            stats.add_modifier(modifier);
+        }
+    }
+}
+
+fn apply_status_events(
+    mut events: EventReader<ApplyStatusEvent>,
+    mut target_query: Query<(&mut StatusEffects, Option<&mut Stats>)>,
+) {
+    for event in events.read() {
+        let Ok((mut effects, stats)) = target_query.get_mut(event.target) else {
+            continue;
+        };
+        if effects.apply(event.effect.clone()) {
+            if let Some(mut stats) = stats {
+                sync_status_modifiers(&mut stats, &effects);
+            }
+        }
+    }
+}
+
+fn tick_status_effects(
+    time: Res<Time>,
+    mut target_query: Query<(Entity, &mut StatusEffects, Option<&mut Stats>)>,
+    mut ev_tick: EventWriter<StatusTickEvent>,
+) {
+    for (entity, mut effects, stats) in target_query.iter_mut() {
+        if effects.is_empty() {
+            continue;
+        }
+
+        let mut expired = false;
+        for effect in effects.bypass_change_detection().0.iter_mut() {
+            effect.duration.tick(time.delta());
+            expired |= effect.duration.finished();
+
+            let Some(tick) = effect.tick.as_mut() else {
+                continue;
+            };
+            tick.tick(time.delta());
+            for _ in 0..tick.times_finished_this_tick() {
+                ev_tick.send(StatusTickEvent {
+                    target: entity,
+                    kind: effect.kind,
+                    amount: effect.strength(),
+                    source: effect.source.clone(),
+                });
+            }
+        }
+
+        if expired {
+            effects.0.retain(|effect| !effect.duration.finished());
+            if let Some(mut stats) = stats {
+                sync_status_modifiers(&mut stats, &effects);
+            }
+        }
+    }
+}
+
+fn handle_status_ticks(
+    mut events: EventReader<StatusTickEvent>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut player_query: Query<&mut Health, With<Player>>,
+    mut enemy_query: Query<(&mut Enemy, &Stats)>,
+) {
+    for event in events.read() {
+        match event.kind {
+            StatusKind::Burn | StatusKind::Poison => {
+                ev_damage.send(DamageEvent {
+                    source: event.source.clone(),
+                    target: event.target,
+                    amount: event.amount.round() as u32,
+                    kind: DamageKind::Status,
+                });
+            }
+            StatusKind::Regen => {
+                let amount = event.amount.round() as u32;
+                if let Ok(mut health) = player_query.get_mut(event.target) {
+                    if health.0 > 0 {
+                        health.0 = (health.0 + amount).min(health.1);
+                    }
+                } else if let Ok((mut enemy, stats)) = enemy_query.get_mut(event.target) {
+                    if enemy.health > 0 {
+                        enemy.health =
+                            (enemy.health + amount).min(stats.value_u32(StatKind::MaxHealth));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_status_indicators(
+    mut commands: Commands,
+    target_query: Query<(Entity, &StatusEffects), Changed<StatusEffects>>,
+    indicator_query: Query<(Entity, &Parent), With<StatusIndicator>>,
+    mut removed: RemovedComponents<StatusEffects>,
+) {
+    let removed: Vec<Entity> = removed.read().collect();
+    for (indicator, parent) in indicator_query.iter() {
+        if removed.contains(&parent.get()) || target_query.contains(parent.get()) {
+            commands.entity(indicator).despawn_recursive();
+        }
+    }
+
+    for (entity, effects) in target_query.iter() {
+        let count = effects.0.len() as f32;
+        commands.entity(entity).with_children(|parent| {
+            for (i, effect) in effects.iter().enumerate() {
+                parent.spawn((
+                    Sprite {
+                        color: effect.kind.color(),
+                        custom_size: Some(Vec2::splat(2.0)),
+                        ..default()
+                    },
+                    Transform::from_xyz((i as f32 - (count - 1.0) / 2.0) * 3.0, 10.0, LAYER3),
+                    StatusIndicator,
+                ));
+            }
+        });
+    }
+}
@@ -2,7 +2,7 @@ use bevy::utils::{Duration, Instant};
 
 use bevy::prelude::*;
 
-use crate::loot::LootType;
+use crate::{bindings::Control, loot::LootType, status::StatusKind};
 
 #[derive(Component)]
 pub struct PauseMenuRoot;
@@ -32,6 +32,8 @@ pub struct MainMenuRoot;
 
 #[derive(Component)]
 pub enum MainMenuButton {
+    Continue,
+    Replay,
     StartNormal,
     StartForever,
     Control,
@@ -46,6 +48,41 @@ pub struct ControlWidget;
 #[derive(Component)]
 pub struct BlinkingText;
 
+#[derive(Component)]
+pub struct DeviceGlyphText {
+    pub template: &'static str,
+    pub control: Control,
+}
+
+#[derive(Component)]
+pub struct ControlRow(pub usize);
+
+#[derive(Component, PartialEq, Eq, Clone, Copy)]
+pub enum ControlColumn {
+    Label,
+    Keyboard,
+    Gamepad,
+}
+
+#[derive(Component)]
+pub struct ControlRowText(pub usize, pub ControlColumn);
+
+#[derive(Component)]
+pub struct ControlStatusText;
+
+#[derive(Resource, Default)]
+pub struct RebindState {
+    pub selected: usize,
+    pub capturing: bool,
+    pub message: String,
+}
+
+impl RebindState {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 #[derive(Component)]
 pub struct ShopMenuRoot;
 
@@ -61,6 +98,21 @@ pub enum ShopMenuButton {
     BuyXP,
 }
 
+#[derive(Component)]
+pub struct ClassSelectMenuRoot;
+
+#[derive(Component)]
+pub struct CoopJoinText;
+
+#[derive(Component)]
+pub struct ClassButtonIndex(pub u8);
+
+#[derive(Component)]
+pub struct LevelUpMenuRoot;
+
+#[derive(Component)]
+pub struct PerkButtonIndex(pub u8);
+
 #[derive(Component)]
 pub struct FloatingTextBox {
     pub spawn_time: Instant,
@@ -109,9 +161,27 @@ pub struct PlayerDamageBoostText;
 #[derive(Component)]
 pub struct UiRoot;
 
+#[derive(Component)]
+pub struct DashCooldownBar(pub Entity);
+
 #[derive(Component)]
 pub struct WaveDisplayRoot;
 
+#[derive(Component)]
+pub struct StatusBarRoot;
+
+#[derive(Component)]
+pub struct BossBarRoot;
+
+#[derive(Component)]
+pub struct BossBarFill;
+
+#[derive(Component)]
+pub struct BossBarText;
+
+#[derive(Component)]
+pub struct StatusIconText(pub StatusKind);
+
 #[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
 pub struct GridSlot {
     pub x: usize,
@@ -1,16 +1,17 @@
 use super::{
-    components::LootSaleEvent,
+    components::{LootSaleEvent, RebindState},
     systems::{
+        controls::{handle_control_widget, update_control_widget, update_device_glyph_text},
         in_game_ui::update_floating_text,
         loot_grid::highlight_active_item,
         menus::{
-            despawn_floating_text_box, handle_control_widget, handle_shop_input,
-            handle_shop_menu_buttons, setup_shop_menu,
+            despawn_floating_text_box, handle_shop_input, handle_shop_menu_buttons, setup_shop_menu,
         },
     },
 };
 use crate::{
     game_state::GameState,
+    perk::{handle_perk_choice, PerkChoices},
     ui::systems::{
         in_game_ui, loot_grid,
         menus::{self, handle_end_screen_input, set_up_death_screen, set_up_win_screen},
@@ -21,7 +22,7 @@ use crate::{
 };
 use bevy::{
     app::{App, Plugin, Update},
-    prelude::{in_state, Condition, IntoSystemConfigs, OnEnter, OnExit},
+    prelude::{in_state, resource_changed, Condition, IntoSystemConfigs, OnEnter, OnExit},
 };
 
 pub struct UiPlugin;
@@ -29,6 +30,7 @@ pub struct UiPlugin;
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LootSaleEvent>()
+            .init_resource::<RebindState>()
             .add_systems(
                 OnEnter(GameState::Initializing),
                 (
@@ -36,6 +38,7 @@ impl Plugin for UiPlugin {
                     menus::setup_pause_menu,
                     setup_shop_menu,
                     in_game_ui::setup_health_bar.after(init_world),
+                    player_info::setup_dash_indicators.after(init_world),
                 ),
             )
             .add_systems(
@@ -47,22 +50,43 @@ impl Plugin for UiPlugin {
                 (menus::setup_main_menu, cleanup_entities),
             )
             .add_systems(OnExit(GameState::MainMenu), menus::despawn_main_menu)
-            .add_systems(OnEnter(GameState::Combat), in_game_ui::setup_wave_display)
+            .add_systems(
+                OnEnter(GameState::ClassSelect),
+                menus::spawn_class_select_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    menus::highlight_selected_class,
+                    menus::update_coop_join_text,
+                )
+                    .run_if(in_state(GameState::ClassSelect)),
+            )
+            .add_systems(
+                OnExit(GameState::ClassSelect),
+                menus::despawn_class_select_menu,
+            )
+            .add_systems(
+                OnEnter(GameState::Combat),
+                (in_game_ui::setup_wave_display, in_game_ui::setup_status_bar),
+            )
             .add_systems(
                 Update,
                 (
-                    (menus::handle_main_menu_buttons, handle_control_widget)
+                    (
+                        menus::handle_main_menu_buttons,
+                        menus::update_continue_button,
+                        (handle_control_widget, update_control_widget).chain(),
+                    )
                         .run_if(in_state(GameState::MainMenu)),
                     menus::blink_text,
+                    update_device_glyph_text,
                 ),
             )
             .add_systems(
                 Update,
-                (player_info::toggle_loot_ui_visibility.run_if(
-                    in_state(GameState::Combat)
-                        .or(in_state(GameState::Ui))
-                        .or(in_state(GameState::Paused)),
-                ),),
+                (player_info::toggle_loot_ui_visibility
+                    .run_if(in_state(GameState::Combat).or(in_state(GameState::Ui))),),
             )
             .add_systems(OnEnter(GameState::Ui), loot_grid::set_up_loot_image)
             .add_systems(
@@ -72,7 +96,13 @@ impl Plugin for UiPlugin {
             )
             .add_systems(
                 Update,
-                (menus::handle_pause_input, in_game_ui::update_health_bar)
+                (
+                    menus::handle_pause_input,
+                    in_game_ui::update_health_bar,
+                    in_game_ui::update_status_bar,
+                    in_game_ui::update_boss_bar,
+                    player_info::update_dash_indicator,
+                )
                     .run_if(in_state(GameState::Combat).or(in_state(GameState::Paused))),
             )
             .add_systems(
@@ -96,6 +126,17 @@ impl Plugin for UiPlugin {
                 ),
             )
             .add_systems(OnEnter(GameState::Win), set_up_win_screen)
+            .add_systems(
+                Update,
+                (
+                    menus::spawn_level_up_menu.run_if(resource_changed::<PerkChoices>),
+                    menus::highlight_selected_perk,
+                )
+                    .chain()
+                    .after(handle_perk_choice)
+                    .run_if(in_state(GameState::LevelUp)),
+            )
+            .add_systems(OnExit(GameState::LevelUp), menus::despawn_level_up_menu)
             .add_systems(
                 Update,
                 handle_shop_input
@@ -0,0 +1,306 @@
+use bevy::{
+    color::Color,
+    hierarchy::{BuildChildren, ChildBuild, DespawnRecursiveExt},
+    prelude::*,
+};
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    audio::AudioEvent,
+    bindings::{Bindings, Control, InputBinding},
+    configs::{INSPECTOR_TOGGLE_KEY, UI_BG_COLOR},
+    input::{InputDevice, MenuAction, NavigationRepeat},
+    ui::components::{
+        ControlColumn, ControlRow, ControlRowText, ControlStatusText, ControlWidget,
+        DeviceGlyphText, MainMenuRoot, RebindState,
+    },
+};
+
+const RESET_ROW: usize = Control::ALL.len();
+const BACK_ROW: usize = Control::ALL.len() + 1;
+
+pub fn setup_control_widget(commands: &mut Commands, font: Handle<Font>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ControlWidget,
+            BackgroundColor(Color::srgb_u8(UI_BG_COLOR.0, UI_BG_COLOR.1, UI_BG_COLOR.2)),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(900.0),
+                        height: Val::Px(700.0),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceEvenly,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderRadius::all(Val::Px(10.0)),
+                    BackgroundColor(Color::srgba_u8(217, 234, 146, 255)),
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            Node {
+                                width: Val::Auto,
+                                height: Val::Auto,
+                                border: UiRect::bottom(Val::Px(4.0)),
+                                ..default()
+                            },
+                            BorderColor(Color::BLACK),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new("Control Bindings"),
+                                TextFont {
+                                    font: font.clone(),
+                                    font_size: 40.0,
+                                    ..default()
+                                },
+                                TextColor(Color::BLACK),
+                            ));
+                        });
+                    spawn_control_row(parent, None, &font);
+                    for index in 0..Control::ALL.len() {
+                        spawn_control_row(parent, Some(index), &font);
+                    }
+                    spawn_control_row(parent, Some(RESET_ROW), &font);
+                    spawn_control_row(parent, Some(BACK_ROW), &font);
+                    parent.spawn((
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(Color::BLACK),
+                        ControlStatusText,
+                    ));
+                });
+        });
+}
+
+fn spawn_control_row(parent: &mut ChildBuilder, index: Option<usize>, font: &Handle<Font>) {
+    let mut row = parent.spawn((
+        Node {
+            width: Val::Px(820.0),
+            height: Val::Px(24.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderRadius::all(Val::Px(4.0)),
+        BackgroundColor(Color::NONE),
+    ));
+    if let Some(index) = index {
+        row.insert(ControlRow(index));
+    }
+    row.with_children(|parent| {
+        for (column, width) in [
+            (ControlColumn::Label, 340.0),
+            (ControlColumn::Keyboard, 260.0),
+            (ControlColumn::Gamepad, 220.0),
+        ] {
+            let header = match column {
+                ControlColumn::Label => "Action",
+                ControlColumn::Keyboard => "Keyboard",
+                ControlColumn::Gamepad => "Gamepad",
+            };
+            let mut text = parent.spawn((
+                Node {
+                    width: Val::Px(width),
+                    ..default()
+                },
+                Text::new(if index.is_none() { header } else { "" }),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::BLACK),
+            ));
+            if let Some(index) = index {
+                text.insert(ControlRowText(index, column));
+            }
+        }
+    });
+}
+
+pub fn handle_control_widget(
+    mut commands: Commands,
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut bindings: ResMut<Bindings>,
+    mut rebind_state: ResMut<RebindState>,
+    query: Query<Entity, With<ControlWidget>>,
+    mut visibility_query: Query<&mut Visibility, With<MainMenuRoot>>,
+    mut ew: EventWriter<AudioEvent>,
+) {
+    let Ok(entity) = query.get_single() else {
+        return;
+    };
+    let row_count = BACK_ROW + 1;
+
+    if rebind_state.capturing {
+        let control = Control::ALL[rebind_state.selected];
+        if keys.just_pressed(KeyCode::Escape) {
+            rebind_state.capturing = false;
+            rebind_state.message = "Rebinding cancelled".to_string();
+            return;
+        }
+        let captured = keys
+            .get_just_pressed()
+            .next()
+            .map(|key| InputBinding::Key(*key))
+            .or_else(|| {
+                mouse
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| InputBinding::Mouse(*button))
+            })
+            .or_else(|| {
+                gamepads.iter().find_map(|gamepad| {
+                    gamepad
+                        .get_just_pressed()
+                        .next()
+                        .map(|button| InputBinding::Gamepad(*button))
+                })
+            });
+        let Some(input) = captured else {
+            return;
+        };
+        if input == InputBinding::Key(INSPECTOR_TOGGLE_KEY) {
+            rebind_state.message = format!("{} is reserved for the inspector", input.label());
+            return;
+        }
+        if control.keys_only() && !matches!(input, InputBinding::Key(_)) {
+            rebind_state.message = format!("{} can only be bound to a key", control.label());
+            return;
+        }
+
+        bindings.rebind(control, input);
+        rebind_state.capturing = false;
+        rebind_state.message = format!("{} bound to {}", control.label(), input.label());
+        ew.send(AudioEvent::PopUp);
+        return;
+    }
+
+    if navigation.triggered(MenuAction::NavigateUp) {
+        rebind_state.selected = (rebind_state.selected + row_count - 1) % row_count;
+        ew.send(AudioEvent::UI);
+    }
+
+    if navigation.triggered(MenuAction::NavigateDown) {
+        rebind_state.selected = (rebind_state.selected + 1) % row_count;
+        ew.send(AudioEvent::UI);
+    }
+
+    let mut close = action_state.just_pressed(&MenuAction::Back);
+    if action_state.just_pressed(&MenuAction::Confirm) {
+        ew.send(AudioEvent::PopUp);
+        match rebind_state.selected {
+            RESET_ROW => {
+                *bindings = Bindings::default();
+                rebind_state.message = "Restored default bindings".to_string();
+            }
+            BACK_ROW => close = true,
+            selected => {
+                rebind_state.capturing = true;
+                rebind_state.message = format!(
+                    "Press a key or button for {} (ESC to cancel)",
+                    Control::ALL[selected].label()
+                );
+            }
+        }
+    }
+
+    if close {
+        if let Ok(mut visibility) = visibility_query.get_single_mut() {
+            commands.entity(entity).despawn_recursive();
+            *visibility = Visibility::Visible;
+            rebind_state.reset();
+        }
+    }
+}
+
+pub fn update_control_widget(
+    bindings: Res<Bindings>,
+    rebind_state: Res<RebindState>,
+    mut row_query: Query<(&ControlRow, &mut BackgroundColor)>,
+    mut text_query: Query<(&ControlRowText, &mut Text, &mut TextColor), Without<ControlStatusText>>,
+    mut status_query: Query<&mut Text, With<ControlStatusText>>,
+) {
+    for (row, mut color) in row_query.iter_mut() {
+        *color = if row.0 == rebind_state.selected {
+            BackgroundColor(Color::srgba_u8(204, 195, 176, 230))
+        } else {
+            BackgroundColor(Color::NONE)
+        };
+    }
+
+    for (row_text, mut text, mut text_color) in text_query.iter_mut() {
+        let ControlRowText(index, column) = *row_text;
+        let Some(control) = Control::ALL.get(index).copied() else {
+            if column == ControlColumn::Label {
+                text.0 = match index {
+                    RESET_ROW => "Reset to Defaults".to_string(),
+                    _ => "Back".to_string(),
+                };
+            }
+            continue;
+        };
+        let capturing = rebind_state.capturing && rebind_state.selected == index;
+        text.0 = match column {
+            ControlColumn::Label => control.label().to_string(),
+            ControlColumn::Keyboard if capturing => "...".to_string(),
+            ControlColumn::Keyboard => bindings.label(control, InputDevice::Keyboard),
+            ControlColumn::Gamepad if capturing => "...".to_string(),
+            ControlColumn::Gamepad if control.keys_only() => "Left Stick".to_string(),
+            ControlColumn::Gamepad => bindings.label(control, InputDevice::Gamepad),
+        };
+        text_color.0 = if bindings.conflicts(control).is_empty() {
+            Color::BLACK
+        } else {
+            Color::srgb_u8(200, 30, 30)
+        };
+    }
+
+    if let Ok(mut text) = status_query.get_single_mut() {
+        let conflicts = Control::ALL
+            .get(rebind_state.selected)
+            .map(|control| bindings.conflicts(*control))
+            .unwrap_or_default();
+        text.0 = if rebind_state.capturing || conflicts.is_empty() {
+            rebind_state.message.clone()
+        } else {
+            let names: Vec<&str> = conflicts.iter().map(|control| control.label()).collect();
+            format!("Conflicts with {}", names.join(", "))
+        };
+    }
+}
+
+pub fn update_device_glyph_text(
+    device: Res<InputDevice>,
+    bindings: Res<Bindings>,
+    mut query: Query<(Ref<DeviceGlyphText>, &mut Text)>,
+) {
+    for (glyph_text, mut text) in query.iter_mut() {
+        if !device.is_changed() && !bindings.is_changed() && !glyph_text.is_added() {
+            continue;
+        }
+        let label = bindings.label(glyph_text.control, *device);
+        text.0 = glyph_text.template.replace("{}", &label);
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{
-    configs::{LAYER1, LAYER2, LAYER5},
-    enemy::Collider,
+    configs::{BOSS_BAR_HEIGHT, BOSS_BAR_WIDTH, LAYER1, LAYER2, LAYER5},
+    enemy::{Boss, BossState, Collider, Enemy},
     gun::HasLifespan,
-    player::{Health, Player},
+    player::{Health, Player, PlayerSlot},
     resources::{UiFont, Wave},
-    ui::components::{FloatingText, PlayerHealthBar, WaveDisplay, WaveDisplayRoot},
+    stats::{StatKind, Stats},
+    status::{StatusEffect, StatusEffects},
+    ui::components::{
+        BossBarFill, BossBarRoot, BossBarText, FloatingText, PlayerHealthBar, StatusBarRoot,
+        StatusIconText, WaveDisplay, WaveDisplayRoot,
+    },
     utils::InGameEntity,
 };
 use bevy::utils::Duration;
@@ -18,7 +23,7 @@ use bevy::{
 };
 
 pub fn setup_health_bar(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
-    if let Ok(player_entity) = player_query.get_single() {
+    for player_entity in player_query.iter() {
         commands.entity(player_entity).with_children(|parent| {
             parent.spawn((
                 Sprite {
@@ -50,10 +55,10 @@ pub fn setup_health_bar(mut commands: Commands, player_query: Query<Entity, With
 
 pub fn update_health_bar(
     player_query: Query<&Health, (With<Player>, Changed<Health>)>,
-    mut health_bar_query: Query<(&mut Transform, &mut Sprite), With<PlayerHealthBar>>,
+    mut health_bar_query: Query<(&mut Transform, &mut Sprite, &Parent), With<PlayerHealthBar>>,
 ) {
-    if let Ok(health) = player_query.get_single() {
-        if let Ok((mut transform, mut sprite)) = health_bar_query.get_single_mut() {
+    for (mut transform, mut sprite, parent) in health_bar_query.iter_mut() {
+        if let Ok(health) = player_query.get(parent.get()) {
             let health_percentage = health.0 as f32 / health.1 as f32;
             sprite.custom_size = Some(Vec2::new(12.0 * health_percentage, 3.0));
             transform.translation.x = -6.0 + (6.0 * health_percentage);
@@ -119,6 +124,209 @@ pub fn update_wave_display(mut wave_query: Query<&mut Text, With<WaveDisplay>>,
     }
 }
 
+pub fn update_boss_bar(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    boss_query: Query<(&Enemy, &Stats, &Name, &BossState), With<Boss>>,
+    root_query: Query<Entity, With<BossBarRoot>>,
+    mut fill_query: Query<&mut Node, With<BossBarFill>>,
+    mut text_query: Query<&mut Text, With<BossBarText>>,
+) {
+    let Some((_, _, name, _)) = boss_query.iter().next() else {
+        for root in root_query.iter() {
+            commands.entity(root).despawn_recursive();
+        }
+        return;
+    };
+
+    let (health, max_health) =
+        boss_query
+            .iter()
+            .fold((0.0, 0.0), |(health, max_health), (enemy, stats, _, _)| {
+                (
+                    health + enemy.health as f32,
+                    max_health + stats.value(StatKind::MaxHealth),
+                )
+            });
+    let fill = (health / max_health.max(1.0)).clamp(0.0, 1.0) * 100.0;
+
+    let count = boss_query.iter().count();
+    let mut label = if count > 1 {
+        format!("{} x{}", name, count)
+    } else {
+        name.to_string()
+    };
+    if let Some(phase_name) = boss_query
+        .iter()
+        .max_by_key(|(_, _, _, boss)| boss.phase)
+        .and_then(|(_, _, _, boss)| boss.phase_name())
+    {
+        label.push_str(&format!(" - {}", phase_name));
+    }
+    if boss_query.iter().any(|(_, _, _, boss)| boss.enraged) {
+        label.push_str(" - ENRAGED");
+    }
+
+    if root_query.is_empty() {
+        spawn_boss_bar(&mut commands, &font.0, label, fill);
+        return;
+    }
+    for mut node in fill_query.iter_mut() {
+        node.width = Val::Percent(fill);
+    }
+    for mut text in text_query.iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
+
+fn spawn_boss_bar(commands: &mut Commands, font: &Handle<Font>, label: String, fill: f32) {
+    commands
+        .spawn((
+            Name::new("Boss Bar"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(115.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BossBarRoot,
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 32.0,
+                    ..default()
+                },
+                TextColor::from(Color::WHITE),
+                BossBarText,
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(BOSS_BAR_WIDTH),
+                        height: Val::Px(BOSS_BAR_HEIGHT),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.15, 0.1, 0.1)),
+                    BorderColor(Color::BLACK),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Node {
+                            width: Val::Percent(fill),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.8, 0.1, 0.1)),
+                        BossBarFill,
+                    ));
+                });
+        });
+}
+
+pub fn setup_status_bar(mut commands: Commands, existing_bars: Query<Entity, With<StatusBarRoot>>) {
+    if !existing_bars.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Status Bar"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(75.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        StatusBarRoot,
+        InGameEntity,
+    ));
+}
+
+pub fn update_status_bar(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    player_query: Query<(Ref<StatusEffects>, &PlayerSlot), With<Player>>,
+    bar_query: Query<(Entity, Option<&Children>), With<StatusBarRoot>>,
+    mut text_query: Query<(&mut Text, &StatusIconText)>,
+) {
+    let effects = player_query
+        .iter()
+        .find_map(|(effects, slot)| (*slot == PlayerSlot::default()).then_some(effects));
+    let (Some(effects), Ok((bar, icons))) = (effects, bar_query.get_single()) else {
+        return;
+    };
+
+    if !effects.is_changed() {
+        for (mut text, icon) in text_query.iter_mut() {
+            if let Some(effect) = effects.get(icon.0) {
+                *text = Text::new(status_icon_text(effect));
+            }
+        }
+        return;
+    }
+
+    for &icon in icons.into_iter().flatten() {
+        commands.entity(icon).despawn_recursive();
+    }
+    commands.entity(bar).with_children(|parent| {
+        for effect in effects.iter() {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(44.0),
+                        margin: UiRect::horizontal(Val::Px(4.0)),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(effect.kind.color().with_alpha(0.8)),
+                ))
+                .with_children(|icon| {
+                    icon.spawn((
+                        Text::new(effect.kind.label()),
+                        TextFont {
+                            font: font.0.clone(),
+                            font_size: 18.0,
+                            ..default()
+                        },
+                        TextColor::from(Color::BLACK),
+                    ));
+                    icon.spawn((
+                        Text::new(status_icon_text(effect)),
+                        TextFont {
+                            font: font.0.clone(),
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor::from(Color::BLACK),
+                        StatusIconText(effect.kind),
+                    ));
+                });
+        }
+    });
+}
+
+fn status_icon_text(effect: &StatusEffect) -> String {
+    if effect.stacks > 1 {
+        format!("{:.0}s x{}", effect.remaining_secs().ceil(), effect.stacks)
+    } else {
+        format!("{:.0}s", effect.remaining_secs().ceil())
+    }
+}
+
 pub fn spawn_floating_text(
     commands: &mut Commands,
     font: &Handle<Font>,
@@ -154,6 +362,6 @@ pub fn spawn_floating_text(
 
 pub fn update_floating_text(time: Res<Time>, mut query: Query<&mut Transform, With<FloatingText>>) {
     for mut transform in query.iter_mut() {
-        transform.translation.y += 6.0 * ops::sin(time.elapsed_secs()).abs()
+        transform.translation.y += 360.0 * ops::sin(time.elapsed_secs()).abs() * time.delta_secs()
     }
 }
@@ -1,9 +1,12 @@
 use crate::{
     armor::ActiveArmor,
     audio::AudioEvent,
+    configs::INVENTORY_SLOTS,
+    coop::MenuOwner,
     gun::ActiveGun,
-    input::Action,
+    input::{MenuAction, NavigationRepeat},
     loot::{Description, LootType},
+    perk::Perks,
     player::{Player, PlayerInventory},
     resources::UiFont,
     ui::components::{DescriptionTextBox, FocusedItem, GridSlot, LootSaleEvent},
@@ -95,11 +98,11 @@ pub fn spawn_single_grid_item(parent: &mut ChildBuilder, x: usize, y: usize, is_
 
 pub fn handle_sell_focused_item(
     mut commands: Commands,
-    action_state: Res<ActionState<Action>>,
+    action_state: Res<ActionState<MenuAction>>,
     focused_item_query: Query<(&GridSlot, Entity, &Parent), With<FocusedItem>>,
     mut loot_sale_event_writer: EventWriter<LootSaleEvent>,
 ) {
-    if action_state.just_pressed(&Action::SellLoot) {
+    if action_state.just_pressed(&MenuAction::SellLoot) {
         if let Ok((focused_slot, focused_entity, parent)) = focused_item_query.get_single() {
             if let Some(item_entity) = focused_slot.item {
                 let loot_type = match focused_slot.y {
@@ -152,13 +155,13 @@ pub fn handle_sell_focused_item(
 }
 
 pub fn navigate_loot_items(
-    action_state: Res<ActionState<Action>>,
+    navigation: Res<NavigationRepeat>,
     mut commands: Commands,
     mut focused_item_query: Query<
         (&GridSlot, Entity, &mut Node, &mut BorderColor, &Children),
         With<FocusedItem>,
     >,
-    mut grid_query: Query<(&GridSlot, Entity), (Without<FocusedItem>, Without<ImageNode>)>,
+    mut grid_query: Query<(&GridSlot, Entity, &Node), (Without<FocusedItem>, Without<ImageNode>)>,
     mut ew: EventWriter<AudioEvent>,
 ) {
     if focused_item_query.iter().next().is_none() {
@@ -166,16 +169,16 @@ pub fn navigate_loot_items(
     }
     let mut focused_item = focused_item_query.single_mut();
     let mut pressed = false;
-    let new_focus = if action_state.just_pressed(&Action::NavigateUp) {
+    let new_focus = if navigation.triggered(MenuAction::NavigateUp) {
         pressed = true;
         Some((0, -1))
-    } else if action_state.just_pressed(&Action::NavigateDown) {
+    } else if navigation.triggered(MenuAction::NavigateDown) {
         pressed = true;
         Some((0, 1))
-    } else if action_state.just_pressed(&Action::NavigationLeft) {
+    } else if navigation.triggered(MenuAction::NavigateLeft) {
         pressed = true;
         Some((-1, 0))
-    } else if action_state.just_pressed(&Action::NavigationRight) {
+    } else if navigation.triggered(MenuAction::NavigateRight) {
         pressed = true;
         Some((1, 0))
     } else {
@@ -185,7 +188,10 @@ pub fn navigate_loot_items(
         return;
     }
     ew.send(AudioEvent::UI);
-    for (grid_slot, entity) in grid_query.iter_mut() {
+    for (grid_slot, entity, node) in grid_query.iter_mut() {
+        if node.display == Display::None {
+            continue;
+        }
         if let Some((dx, dy)) = new_focus {
             if (grid_slot.x as isize - dx) == focused_item.0.x as isize
                 && (grid_slot.y as isize - dy) == focused_item.0.y as isize
@@ -267,17 +273,33 @@ pub fn highlight_focused_item(
 
 pub fn set_up_loot_image(
     mut grid_query: Query<(&mut ImageNode, &mut GridSlot, &Parent)>,
-    inventory_query: Query<&PlayerInventory, With<Player>>,
+    inventory_query: Query<(&PlayerInventory, &Perks), With<Player>>,
+    menu_owner: Res<MenuOwner>,
     sprite_query: Query<&Sprite>,
-    mut grid_slot_query: Query<&mut GridSlot, Without<ImageNode>>,
+    mut grid_slot_query: Query<(&mut GridSlot, &mut Node), Without<ImageNode>>,
     mut commands: Commands,
     text_box_query: Query<Entity, With<DescriptionTextBox>>,
 ) {
     for entity in text_box_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
-    if let Ok(player_inventory) = inventory_query.get_single() {
+    let owner = menu_owner
+        .0
+        .and_then(|owner| inventory_query.get(owner).ok());
+    if let Some((player_inventory, perks)) = owner {
         for (mut image_node, grid_slot, parent) in grid_query.iter_mut() {
+            let capacity = match grid_slot.y {
+                0 | 1 => perks.potion_slots,
+                _ => INVENTORY_SLOTS,
+            };
+            if let Ok((_, mut node)) = grid_slot_query.get_mut(**parent) {
+                node.display = if grid_slot.x < capacity {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+
             let item_entity = match grid_slot.y {
                 0 => player_inventory.health_potions.get(grid_slot.x),
                 1 => player_inventory.speed_potions.get(grid_slot.x),
@@ -290,7 +312,7 @@ pub fn set_up_loot_image(
                 if let Ok(sprite) = sprite_query.get(*item_entity) {
                     image_node.image = sprite.image.clone();
                     image_node.texture_atlas = sprite.texture_atlas.clone();
-                    if let Ok(mut grid_slot) = grid_slot_query.get_mut(**parent) {
+                    if let Ok((mut grid_slot, _)) = grid_slot_query.get_mut(**parent) {
                         grid_slot.item = Some(*item_entity);
                     }
                 } else {
@@ -302,7 +324,7 @@ pub fn set_up_loot_image(
             } else {
                 image_node.image = Default::default();
                 image_node.texture_atlas = None;
-                if let Ok(mut grid_slot) = grid_slot_query.get_mut(**parent) {
+                if let Ok((mut grid_slot, _)) = grid_slot_query.get_mut(**parent) {
                     grid_slot.item = None;
                 }
             }
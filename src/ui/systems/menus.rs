@@ -2,17 +2,29 @@ use bevy::utils::Duration;
 
 use crate::{
     audio::AudioEvent,
-    configs::{SPRITE_SCALE_FACTOR, UI_BG_COLOR},
+    bindings::{Bindings, Control, InputBinding},
+    class::{ClassRegistry, SelectedClass},
+    configs::{INVENTORY_SLOTS, SPRITE_SCALE_FACTOR, UI_BG_COLOR},
+    coop::{player_just_pressed, CoopSession, MenuOwner},
     game_state::GameState,
-    input::Action,
+    input::{CombatAction, MenuAction, NavigationRepeat},
     loot::{medium_enemies_loots, spawn_armor_entity, spawn_gun_entity, LootStatRange, Value},
-    player::{Gold, PlayerInventory, PlayerLevelingUpEvent},
+    perk::{PendingLevelUps, PerkChoices, Perks, SelectedPerk},
+    player::{Gold, Player, PlayerInventory, PlayerLevelingUpEvent, PlayerSlot},
     potion::{Potion, PotionStats, PotionType},
+    replay::{has_replay, load_replay, ReplayPlayback},
     resources::{GameMode, GlobalTextureAtlas, Level, UiFont},
-    ui::components::{
-        BlinkingText, ControlWidget, EndScreenRoot, FloatingTextBox, MainMenuButton,
-        MainMenuButtonIndex, MainMenuRoot, PauseMenuButton, PauseMenuButtonIndex, PauseMenuRoot,
-        ShopMenuButton, ShopMenuButtonIndex, ShopMenuRoot,
+    rng::GameRng,
+    save::{load_save, LoadedRun},
+    ui::{
+        components::{
+            BlinkingText, ClassButtonIndex, ClassSelectMenuRoot, ControlWidget, CoopJoinText,
+            DeviceGlyphText, EndScreenRoot, FloatingTextBox, LevelUpMenuRoot, MainMenuButton,
+            MainMenuButtonIndex, MainMenuRoot, PauseMenuButton, PauseMenuButtonIndex,
+            PauseMenuRoot, PerkButtonIndex, RebindState, ShopMenuButton, ShopMenuButtonIndex,
+            ShopMenuRoot,
+        },
+        systems::controls::setup_control_widget,
     },
     utils::{cleanup_entities, InGameEntity},
 };
@@ -24,6 +36,7 @@ use bevy::{
     prelude::*,
 };
 use leafwing_input_manager::action_state::ActionState;
+use rand::Rng;
 
 pub fn setup_main_menu(mut commands: Commands, font: Res<UiFont>) {
     commands
@@ -72,7 +85,7 @@ pub fn setup_main_menu(mut commands: Commands, font: Res<UiFont>) {
                 .spawn((
                     Node {
                         width: Val::Px(600.0),
-                        height: Val::Px(400.0),
+                        height: Val::Px(480.0),
                         flex_direction: FlexDirection::Column,
                         justify_content: JustifyContent::SpaceEvenly,
                         align_items: AlignItems::Center,
@@ -81,22 +94,55 @@ pub fn setup_main_menu(mut commands: Commands, font: Res<UiFont>) {
                     BorderRadius::all(Val::Px(6.0)),
                 ))
                 .with_children(|parent| {
+                    let mut index = 0;
+                    if load_save().is_some() {
+                        spawn_main_menu_button(
+                            parent,
+                            "Continue",
+                            MainMenuButton::Continue,
+                            &font.0,
+                            index,
+                        );
+                        index += 1;
+                    }
+                    if has_replay() {
+                        spawn_main_menu_button(
+                            parent,
+                            "Replay",
+                            MainMenuButton::Replay,
+                            &font.0,
+                            index,
+                        );
+                        index += 1;
+                    }
                     spawn_main_menu_button(
                         parent,
                         "Normal Mode",
                         MainMenuButton::StartNormal,
                         &font.0,
-                        0,
+                        index,
                     );
                     spawn_main_menu_button(
                         parent,
                         "Forever Mode",
                         MainMenuButton::StartForever,
                         &font.0,
-                        1,
+                        index + 1,
+                    );
+                    spawn_main_menu_button(
+                        parent,
+                        "Control",
+                        MainMenuButton::Control,
+                        &font.0,
+                        index + 2,
+                    );
+                    spawn_main_menu_button(
+                        parent,
+                        "Exit",
+                        MainMenuButton::Exit,
+                        &font.0,
+                        index + 3,
                     );
-                    spawn_main_menu_button(parent, "Control", MainMenuButton::Control, &font.0, 2);
-                    spawn_main_menu_button(parent, "Exit", MainMenuButton::Exit, &font.0, 3);
                 });
         });
 }
@@ -138,7 +184,8 @@ fn spawn_main_menu_button(
 pub fn handle_main_menu_buttons(
     mut next_state: ResMut<NextState<GameState>>,
     mut game_mode: ResMut<GameMode>,
-    action_state: Res<ActionState<Action>>,
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
     mut selected_button: Local<u8>,
     mut query: Query<(&MainMenuButton, &mut BackgroundColor, &MainMenuButtonIndex)>,
     mut commands: Commands,
@@ -146,25 +193,27 @@ pub fn handle_main_menu_buttons(
     control_query: Query<&ControlWidget>,
     font: Res<UiFont>,
     mut visibility_query: Query<&mut Visibility, With<MainMenuRoot>>,
+    mut rebind_state: ResMut<RebindState>,
+    coop: Res<CoopSession>,
     mut ew: EventWriter<AudioEvent>,
 ) {
     if !control_query.is_empty() || visibility_query.is_empty() {
         return;
     }
-    let button_count = 4;
+    let button_count = query.iter().count() as u8;
     let mut execute = false;
 
-    if action_state.just_pressed(&Action::NavigateUp) {
+    if navigation.triggered(MenuAction::NavigateUp) {
         *selected_button = (*selected_button + button_count - 1) % button_count;
         ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::NavigateDown) {
+    if navigation.triggered(MenuAction::NavigateDown) {
         *selected_button = (*selected_button + 1) % button_count;
         ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::Confirm) {
+    if action_state.just_pressed(&MenuAction::Confirm) {
         execute = true;
     }
 
@@ -173,9 +222,24 @@ pub fn handle_main_menu_buttons(
             *color = BackgroundColor(Color::srgba_u8(204, 195, 176, 230));
             if execute {
                 match button {
+                    MainMenuButton::Continue => {
+                        if let Some(save) = load_save().filter(|_| !coop.is_active()) {
+                            *game_mode = save.game_mode;
+                            commands.insert_resource(LoadedRun(save));
+                            next_state.set(GameState::Initializing);
+                        }
+                    }
+                    MainMenuButton::Replay => {
+                        if let Some(replay) = load_replay() {
+                            *game_mode = replay.game_mode;
+                            commands.insert_resource(ReplayPlayback::new(replay));
+                            next_state.set(GameState::Initializing);
+                        }
+                    }
                     MainMenuButton::Control => {
                         ew.send(AudioEvent::PopUp);
                         setup_control_widget(&mut commands, font.0.clone());
+                        rebind_state.reset();
                         let mut visibility = visibility_query.get_single_mut().unwrap();
                         *visibility = Visibility::Hidden;
                     }
@@ -184,11 +248,11 @@ pub fn handle_main_menu_buttons(
                     }
                     MainMenuButton::StartNormal => {
                         *game_mode = GameMode::Normal;
-                        next_state.set(GameState::Initializing);
+                        next_state.set(GameState::ClassSelect);
                     }
                     MainMenuButton::StartForever => {
                         *game_mode = GameMode::Forever;
-                        next_state.set(GameState::Initializing);
+                        next_state.set(GameState::ClassSelect);
                     }
                 }
             }
@@ -198,123 +262,6 @@ pub fn handle_main_menu_buttons(
     }
 }
 
-pub fn setup_control_widget(commands: &mut Commands, font: Handle<Font>) {
-    commands
-        .spawn((
-            Node {
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                flex_direction: FlexDirection::Column,
-                ..default()
-            },
-            ControlWidget,
-            BackgroundColor(Color::srgb_u8(UI_BG_COLOR.0, UI_BG_COLOR.1, UI_BG_COLOR.2)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn((
-                    Node {
-                        width: Val::Px(600.0),
-                        height: Val::Px(620.0),
-                        flex_direction: FlexDirection::Column,
-                        justify_content: JustifyContent::SpaceEvenly,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    BorderRadius::all(Val::Px(10.0)),
-                    BackgroundColor(Color::srgba_u8(217, 234, 146, 255)),
-                ))
-                .with_children(|parent| {
-                    parent
-                        .spawn((
-                            Node {
-                                width: Val::Auto,
-                                height: Val::Auto,
-                                border: UiRect::bottom(Val::Px(4.0)),
-                                ..default()
-                            },
-                            BorderColor(Color::BLACK),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                Text::new("Control Bindings"),
-                                TextFont {
-                                    font: font.clone(),
-                                    font_size: 40.0,
-                                    ..default()
-                                },
-                                TextColor(Color::BLACK),
-                            ));
-                        });
-                    spawn_control_binding_text(parent, "Move: W/A/S/D", &font);
-                    spawn_control_binding_text(parent, "Switch Gun: Q", &font);
-                    spawn_control_binding_text(parent, "Pause Menu: P/ESC", &font);
-                    spawn_control_binding_text(parent, "Confirm: Enter", &font);
-                    spawn_control_binding_text(parent, "Navigate: Arrow Keys/WASD", &font);
-                    spawn_control_binding_text(parent, "Use Health Potion: 1", &font);
-                    spawn_control_binding_text(parent, "Use Speed Potion: 2", &font);
-                    spawn_control_binding_text(parent, "Toggle Shop: O", &font);
-                    spawn_control_binding_text(parent, "Toggle Loot Board: Tab", &font);
-                    spawn_control_binding_text(parent, "Sell Loot: Del", &font);
-                    parent
-                        .spawn((
-                            Node {
-                                width: Val::Px(100.0),
-                                height: Val::Px(50.0),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                ..default()
-                            },
-                            BorderRadius::all(Val::Px(6.0)),
-                            BackgroundColor(Color::srgba_u8(255, 246, 225, 230)),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                Text::new("Confirm"),
-                                TextFont {
-                                    font: font.clone(),
-                                    font_size: 30.0,
-                                    ..default()
-                                },
-                                TextColor(Color::BLACK),
-                            ));
-                        });
-                });
-        });
-}
-
-fn spawn_control_binding_text(parent: &mut ChildBuilder, text: &str, font: &Handle<Font>) {
-    parent.spawn((
-        Text::new(text),
-        TextFont {
-            font: font.clone(),
-            font_size: 30.0,
-            ..default()
-        },
-        TextColor(Color::BLACK),
-    ));
-}
-
-pub fn handle_control_widget(
-    mut commands: Commands,
-    action_state: Res<ActionState<Action>>,
-    query: Query<Entity, With<ControlWidget>>,
-    mut visibility_query: Query<&mut Visibility, With<MainMenuRoot>>,
-    mut ew: EventWriter<AudioEvent>,
-) {
-    if action_state.just_pressed(&Action::Confirm) {
-        if let Ok(entity) = query.get_single() {
-            if let Ok(mut visibility) = visibility_query.get_single_mut() {
-                ew.send(AudioEvent::PopUp);
-                commands.entity(entity).despawn_recursive();
-                *visibility = Visibility::Visible;
-            }
-        }
-    }
-}
-
 pub fn blink_text(mut text_query: Query<&mut TextColor, With<BlinkingText>>, time: Res<Time>) {
     if let Ok(mut text_color) = text_query.get_single_mut() {
         let flash_rate = 2.0;
@@ -333,26 +280,27 @@ pub fn despawn_main_menu(
 }
 
 pub fn handle_pause_input(
-    action_state: Res<ActionState<Action>>,
+    combat_state: Res<ActionState<CombatAction>>,
+    menu_state: Res<ActionState<MenuAction>>,
+    player_query: Query<(Entity, Option<&ActionState<CombatAction>>), With<Player>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut query: Query<&mut Visibility, With<PauseMenuRoot>>,
-    current_state: Res<State<GameState>>,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if action_state.just_pressed(&Action::TogglePause) {
-        let mut visibility = query.single_mut();
-        if current_state.get() == &GameState::Combat {
-            ew.send(AudioEvent::PopUp);
-            next_state.set(GameState::Paused);
-            *visibility = Visibility::Visible;
-            return;
-        }
-        if current_state.get() == &GameState::Paused {
-            ew.send(AudioEvent::PopUp);
-            next_state.set(GameState::Combat);
-            *visibility = Visibility::Hidden;
-            return;
-        }
+    if player_just_pressed(
+        CombatAction::TogglePause,
+        &combat_state,
+        player_query.iter(),
+    )
+    .is_some()
+    {
+        ew.send(AudioEvent::PopUp);
+        next_state.set(GameState::Paused);
+        *query.single_mut() = Visibility::Visible;
+    } else if menu_state.just_pressed(&MenuAction::Back) {
+        ew.send(AudioEvent::PopUp);
+        next_state.set(GameState::Combat);
+        *query.single_mut() = Visibility::Hidden;
     }
 }
 
@@ -442,7 +390,8 @@ fn spawn_pause_menu_button(
 }
 pub fn pause_menu_navigation(
     mut next_state: ResMut<NextState<GameState>>,
-    action_state: Res<ActionState<Action>>,
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
     all_entities: Query<Entity, With<InGameEntity>>,
     mut visibility_query: Query<&mut Visibility, With<PauseMenuRoot>>,
     mut selected_button: Local<u8>,
@@ -458,17 +407,17 @@ pub fn pause_menu_navigation(
     let button_count = query.iter().count() as u8;
     let mut execute = false;
 
-    if action_state.just_pressed(&Action::NavigateUp) {
+    if navigation.triggered(MenuAction::NavigateUp) {
         *selected_button = (*selected_button + button_count - 1) % button_count;
         ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::NavigateDown) {
+    if navigation.triggered(MenuAction::NavigateDown) {
         *selected_button = (*selected_button + 1) % button_count;
         ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::Confirm) {
+    if action_state.just_pressed(&MenuAction::Confirm) {
         execute = true;
         ew.send(AudioEvent::UI);
     }
@@ -556,6 +505,10 @@ pub fn set_up_death_screen(
                         },
                         TextColor(Color::WHITE),
                         BlinkingText,
+                        DeviceGlyphText {
+                            template: "Press {} to Restart",
+                            control: Control::Menu(MenuAction::Confirm),
+                        },
                     ));
                 });
         });
@@ -616,19 +569,23 @@ pub fn set_up_win_screen(
                         },
                         TextColor(Color::WHITE),
                         BlinkingText,
+                        DeviceGlyphText {
+                            template: "Press {} to Restart",
+                            control: Control::Menu(MenuAction::Confirm),
+                        },
                     ));
                 });
         });
 }
 
 pub fn handle_end_screen_input(
-    action_state: Res<ActionState<Action>>,
+    action_state: Res<ActionState<MenuAction>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut query: Query<Entity, With<EndScreenRoot>>,
     mut commands: Commands,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if action_state.just_pressed(&Action::Confirm) {
+    if action_state.just_pressed(&MenuAction::Confirm) {
         let entity = query.single_mut();
         commands.entity(entity).despawn_recursive();
         ew.send(AudioEvent::UI);
@@ -637,23 +594,27 @@ pub fn handle_end_screen_input(
 }
 
 pub fn handle_shop_input(
-    action_state: Res<ActionState<Action>>,
+    combat_state: Res<ActionState<CombatAction>>,
+    menu_state: Res<ActionState<MenuAction>>,
+    player_query: Query<(Entity, Option<&ActionState<CombatAction>>), With<Player>>,
+    mut menu_owner: ResMut<MenuOwner>,
     mut next_state: ResMut<NextState<GameState>>,
     mut query: Query<&mut Visibility, With<ShopMenuRoot>>,
-    current_state: Res<State<GameState>>,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if action_state.just_pressed(&Action::ToggleShop) {
-        let mut visibility = query.single_mut();
-        if current_state.get() == &GameState::Combat {
-            next_state.set(GameState::Shopping);
-            *visibility = Visibility::Visible;
-            ew.send(AudioEvent::PopUp);
-        } else {
-            next_state.set(GameState::Combat);
-            *visibility = Visibility::Hidden;
-            ew.send(AudioEvent::PopUp);
-        }
+    if let Some(owner) =
+        player_just_pressed(CombatAction::ToggleShop, &combat_state, player_query.iter())
+    {
+        menu_owner.0 = Some(owner);
+        next_state.set(GameState::Shopping);
+        *query.single_mut() = Visibility::Visible;
+        ew.send(AudioEvent::PopUp);
+    } else if menu_state.just_pressed(&MenuAction::ToggleShop)
+        || menu_state.just_pressed(&MenuAction::Back)
+    {
+        next_state.set(GameState::Combat);
+        *query.single_mut() = Visibility::Hidden;
+        ew.send(AudioEvent::PopUp);
     }
 }
 
@@ -761,30 +722,38 @@ fn spawn_shop_menu_button(
 
 pub fn handle_shop_menu_buttons(
     mut commands: Commands,
-    mut player_query: Query<(&mut PlayerInventory, &mut Gold)>,
-    action_state: Res<ActionState<Action>>,
+    mut player_query: Query<(&mut PlayerInventory, &mut Gold, &Perks)>,
+    menu_owner: Res<MenuOwner>,
+    action_state: Res<ActionState<MenuAction>>,
+    navigation: Res<NavigationRepeat>,
     mut selected_button: Local<u8>,
+    new_shop_query: Query<(), Added<ShopMenuRoot>>,
     mut query: Query<(&ShopMenuButton, &mut BackgroundColor, &ShopMenuButtonIndex)>,
     texture_atlases: Res<GlobalTextureAtlas>,
     font: Res<UiFont>,
     level: ResMut<Level>,
     ew: EventWriter<PlayerLevelingUpEvent>,
     mut audio_ew: EventWriter<AudioEvent>,
+    mut rng: ResMut<GameRng>,
 ) {
     let button_count = 5;
     let mut execute = false;
 
-    if action_state.just_pressed(&Action::NavigateUp) {
+    if !new_shop_query.is_empty() {
+        *selected_button = 0;
+    }
+
+    if navigation.triggered(MenuAction::NavigateUp) {
         *selected_button = (*selected_button + button_count - 1) % button_count;
         audio_ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::NavigateDown) {
+    if navigation.triggered(MenuAction::NavigateDown) {
         *selected_button = (*selected_button + 1) % button_count;
         audio_ew.send(AudioEvent::UI);
     }
 
-    if action_state.just_pressed(&Action::Confirm) {
+    if action_state.just_pressed(&MenuAction::Confirm) {
         execute = true;
         audio_ew.send(AudioEvent::PopUp);
     }
@@ -793,13 +762,17 @@ pub fn handle_shop_menu_buttons(
         if index.0 == *selected_button {
             *color = BackgroundColor(Color::srgba_u8(204, 195, 176, 230));
             if execute {
-                if let Ok((mut inventory, mut gold)) = player_query.get_single_mut() {
+                let owner = menu_owner
+                    .0
+                    .and_then(|owner| player_query.get_mut(owner).ok());
+                if let Some((mut inventory, mut gold, perks)) = owner {
                     match button {
                         ShopMenuButton::BuyHealthPotion => {
                             handle_buy_health_potion(
                                 &mut commands,
                                 &mut inventory,
                                 &mut gold,
+                                perks.potion_slots,
                                 &texture_atlases,
                                 &font,
                             );
@@ -810,6 +783,7 @@ pub fn handle_shop_menu_buttons(
                                 &mut commands,
                                 &mut inventory,
                                 &mut gold,
+                                perks.potion_slots,
                                 &texture_atlases,
                                 &font,
                             );
@@ -822,6 +796,7 @@ pub fn handle_shop_menu_buttons(
                                 &mut gold,
                                 &texture_atlases,
                                 &font,
+                                &mut rng.loot,
                             );
                             break;
                         }
@@ -832,6 +807,7 @@ pub fn handle_shop_menu_buttons(
                                 &mut gold,
                                 &texture_atlases,
                                 &font,
+                                &mut rng.loot,
                             );
                             break;
                         }
@@ -872,11 +848,12 @@ fn handle_buy_health_potion(
     commands: &mut Commands,
     inventory: &mut PlayerInventory,
     gold: &mut Gold,
+    potion_slots: usize,
     texture_atlases: &GlobalTextureAtlas,
     font: &UiFont,
 ) {
     if gold.0 >= 50 {
-        if inventory.health_potions.len() < 4 {
+        if inventory.health_potions.len() < potion_slots {
             gold.0 -= 50;
             let health_potion = commands
                 .spawn((
@@ -914,11 +891,12 @@ fn handle_buy_speed_potion(
     commands: &mut Commands,
     inventory: &mut PlayerInventory,
     gold: &mut Gold,
+    potion_slots: usize,
     texture_atlases: &GlobalTextureAtlas,
     font: &UiFont,
 ) {
     if gold.0 >= 50 {
-        if inventory.speed_potions.len() < 4 {
+        if inventory.speed_potions.len() < potion_slots {
             gold.0 -= 50;
             let speed_potion = commands
                 .spawn((
@@ -958,9 +936,10 @@ pub fn handle_buy_gun(
     gold: &mut Gold,
     texture_atlases: &GlobalTextureAtlas,
     font: &UiFont,
+    rng: &mut impl Rng,
 ) {
     if gold.0 >= 200 {
-        if inventory.guns.len() < 4 {
+        if inventory.guns.len() < INVENTORY_SLOTS {
             gold.0 -= 200;
             let medium_enemies_loot = medium_enemies_loots();
             let gun_stat_range = if let Some(gun_loot) = medium_enemies_loot
@@ -984,6 +963,7 @@ pub fn handle_buy_gun(
                 texture_atlases.layout_16x16.clone().unwrap(),
                 gun_stat_range,
                 200,
+                rng,
             );
             commands.entity(gun).insert(Visibility::Hidden);
             inventory.guns.push(gun);
@@ -1001,9 +981,10 @@ pub fn handle_buy_armor(
     gold: &mut Gold,
     texture_atlases: &GlobalTextureAtlas,
     font: &UiFont,
+    rng: &mut impl Rng,
 ) {
     if gold.0 >= 100 {
-        if inventory.armors.len() < 4 {
+        if inventory.armors.len() < INVENTORY_SLOTS {
             gold.0 -= 100;
             let medium_enemies_loot = medium_enemies_loots();
             let armor_stat_range = if let Some(armor_loot) = medium_enemies_loot
@@ -1027,6 +1008,7 @@ pub fn handle_buy_armor(
                 texture_atlases.layout_16x16.clone().unwrap(),
                 armor_stat_range,
                 100,
+                rng,
             );
             commands.entity(armor).insert(Visibility::Hidden);
             inventory.armors.push(armor);
@@ -1039,6 +1021,320 @@ pub fn handle_buy_armor(
     }
 }
 
+pub fn spawn_level_up_menu(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    choices: Res<PerkChoices>,
+    pending: Res<PendingLevelUps>,
+    menu_query: Query<Entity, With<LevelUpMenuRoot>>,
+    slot_query: Query<&PlayerSlot, With<Player>>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if choices.perks.is_empty() {
+        return;
+    }
+
+    let mut title = match pending.0.first() {
+        Some(level) => format!("Level {}! Choose a Perk", level),
+        None => "Choose a Perk".to_owned(),
+    };
+    if slot_query.iter().len() > 1 {
+        if let Some(slot) = choices
+            .player
+            .and_then(|player| slot_query.get(player).ok())
+        {
+            title = format!("{}: {}", slot.label(), title);
+        }
+    }
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            LevelUpMenuRoot,
+            InGameEntity,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(700.0),
+                        height: Val::Px(520.0),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceEvenly,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Name::new("LevelUpMenu"),
+                    BorderRadius::all(Val::Px(10.0)),
+                    BackgroundColor(Color::srgba_u8(237, 217, 165, 230)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(title),
+                        TextFont {
+                            font: font.0.clone(),
+                            font_size: 50.0,
+                            ..default()
+                        },
+                        TextColor(Color::BLACK),
+                    ));
+                    for (index, definition) in choices.perks.iter().enumerate() {
+                        parent
+                            .spawn((
+                                Node {
+                                    width: Val::Px(600.0),
+                                    height: Val::Px(110.0),
+                                    flex_direction: FlexDirection::Column,
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    border: UiRect::all(Val::Px(3.0)),
+                                    ..default()
+                                },
+                                BorderRadius::all(Val::Px(6.0)),
+                                BorderColor(definition.rarity.color()),
+                                BackgroundColor(Color::srgba_u8(255, 246, 225, 230)),
+                                PerkButtonIndex(index as u8),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(format!(
+                                        "{} ({})",
+                                        definition.name,
+                                        definition.rarity.label()
+                                    )),
+                                    TextFont {
+                                        font: font.0.clone(),
+                                        font_size: 36.0,
+                                        ..default()
+                                    },
+                                    TextColor(definition.rarity.color()),
+                                ));
+                                parent.spawn((
+                                    Text::new(definition.description.clone()),
+                                    TextFont {
+                                        font: font.0.clone(),
+                                        font_size: 26.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::BLACK),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+pub fn highlight_selected_perk(
+    selected: Res<SelectedPerk>,
+    mut query: Query<(&mut BackgroundColor, &PerkButtonIndex)>,
+) {
+    for (mut color, index) in query.iter_mut() {
+        if index.0 as usize == selected.0 {
+            *color = BackgroundColor(Color::srgba_u8(204, 195, 176, 230));
+        } else {
+            *color = BackgroundColor(Color::srgba_u8(255, 246, 225, 230));
+        }
+    }
+}
+
+pub fn despawn_level_up_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<LevelUpMenuRoot>>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn spawn_class_select_menu(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    registry: Res<ClassRegistry>,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ClassSelectMenuRoot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(760.0),
+                        height: Val::Px(640.0),
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::SpaceEvenly,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Name::new("ClassSelectMenu"),
+                    BorderRadius::all(Val::Px(10.0)),
+                    BackgroundColor(Color::srgba_u8(237, 217, 165, 230)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Choose Your Class"),
+                        TextFont {
+                            font: font.0.clone(),
+                            font_size: 50.0,
+                            ..default()
+                        },
+                        TextColor(Color::BLACK),
+                    ));
+                    for (index, definition) in registry.classes().iter().enumerate() {
+                        parent
+                            .spawn((
+                                Node {
+                                    width: Val::Px(660.0),
+                                    height: Val::Px(150.0),
+                                    flex_direction: FlexDirection::Column,
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                BorderRadius::all(Val::Px(6.0)),
+                                BackgroundColor(Color::srgba_u8(255, 246, 225, 230)),
+                                ClassButtonIndex(index as u8),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    Text::new(definition.name.clone()),
+                                    TextFont {
+                                        font: font.0.clone(),
+                                        font_size: 36.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::BLACK),
+                                ));
+                                parent.spawn((
+                                    Text::new(format!(
+                                        "{}\nHP: {}  Speed: {}  Defense: {}",
+                                        definition.description,
+                                        definition.player.max_health,
+                                        definition.player.speed,
+                                        definition.player.defense,
+                                    )),
+                                    TextFont {
+                                        font: font.0.clone(),
+                                        font_size: 24.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::BLACK),
+                                    TextLayout {
+                                        justify: JustifyText::Center,
+                                        ..default()
+                                    },
+                                ));
+                                parent.spawn((
+                                    Text::new(format!(
+                                        "{}: {}",
+                                        definition.passive.name(),
+                                        definition.passive.description()
+                                    )),
+                                    TextFont {
+                                        font: font.0.clone(),
+                                        font_size: 24.0,
+                                        ..default()
+                                    },
+                                    TextColor(Color::srgb_u8(40, 90, 200)),
+                                ));
+                            });
+                    }
+                    parent.spawn((
+                        Text::new(""),
+                        TextFont {
+                            font: font.0.clone(),
+                            font_size: 24.0,
+                            ..default()
+                        },
+                        TextColor(Color::BLACK),
+                        CoopJoinText,
+                    ));
+                });
+        });
+}
+
+pub fn update_continue_button(
+    coop: Res<CoopSession>,
+    button_query: Query<(&MainMenuButton, &Children)>,
+    mut text_query: Query<&mut TextColor>,
+) {
+    let color = if coop.is_active() {
+        Color::srgb(0.55, 0.55, 0.55)
+    } else {
+        Color::BLACK
+    };
+    for (button, children) in button_query.iter() {
+        if !matches!(button, MainMenuButton::Continue) {
+            continue;
+        }
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text_color) = texts.fetch_next() {
+            if text_color.0 != color {
+                text_color.0 = color;
+            }
+        }
+    }
+}
+
+pub fn update_coop_join_text(
+    session: Res<CoopSession>,
+    bindings: Res<Bindings>,
+    mut query: Query<&mut Text, With<CoopJoinText>>,
+) {
+    let message = match bindings.coop_join_button() {
+        Some(button) if session.is_active() => format!(
+            "Player 2 joined (press {} again to leave). Co-op runs are not saved.",
+            InputBinding::Gamepad(button).label()
+        ),
+        Some(button) => format!(
+            "Press {} on a second gamepad to join",
+            InputBinding::Gamepad(button).label()
+        ),
+        None => "No free gamepad button to join co-op".to_string(),
+    };
+    for mut text in query.iter_mut() {
+        if text.0 != message {
+            text.0 = message.clone();
+        }
+    }
+}
+
+pub fn highlight_selected_class(
+    selected: Res<SelectedClass>,
+    mut query: Query<(&mut BackgroundColor, &ClassButtonIndex)>,
+) {
+    for (mut color, index) in query.iter_mut() {
+        if index.0 as usize == selected.0 {
+            *color = BackgroundColor(Color::srgba_u8(204, 195, 176, 230));
+        } else {
+            *color = BackgroundColor(Color::srgba_u8(255, 246, 225, 230));
+        }
+    }
+}
+
+pub fn despawn_class_select_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<ClassSelectMenuRoot>>,
+) {
+    for entity in menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn spawn_floating_text_box(commands: &mut Commands, font: &Handle<Font>, message: String) {
     commands
         .spawn((
@@ -1,3 +1,4 @@
+pub mod controls;
 pub mod in_game_ui;
 pub mod loot_grid;
 pub mod menus;
@@ -1,14 +1,15 @@
 use crate::{
     audio::AudioEvent,
-    configs::MAX_DEFENSE,
+    configs::{INVENTORY_SLOTS, MAX_DEFENSE, MAX_POTION_SLOTS},
+    coop::{player_just_pressed, MenuOwner},
     game_state::GameState,
-    input::Action,
-    player::{DamageBoost, Defense, Gold, Health, Player},
+    input::{CombatAction, MenuAction},
+    player::{DamageBoost, Dash, Defense, Downed, Gold, Health, Player, PlayerSlot},
     resources::{GlobalTextureAtlas, Level, UiFont},
     ui::{
         components::{
-            PauseMenuRoot, PlayerDamageBoostText, PlayerDefenseText, PlayerGoldText,
-            PlayerHealthText, PlayerLevelText, PlayerXpText, UiRoot,
+            DashCooldownBar, PauseMenuRoot, PlayerDamageBoostText, PlayerDefenseText,
+            PlayerGoldText, PlayerHealthText, PlayerLevelText, PlayerXpText, UiRoot,
         },
         systems::loot_grid,
     },
@@ -21,9 +22,10 @@ use bevy::{
     hierarchy::{BuildChildren, ChildBuild, ChildBuilder},
     image::Image,
     prelude::{
-        default, AlignItems, BackgroundColor, BorderColor, Commands, Component, EventWriter,
-        FlexDirection, Font, GlobalZIndex, ImageNode, NextState, Node, ParamSet, Query, Res,
-        ResMut, Text, TextColor, TextFont, TextureAtlas, UiRect, Val, Visibility, With,
+        default, AlignItems, BackgroundColor, BorderColor, Commands, Component, Entity,
+        EventWriter, FlexDirection, Font, GlobalZIndex, Has, ImageNode, NextState, Node, ParamSet,
+        PositionType, Query, Res, ResMut, Text, TextColor, TextFont, TextureAtlas, UiRect, Val,
+        Visibility, With,
     },
 };
 use leafwing_input_manager::action_state::ActionState;
@@ -54,10 +56,10 @@ pub fn setup_ui(mut commands: Commands, font: Res<UiFont>, handle: Res<GlobalTex
                     ..default()
                 })
                 .with_children(|parent| {
-                    loot_grid::spawn_slots_grid(parent, &font.0, "Health Potions", 4, 0);
-                    loot_grid::spawn_slots_grid(parent, &font.0, "Speed Potions", 4, 1);
-                    loot_grid::spawn_slots_grid(parent, &font.0, "Guns", 4, 2);
-                    loot_grid::spawn_slots_grid(parent, &font.0, "Armors", 4, 3);
+                    loot_grid::spawn_slots_grid(parent, &font.0, "Health Potions", MAX_POTION_SLOTS, 0);
+                    loot_grid::spawn_slots_grid(parent, &font.0, "Speed Potions", MAX_POTION_SLOTS, 1);
+                    loot_grid::spawn_slots_grid(parent, &font.0, "Guns", INVENTORY_SLOTS, 2);
+                    loot_grid::spawn_slots_grid(parent, &font.0, "Armors", INVENTORY_SLOTS, 3);
                 });
 
             parent
@@ -151,8 +153,94 @@ pub fn setup_ui(mut commands: Commands, font: Res<UiFont>, handle: Res<GlobalTex
         .insert(UiRoot);
 }
 
+pub fn setup_dash_indicators(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    player_query: Query<(Entity, &PlayerSlot), With<Player>>,
+) {
+    let coop = player_query.iter().count() > 1;
+    for (player, slot) in player_query.iter() {
+        let (left, right, align_items) = if slot.0 == 0 {
+            (Val::Px(20.0), Val::Auto, AlignItems::FlexStart)
+        } else {
+            (Val::Auto, Val::Px(20.0), AlignItems::FlexEnd)
+        };
+        let label = if coop {
+            format!("{} Dash", slot.label())
+        } else {
+            "Dash".to_string()
+        };
+        commands
+            .spawn((
+                Name::new("Dash Indicator"),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left,
+                    right,
+                    bottom: Val::Px(20.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items,
+                    ..default()
+                },
+                InGameEntity,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font: font.0.clone(),
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+                parent
+                    .spawn((
+                        Node {
+                            width: Val::Px(120.0),
+                            height: Val::Px(10.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::linear_rgb(0.2, 0.2, 0.2)),
+                        BorderColor(Color::BLACK),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Node {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::linear_rgb(0.3, 0.8, 1.0)),
+                            DashCooldownBar(player),
+                        ));
+                    });
+            });
+    }
+}
+
+pub fn update_dash_indicator(
+    player_query: Query<(&Dash, Has<Downed>), With<Player>>,
+    mut bar_query: Query<(&mut Node, &mut BackgroundColor, &DashCooldownBar)>,
+) {
+    for (mut node, mut background, bar) in bar_query.iter_mut() {
+        let Ok((dash, downed)) = player_query.get(bar.0) else {
+            continue;
+        };
+
+        node.width = Val::Percent(dash.cooldown.fraction() * 100.0);
+        background.0 = if dash.cooldown.finished() && !downed {
+            Color::linear_rgb(0.3, 0.8, 1.0)
+        } else {
+            Color::linear_rgb(0.5, 0.5, 0.5)
+        };
+    }
+}
+
 pub fn update_ui(
     level: Res<Level>,
+    menu_owner: Res<MenuOwner>,
     player_query: Query<(&Health, &Defense, &Gold, &DamageBoost), With<Player>>,
     mut param_set: ParamSet<(
         Query<&mut Text, With<PlayerHealthText>>,
@@ -164,7 +252,8 @@ pub fn update_ui(
     )>,
     mut pause_menu_query: Query<&mut Visibility, With<PauseMenuRoot>>,
 ) {
-    if let Ok((health, defense, gold, damage_boost)) = player_query.get_single() {
+    let owner = menu_owner.0.and_then(|owner| player_query.get(owner).ok());
+    if let Some((health, defense, gold, damage_boost)) = owner {
         if let Ok(mut health_text) = param_set.p0().get_single_mut() {
             *health_text = format!("Health: {}", health.0).into();
         }
@@ -194,23 +283,33 @@ pub fn update_ui(
 }
 
 pub fn toggle_loot_ui_visibility(
-    action_state: Res<ActionState<Action>>,
+    combat_state: Res<ActionState<CombatAction>>,
+    menu_state: Res<ActionState<MenuAction>>,
+    player_query: Query<(Entity, Option<&ActionState<CombatAction>>), With<Player>>,
+    mut menu_owner: ResMut<MenuOwner>,
     mut ui_query: Query<&mut Visibility, With<UiRoot>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut ew: EventWriter<AudioEvent>,
 ) {
-    if action_state.just_pressed(&Action::ToggleLootBoard) {
+    if let Some(owner) = player_just_pressed(
+        CombatAction::ToggleLootBoard,
+        &combat_state,
+        player_query.iter(),
+    ) {
+        menu_owner.0 = Some(owner);
+        for mut visibility in ui_query.iter_mut() {
+            *visibility = Visibility::Visible;
+        }
+        next_state.set(GameState::Ui);
+        ew.send(AudioEvent::PopUp);
+    } else if menu_state.just_pressed(&MenuAction::ToggleLootBoard)
+        || menu_state.just_pressed(&MenuAction::Back)
+    {
         for mut visibility in ui_query.iter_mut() {
-            if *visibility == Visibility::Hidden {
-                *visibility = Visibility::Visible;
-                next_state.set(GameState::Ui);
-                ew.send(AudioEvent::PopUp);
-            } else {
-                *visibility = Visibility::Hidden;
-                next_state.set(GameState::Combat);
-                ew.send(AudioEvent::PopUp);
-            }
+            *visibility = Visibility::Hidden;
         }
+        next_state.set(GameState::Combat);
+        ew.send(AudioEvent::PopUp);
     }
 }
 
@@ -1,5 +1,11 @@
-use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
 use rand::{seq::SliceRandom, Rng};
+use serde::de::DeserializeOwned;
 
 use crate::{
     collision::EnemyKdTree,
@@ -13,6 +19,35 @@ pub struct InGameEntity;
 #[derive(Component)]
 pub struct Pickable;
 
+pub struct RonAssetLoader<A>(PhantomData<A>);
+
+impl<A> Default for RonAssetLoader<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
 pub fn calculate_enemies_per_wave(_wave_number: u32) -> u32 {
     let base_enemies = 1;
     //let increase = (wave_number as f32 * 0.5).floor() as u32 * 3;
@@ -59,8 +94,30 @@ pub fn calculate_damage_boost_increase(level: u32) -> u32 {
     damage_boost_increase.round() as u32
 }
 
-pub fn get_random_position_around(pos: Vec2, dist_range: std::ops::Range<f32>) -> (f32, f32) {
-    let mut rng = rand::thread_rng();
+pub fn pick_weighted<T: Copy>(entries: &[(T, f32)], rng: &mut impl Rng) -> Option<T> {
+    if let [(entry, _)] = entries {
+        return Some(*entry);
+    }
+
+    let total_weight: f32 = entries.iter().map(|(_, weight)| weight).sum();
+    let random_value = rng.gen::<f32>() * total_weight;
+
+    let mut cumulative_weight = 0.0;
+    for (entry, weight) in entries {
+        cumulative_weight += weight;
+        if random_value <= cumulative_weight {
+            return Some(*entry);
+        }
+    }
+
+    entries.last().map(|(entry, _)| *entry)
+}
+
+pub fn get_random_position_around(
+    pos: Vec2,
+    dist_range: std::ops::Range<f32>,
+    rng: &mut impl Rng,
+) -> (f32, f32) {
     let angle = rng.gen_range(0.0..std::f32::consts::TAU);
     let dist = rng.gen_range(dist_range);
 
@@ -88,10 +145,10 @@ pub fn cleanup_entities(mut commands: Commands, all_entities: Query<Entity, With
     }
 }
 
-pub fn apply_movement(position: &mut Vec3, mut movement: Vec2, layer: f32) {
+pub fn apply_movement(position: &mut Vec3, mut velocity: Vec2, delta_secs: f32, layer: f32) {
     const MARGIN: f32 = 50.0;
     const REPEL_MARGIN: f32 = 120.0;
-    const REPEL_STRENGTH: f32 = 2.0;
+    const REPEL_STRENGTH: f32 = 120.0;
 
     let mut repulsion = Vec2::ZERO;
 
@@ -112,7 +169,8 @@ pub fn apply_movement(position: &mut Vec3, mut movement: Vec2, layer: f32) {
         repulsion.y += force * REPEL_STRENGTH;
     }
 
-    movement += repulsion;
+    velocity += repulsion;
+    let movement = velocity * delta_secs;
 
     position.x = (position.x + movement.x).clamp(-WW + MARGIN, WW - MARGIN);
     position.y = (position.y + movement.y).clamp(-WH + MARGIN, WH - MARGIN);
@@ -143,7 +201,7 @@ pub fn calculate_defense_percentage(defense: u32) -> f32 {
     scaled_defense * max_percentage
 }
 
-pub fn generate_random_cool_name(loot_type: LootType) -> String {
+pub fn generate_random_cool_name(loot_type: LootType, rng: &mut impl Rng) -> String {
     let adjectives = [
         "Epic",
         "Legendary",
@@ -188,12 +246,11 @@ pub fn generate_random_cool_name(loot_type: LootType) -> String {
         "Mixture",
     ];
 
-    let mut rng = rand::thread_rng();
-    let adjective = adjectives.choose(&mut rng).unwrap();
+    let adjective = adjectives.choose(rng).unwrap();
     let noun = match loot_type {
-        LootType::Gun => gun_nouns.choose(&mut rng).unwrap(),
-        LootType::Armor => armor_nouns.choose(&mut rng).unwrap(),
-        LootType::Potion => potion_nouns.choose(&mut rng).unwrap(),
+        LootType::Gun => gun_nouns.choose(rng).unwrap(),
+        LootType::Armor => armor_nouns.choose(rng).unwrap(),
+        LootType::Potion => potion_nouns.choose(rng).unwrap(),
     };
 
     format!("{} {}", adjective, noun)
@@ -1,21 +1,34 @@
 use rand::Rng;
 
 use crate::{
+    aim::AimTarget,
     animation::AnimationTimer,
-    armor::{ActiveArmor, Armor, ArmorStats},
+    armor::{ActiveArmor, Armor},
+    bindings::Bindings,
+    class::{ClassDefinition, ClassRegistry, ClassSprite, PassiveTimer, PlayerClass},
     configs::*,
+    coop::CoopSession,
     game_state::GameState,
     gun::{ActiveGun, Gun},
+    input::{CombatAction, InputBuffer},
+    interpolation::Interpolated,
     loot::{Description, Value},
+    net::{has_authority, RemotePlayer},
+    perk::Perks,
     player::{
-        DamageBoost, Defense, Gold, Health, OriginalColor, Player, PlayerInventory, PlayerState,
-        Speed,
+        DamageBoost, Dash, Defense, Gold, Health, OriginalColor, Player, PlayerInventory,
+        PlayerSlot, PlayerState, Speed,
     },
-    potion::{Potion, PotionStats, PotionType},
-    resources::{GlobalTextureAtlas, Level, Wave},
+    potion::{Potion, PotionType},
+    resources::{GameMode, GlobalTextureAtlas, Level, Wave},
+    rng::GameRng,
+    save::{LoadedRun, SaveData, SavedArmor, SavedGun, SavedPlayer, SavedPotion},
+    stats::{StatKind, Stats},
+    status::StatusEffects,
     utils::InGameEntity,
 };
 use bevy::{math::vec3, prelude::*};
+use leafwing_input_manager::prelude::ActionState;
 
 pub struct WorldPlugin;
 
@@ -27,7 +40,7 @@ impl Plugin for WorldPlugin {
                 spawn_background,
                 spawn_world_decorations,
                 spawn_world_edges,
-                init_world,
+                init_world.run_if(has_authority),
             )
                 .chain(),
         );
@@ -37,184 +50,292 @@ impl Plugin for WorldPlugin {
 pub fn init_world(
     mut commands: Commands,
     handle: Res<GlobalTextureAtlas>,
+    game_mode: Res<GameMode>,
+    class_registry: Res<ClassRegistry>,
+    mut player_class: ResMut<PlayerClass>,
+    loaded_run: Option<Res<LoadedRun>>,
+    coop: Res<CoopSession>,
+    bindings: Res<Bindings>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    commands.insert_resource(Wave::default());
-    commands.insert_resource(Level::default());
+    let run = match loaded_run {
+        Some(loaded_run) => {
+            commands.remove_resource::<LoadedRun>();
+            loaded_run.0.clone()
+        }
+        None => new_run(*game_mode, class_registry.get_or_default(&player_class.0)),
+    };
+    let class = class_registry.get_or_default(&run.player_class);
+    player_class.0 = class.name.clone();
+
+    commands.insert_resource(Wave {
+        number: run.wave.saturating_sub(1),
+    });
+    commands.insert_resource(run.level.clone());
+
+    let player_entity = spawn_player(
+        &mut commands,
+        &handle,
+        &run.player,
+        &run.perks,
+        class,
+        PlayerSlot(0),
+    );
+    let inventory = spawn_inventory(&mut commands, &handle, &run);
+    commands.entity(player_entity).insert(inventory);
 
-    let player_entity = commands
+    if coop.is_active() {
+        let coop_run = new_run(*game_mode, class);
+        let coop_entity = spawn_player(
+            &mut commands,
+            &handle,
+            &coop_run.player,
+            &run.perks,
+            class,
+            PlayerSlot(1),
+        );
+        let inventory = spawn_inventory(&mut commands, &handle, &coop_run);
+        commands.entity(coop_entity).insert((
+            inventory,
+            ActionState::<CombatAction>::default(),
+            InputBuffer::default(),
+        ));
+        match coop.gamepad {
+            Some(gamepad) => commands
+                .entity(coop_entity)
+                .insert(bindings.gamepad_combat_input_map(gamepad)),
+            None => commands.entity(coop_entity).insert(RemotePlayer),
+        };
+    }
+
+    next_state.set(GameState::Combat);
+}
+
+fn spawn_inventory(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    run: &SaveData,
+) -> PlayerInventory {
+    let guns = run
+        .guns
+        .iter()
+        .enumerate()
+        .map(|(index, gun)| {
+            spawn_inventory_gun(commands, handle, gun, index == run.active_gun_index)
+        })
+        .collect();
+    let health_potions = run
+        .health_potions
+        .iter()
+        .map(|potion| spawn_inventory_potion(commands, handle, potion))
+        .collect();
+    let speed_potions = run
+        .speed_potions
+        .iter()
+        .map(|potion| spawn_inventory_potion(commands, handle, potion))
+        .collect();
+    let armors = run
+        .armors
+        .iter()
+        .enumerate()
+        .map(|(index, armor)| {
+            spawn_inventory_armor(commands, handle, armor, index == run.active_armor_index)
+        })
+        .collect();
+
+    PlayerInventory {
+        guns,
+        active_gun_index: run.active_gun_index,
+        health_potions,
+        speed_potions,
+        armors,
+        active_armor_index: run.active_armor_index,
+    }
+}
+
+fn new_run(game_mode: GameMode, class: &ClassDefinition) -> SaveData {
+    SaveData {
+        version: SAVE_VERSION,
+        game_mode,
+        wave: 0,
+        level: Level::default(),
+        player: class.player.clone(),
+        guns: class.guns.clone(),
+        active_gun_index: 0,
+        health_potions: class.health_potions.clone(),
+        speed_potions: class.speed_potions.clone(),
+        armors: class.armors.clone(),
+        active_armor_index: 0,
+        rng: None,
+        perks: Perks::default(),
+        player_class: class.name.clone(),
+    }
+}
+
+fn spawn_player(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    player: &SavedPlayer,
+    perks: &Perks,
+    class: &ClassDefinition,
+    slot: PlayerSlot,
+) -> Entity {
+    let name = match slot.0 {
+        0 => "Player".to_string(),
+        index => format!("Player {}", index + 1),
+    };
+    commands
         .spawn((
-            Name::new("Player"),
+            Name::new(name),
             Sprite {
                 image: handle.image.clone().unwrap(),
                 texture_atlas: Some(TextureAtlas {
                     layout: handle.layout_16x16.clone().unwrap(),
-                    index: 0,
+                    index: class.sprite_index,
                 }),
+                color: class.color(),
                 ..default()
             },
-            Transform::from_translation(Vec3::new(0.0, 0.0, LAYER2))
+            Transform::from_translation(Vec3::new(slot.0 as f32 * COOP_SPAWN_OFFSET, 0.0, LAYER2))
                 .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
             Player,
-            Health(PLAYER_HEALTH, PLAYER_HEALTH),
-            Speed(PLAYER_SPEED),
-            Defense(1),
-            Gold(100),
-            DamageBoost(0),
-            OriginalColor(Color::WHITE),
+            Health(player.health, player.max_health),
+            Speed(player.speed),
+            Defense(player.defense),
+            Gold(player.gold),
+            DamageBoost(player.damage_boost),
+            (
+                Stats::new([
+                    (StatKind::MaxHealth, player.max_health as f32),
+                    (StatKind::Speed, player.speed as f32),
+                    (StatKind::Defense, player.defense as f32),
+                    (StatKind::DamageBoost, player.damage_boost as f32),
+                ]),
+                StatusEffects::default(),
+                Dash::default(),
+                perks.clone(),
+                ClassSprite(class.sprite_index),
+                class.passive,
+                PassiveTimer(class.passive.timer()),
+                slot,
+                AimTarget::default(),
+            ),
+            OriginalColor(class.color()),
             PlayerState::default(),
             AnimationTimer(Timer::from_seconds(0.15, TimerMode::Repeating)),
             InGameEntity,
+            Interpolated::default(),
         ))
-        .id();
+        .id()
+}
 
-    let default_gun = commands
-        .spawn((
-            Name::new("DefaultGun"),
-            Gun,
-            Value(10),
-            ActiveGun,
-            Sprite {
-                image: handle.image.clone().unwrap(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: handle.layout_16x16.clone().unwrap(),
-                    index: 65,
-                }),
-                ..default()
-            },
-            Description {
-                name: "Apprentice Gun".to_string(),
-                description: format!(
-                    "Damage: {}; Speed: {}; Firing Interval: {}; Bullet Per Shot: {}",
-                    BULLET_DAMAGE, BULLET_SPEED, FIRING_INTERVAL, NUM_BULLETS_PER_SHOT,
-                ),
-            },
-            Transform::from_translation(Vec3::new(0.0, 0.0, LAYER3))
-                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-        ))
-        .id();
+fn spawn_inventory_gun(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    gun: &SavedGun,
+    active: bool,
+) -> Entity {
+    let mut gun_entity = commands.spawn((
+        Name::new("Gun"),
+        Gun,
+        Value(gun.item.value),
+        Sprite {
+            image: handle.image.clone().unwrap(),
+            texture_atlas: Some(TextureAtlas {
+                layout: handle.layout_16x16.clone().unwrap(),
+                index: gun.item.sprite_index,
+            }),
+            ..default()
+        },
+        Description {
+            name: gun.item.name.clone(),
+            description: gun.item.description.clone(),
+        },
+        gun.gun_type,
+        gun.gun_stats.clone(),
+        gun.bullet_stats.clone(),
+        Transform::from_translation(Vec3::new(0.0, 0.0, LAYER3))
+            .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+    ));
+    if active {
+        gun_entity.insert(ActiveGun);
+    } else {
+        gun_entity.insert(Visibility::Hidden);
+    }
+    gun_entity.id()
+}
 
-    let health_potion = commands
+fn spawn_inventory_potion(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    potion: &SavedPotion,
+) -> Entity {
+    let name = match potion.potion_type {
+        PotionType::Health => "HealthPotion",
+        PotionType::Speed => "SpeedPotion",
+    };
+    commands
         .spawn((
-            Name::new("HealthPotion"),
+            Name::new(name),
             Potion,
-            Value(5),
+            Value(potion.item.value),
             Sprite {
                 image: handle.image.clone().unwrap(),
                 texture_atlas: Some(TextureAtlas {
                     layout: handle.layout_16x16.clone().unwrap(),
-                    index: 96,
+                    index: potion.item.sprite_index,
                 }),
-
                 ..default()
             },
             Visibility::Hidden,
             Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            PotionStats {
-                effect_duration: 0.0,
-                effect_amount: 10,
-            },
+            potion.stats.clone(),
             Description {
-                name: "Health Potion".to_string(),
-                description: "Amount: 10".to_string(),
+                name: potion.item.name.clone(),
+                description: potion.item.description.clone(),
             },
-            PotionType::Health,
+            potion.potion_type.clone(),
         ))
-        .id();
-    let speed_potion = commands
-        .spawn((
-            Name::new("SpeedPotion"),
-            Potion,
-            Value(5),
-            Sprite {
-                image: handle.image.clone().unwrap(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: handle.layout_16x16.clone().unwrap(),
-                    index: 97,
-                }),
-
-                ..default()
-            },
-            Visibility::Hidden,
-            Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            PotionStats {
-                effect_duration: 5.0,
-                effect_amount: 10,
-            },
-            Description {
-                name: "Speed Potion".to_string(),
-                description: "Duration: 1.0s; Amount: 10".to_string(),
-            },
-            PotionType::Speed,
-        ))
-        .id();
-
-    let basic_armor = commands
-        .spawn((
-            Name::new("BasicArmor"),
-            Armor,
-            Value(10),
-            ArmorStats {
-                defense: 2,
-                durability: 15,
-            },
-            Sprite {
-                image: handle.image.clone().unwrap(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: handle.layout_16x16.clone().unwrap(),
-                    index: 98,
-                }),
-                ..default()
-            },
-            ActiveArmor,
-            Description {
-                name: "Apprentice Armor".to_string(),
-                description: "Defense: 2, Durability: 15".to_string(),
-            },
-            Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            Visibility::Hidden,
-        ))
-        .id();
-
-    let advanced_armor = commands
-        .spawn((
-            Name::new("AdvancedArmor"),
-            Armor,
-            Value(10),
-            ArmorStats {
-                defense: 3,
-                durability: 20,
-            },
-            Sprite {
-                image: handle.image.clone().unwrap(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: handle.layout_16x16.clone().unwrap(),
-                    index: 99,
-                }),
-                ..default()
-            },
-            Description {
-                name: "Advanced Armor".to_string(),
-                description: "Defense: 3, Durability: 20".to_string(),
-            },
-            Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            Visibility::Hidden,
-        ))
-        .id();
-
-    commands.entity(player_entity).insert(PlayerInventory {
-        guns: vec![default_gun],
-        active_gun_index: 0,
-        health_potions: vec![health_potion],
-        speed_potions: vec![speed_potion],
-        armors: vec![basic_armor, advanced_armor],
-        active_armor_index: 0,
-    });
+        .id()
+}
 
-    next_state.set(GameState::Combat);
+fn spawn_inventory_armor(
+    commands: &mut Commands,
+    handle: &GlobalTextureAtlas,
+    armor: &SavedArmor,
+    active: bool,
+) -> Entity {
+    let mut armor_entity = commands.spawn((
+        Name::new("Armor"),
+        Armor,
+        Value(armor.item.value),
+        armor.stats.clone(),
+        Sprite {
+            image: handle.image.clone().unwrap(),
+            texture_atlas: Some(TextureAtlas {
+                layout: handle.layout_16x16.clone().unwrap(),
+                index: armor.item.sprite_index,
+            }),
+            ..default()
+        },
+        Description {
+            name: armor.item.name.clone(),
+            description: armor.item.description.clone(),
+        },
+        Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        Visibility::Hidden,
+    ));
+    if active {
+        armor_entity.insert(ActiveArmor);
+    }
+    armor_entity.id()
 }
 
-pub fn spawn_background(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
+pub fn spawn_background(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    mut rng: ResMut<GameRng>,
+) {
     let tile_size = TILE_W as f32 * SPRITE_SCALE_FACTOR;
     let width = WW;
     let height = WH;
@@ -227,7 +348,7 @@ pub fn spawn_background(mut commands: Commands, handle: Res<GlobalTextureAtlas>)
                     image: handle.image.clone().unwrap(),
                     texture_atlas: Some(TextureAtlas {
                         layout: handle.layout_16x16.clone().unwrap(),
-                        index: rand::thread_rng().gen_range(8..=13),
+                        index: rng.world.gen_range(8..=13),
                     }),
                     ..default()
                 },
@@ -239,8 +360,12 @@ pub fn spawn_background(mut commands: Commands, handle: Res<GlobalTextureAtlas>)
     }
 }
 
-fn spawn_world_decorations(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
-    let mut rng = rand::thread_rng();
+fn spawn_world_decorations(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    mut rng: ResMut<GameRng>,
+) {
+    let rng = &mut rng.world;
     for _ in 0..NUM_WORLD_DECORATIONS {
         let x = rng.gen_range((-WW + TILE_W as f32)..(WW - TILE_W as f32));
         let y = rng.gen_range((-WH + TILE_H as f32)..(WH - TILE_H as f32));
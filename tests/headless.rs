@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+use fishmans_adventure::{
+    armor::ArmorStats,
+    class::{ClassPassive, PlayerClass},
+    configs::NAVIGATION_REPEAT_DELAY,
+    coop::CoopSession,
+    enemy::{Enemy, EnemyBullet, EnemyRegistry, ExplosionAbility},
+    game_state::GameState,
+    headless::{current_state, headless_app, step_combat},
+    input::{
+        set_combat_input, update_navigation_repeat, CombatAction, MenuAction, NavigationRepeat,
+    },
+    perk::{ChoosePerkEvent, PerkChoices, Perks},
+    player::{
+        Defense, Health, InvincibilityEffect, Player, PlayerInventory, PlayerLevelingUpEvent,
+        PlayerSlot,
+    },
+    resources::{GameMode, GlobalTextureAtlas},
+    stats::Stats,
+    status::{StatusEffects, StatusKind},
+    utils::calculate_defense_percentage,
+};
+use leafwing_input_manager::prelude::{ActionState, InputManagerSystem, InputMap};
+
+struct Arena {
+    app: App,
+    player: Entity,
+    player_position: Vec3,
+    total_defense: u32,
+}
+
+fn cleared_arena() -> Arena {
+    let mut app = headless_app(GameMode::Normal, 42);
+    assert_eq!(step_combat(&mut app, 1), 1);
+
+    let world = app.world_mut();
+    let hostiles = world
+        .query_filtered::<Entity, Or<(With<Enemy>, With<EnemyBullet>)>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in hostiles {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    let (player, player_position, total_defense) = {
+        let (player, transform, defense, inventory) = world
+            .query_filtered::<(Entity, &Transform, &Defense, &PlayerInventory), With<Player>>()
+            .single(world);
+        let armor_defense = inventory
+            .armors
+            .get(inventory.active_armor_index)
+            .and_then(|armor| world.get::<ArmorStats>(*armor))
+            .map_or(0, |armor| armor.defense);
+        (player, transform.translation, defense.0 + armor_defense)
+    };
+    world.entity_mut(player).remove::<InvincibilityEffect>();
+
+    Arena {
+        app,
+        player,
+        player_position,
+        total_defense,
+    }
+}
+
+fn spawn_enemy(app: &mut App, name: &str, position: Vec3) -> Entity {
+    let world = app.world_mut();
+    let builder = world
+        .resource::<EnemyRegistry>()
+        .builder(name)
+        .expect("enemy should be registered");
+    let enemy = world.resource_scope(|world, atlas: Mut<GlobalTextureAtlas>| {
+        builder.spawn(&mut world.commands(), position, &atlas)
+    });
+    world.flush();
+    enemy
+}
+
+fn player_health(app: &App, player: Entity) -> u32 {
+    app.world().get::<Health>(player).unwrap().0
+}
+
+#[test]
+fn bomber_explosion_deals_damage_after_defense() {
+    let mut arena = cleared_arena();
+    let health_before = player_health(&arena.app, arena.player);
+    let bomber = spawn_enemy(
+        &mut arena.app,
+        "Bomber Enemy",
+        arena.player_position + Vec3::new(40.0, 0.0, 0.0),
+    );
+    let world = arena.app.world_mut();
+    let explosion_damage = world
+        .get::<ExplosionAbility>(bomber)
+        .unwrap()
+        .explosion_damage;
+    world.get_mut::<Enemy>(bomber).unwrap().health = 0;
+
+    step_combat(&mut arena.app, 2);
+
+    let expected =
+        (explosion_damage as f32 * calculate_defense_percentage(arena.total_defense)) as u32;
+    assert!(expected > 0);
+    let health_after = player_health(&arena.app, arena.player);
+    assert_eq!(health_before - health_after, expected);
+}
+
+#[test]
+fn bomber_explosion_burns_player() {
+    let mut arena = cleared_arena();
+    let bomber = spawn_enemy(
+        &mut arena.app,
+        "Bomber Enemy",
+        arena.player_position + Vec3::new(40.0, 0.0, 0.0),
+    );
+    let status = arena
+        .app
+        .world()
+        .get::<ExplosionAbility>(bomber)
+        .unwrap()
+        .status
+        .expect("Bomber Enemy explosions should inflict a status");
+    assert_eq!(status.kind, StatusKind::Burn);
+    arena
+        .app
+        .world_mut()
+        .get_mut::<Enemy>(bomber)
+        .unwrap()
+        .health = 0;
+
+    step_combat(&mut arena.app, 3);
+    let effects = arena
+        .app
+        .world()
+        .get::<StatusEffects>(arena.player)
+        .unwrap();
+    assert!(effects.has(StatusKind::Burn));
+    let health_after_explosion = player_health(&arena.app, arena.player);
+
+    let tick_interval = StatusKind::Burn.tick_interval().unwrap();
+    let timestep = arena
+        .app
+        .world()
+        .resource::<Time<Fixed>>()
+        .timestep()
+        .as_secs_f32();
+    step_combat(&mut arena.app, (tick_interval / timestep).ceil() as u32 + 1);
+
+    let expected =
+        (status.magnitude.round() * calculate_defense_percentage(arena.total_defense)) as u32;
+    assert!(expected > 0);
+    let health_after_tick = player_health(&arena.app, arena.player);
+    assert!(health_after_explosion - health_after_tick >= expected);
+}
+
+fn perk_chooser(app: &App) -> Option<Entity> {
+    app.world().resource::<PerkChoices>().player
+}
+
+fn perks_taken(app: &App, player: Entity) -> usize {
+    app.world().get::<Perks>(player).unwrap().taken.len()
+}
+
+#[test]
+fn coop_players_choose_their_own_perks() {
+    let mut app = headless_app(GameMode::Normal, 42);
+    app.insert_resource(CoopSession {
+        gamepad: None,
+        remote: true,
+    });
+    assert_eq!(step_combat(&mut app, 1), 1);
+
+    let world = app.world_mut();
+    let mut players = world
+        .query_filtered::<(&PlayerSlot, Entity), With<Player>>()
+        .iter(world)
+        .map(|(slot, player)| (slot.0, player))
+        .collect::<Vec<_>>();
+    players.sort();
+    let [(0, first), (1, second)] = players[..] else {
+        panic!("co-op run should spawn two players, got {:?}", players);
+    };
+
+    world.send_event(PlayerLevelingUpEvent { new_level: 2 });
+    step_combat(&mut app, 5);
+    assert_eq!(current_state(&app), GameState::LevelUp);
+    app.update();
+    assert_eq!(perk_chooser(&app), Some(first));
+
+    app.world_mut().send_event(ChoosePerkEvent(0));
+    app.update();
+    assert_eq!(perks_taken(&app, first), 1);
+    assert_eq!(perks_taken(&app, second), 0);
+    assert_eq!(perk_chooser(&app), Some(second));
+
+    app.world_mut().send_event(ChoosePerkEvent(0));
+    app.update();
+    app.update();
+    assert_eq!(perks_taken(&app, first), 1);
+    assert_eq!(perks_taken(&app, second), 1);
+    assert_eq!(current_state(&app), GameState::Combat);
+}
+
+#[test]
+fn sniper_loses_steady_aim_when_moving() {
+    let mut app = headless_app(GameMode::Normal, 42);
+    app.insert_resource(PlayerClass("Sniper".to_string()));
+    assert_eq!(step_combat(&mut app, 1), 1);
+
+    let world = app.world_mut();
+    let (player, passive) = world
+        .query_filtered::<(Entity, &ClassPassive), With<Player>>()
+        .single(world);
+    let ClassPassive::SteadyAim { delay, .. } = *passive else {
+        panic!("Sniper should have the Steady Aim passive");
+    };
+    let source = passive.name();
+    let has_boost = |app: &App| {
+        app.world()
+            .get::<Stats>(player)
+            .unwrap()
+            .has_modifier_from(source)
+    };
+
+    let timestep = app
+        .world()
+        .resource::<Time<Fixed>>()
+        .timestep()
+        .as_secs_f32();
+    let delay_ticks = (delay / timestep).ceil() as u32 + 2;
+    assert_eq!(step_combat(&mut app, delay_ticks), delay_ticks);
+    assert!(has_boost(&app));
+
+    app.world_mut().remove_resource::<InputMap<CombatAction>>();
+    set_combat_input(
+        &mut app.world_mut().resource_mut::<ActionState<CombatAction>>(),
+        &[],
+        Vec2::X,
+        Vec2::ZERO,
+    );
+    step_combat(&mut app, 2);
+    assert!(!has_boost(&app));
+}
+
+fn hold_navigate_down(mut action_state: ResMut<ActionState<MenuAction>>) {
+    action_state.press(&MenuAction::NavigateDown);
+}
+
+#[test]
+fn held_navigation_repeats_while_game_time_is_paused() {
+    let mut app = headless_app(GameMode::Normal, 42);
+    assert_eq!(step_combat(&mut app, 1), 1);
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Paused);
+    app.update();
+    assert_eq!(current_state(&app), GameState::Paused);
+    assert!(app.world().resource::<Time<Virtual>>().is_paused());
+
+    app.world_mut().remove_resource::<InputMap<MenuAction>>();
+    app.world_mut()
+        .resource_mut::<ActionState<MenuAction>>()
+        .release(&MenuAction::NavigateDown);
+    app.add_systems(
+        PreUpdate,
+        hold_navigate_down
+            .after(InputManagerSystem::Update)
+            .before(update_navigation_repeat),
+    );
+    let triggered = |app: &App| {
+        app.world()
+            .resource::<NavigationRepeat>()
+            .triggered(MenuAction::NavigateDown)
+    };
+
+    app.update();
+    assert!(triggered(&app));
+
+    let timestep = app
+        .world()
+        .resource::<Time<Fixed>>()
+        .timestep()
+        .as_secs_f32();
+    let repeat_updates = (NAVIGATION_REPEAT_DELAY / timestep).ceil() as u32 + 1;
+    let mut repeats = 0;
+    for _ in 0..repeat_updates {
+        app.update();
+        repeats += triggered(&app) as u32;
+    }
+    assert_eq!(repeats, 1);
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use fishmans_adventure::{
+    game_state::GameState,
+    gun::Bullet,
+    headless::{current_state, HeadlessPlugin},
+    net::{NetClient, NetHost, NetKind, NetPhase, NetPlugin, Snapshot},
+    rng::RunSeed,
+};
+
+fn net_app(state: GameState) -> App {
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugin, NetPlugin))
+        .insert_resource(RunSeed(Some(42)))
+        .insert_state(state);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn run_until(host: &mut App, client: &mut App, done: impl Fn(&App, &App) -> bool) {
+    for _ in 0..600 {
+        host.update();
+        client.update();
+        if done(host, client) {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("Timed out waiting for host and client");
+}
+
+fn latest_snapshot(client: &App) -> Option<&Snapshot> {
+    client.world().resource::<NetClient>().latest_snapshot()
+}
+
+#[test]
+fn host_and_client_exchange_snapshots_over_localhost() {
+    let host = NetHost::bind("127.0.0.1:0").expect("host should bind");
+    let address = host.local_addr().unwrap();
+    let mut host_app = net_app(GameState::MainMenu);
+    host_app.insert_resource(host);
+    let mut client_app = net_app(GameState::Initializing);
+    client_app.insert_resource(NetClient::connect(address).expect("client should connect"));
+
+    run_until(&mut host_app, &mut client_app, |_, client| {
+        client.world().resource::<NetClient>().is_connected() && latest_snapshot(client).is_some()
+    });
+    assert!(host_app.world().resource::<NetHost>().client().is_some());
+    assert_eq!(latest_snapshot(&client_app).unwrap().phase, NetPhase::Lobby);
+
+    host_app
+        .world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Initializing);
+    run_until(&mut host_app, &mut client_app, |_, client| {
+        current_state(client) == GameState::Combat
+            && latest_snapshot(client).is_some_and(|snapshot| {
+                snapshot.phase == NetPhase::Running
+                    && snapshot
+                        .entities
+                        .iter()
+                        .any(|entity| entity.kind == NetKind::Player)
+            })
+    });
+
+    let spawned_at = latest_snapshot(&client_app).unwrap().time;
+    host_app.world_mut().spawn_batch((0..2000).map(|index| {
+        let offset = index as f32 * 1.37;
+        (
+            Bullet,
+            Sprite::default(),
+            Transform::from_xyz(offset, -offset, 5.0),
+        )
+    }));
+    run_until(&mut host_app, &mut client_app, |_, client| {
+        latest_snapshot(client).is_some_and(|snapshot| {
+            snapshot.time > spawned_at
+                && snapshot.entities.len() > 100
+                && snapshot.entities[0].kind == NetKind::Player
+        })
+    });
+}